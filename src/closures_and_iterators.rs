@@ -5,6 +5,15 @@
 
 use std::collections::HashMap;
 
+// 解析器组合子：接收闭包、返回闭包的函数（示例 19-21）
+pub mod combinator;
+
+// 用组合子写的 JSON 解析器（示例 20）
+pub mod json;
+
+// 用组合子解析 Expr 算术语言（示例 21）
+pub mod expr_grammar;
+
 /// 示例 1: 闭包基础语法
 ///
 /// 展示闭包的基本语法和类型推断
@@ -262,6 +271,7 @@ pub fn closure_as_return() {
     println!("  - impl Trait: 静态分发，性能更好");
     println!("  - Box<dyn Trait>: 动态分发，更灵活");
     println!("  - 必须使用 move 捕获环境");
+    println!("  - 返回闭包的函数可以拼出解析器，见示例 19-21 的解析器组合子");
 }
 
 /// 示例 9: 迭代器基础
@@ -569,10 +579,13 @@ pub fn custom_iterator_range() {
 
 /// 示例 16: 实际应用 - 数据处理管道
 ///
-/// 使用迭代器构建数据处理管道
+/// 使用迭代器构建数据处理管道，数据从 CSV 读取（见 lifetimes::csv），
+/// 需要在 main.rs 中同时启用 mod lifetimes
 pub fn practical_data_pipeline() {
     println!("\n=== 示例 16: 实际应用 - 数据处理管道 ===");
 
+    use crate::lifetimes::csv::{self, CsvError, FromRow, Row};
+
     #[derive(Debug)]
     struct Person {
         name: String,
@@ -580,13 +593,27 @@ pub fn practical_data_pipeline() {
         salary: u32,
     }
 
-    let people = vec![
-        Person { name: "Alice".to_string(), age: 30, salary: 50000 },
-        Person { name: "Bob".to_string(), age: 25, salary: 45000 },
-        Person { name: "Charlie".to_string(), age: 35, salary: 60000 },
-        Person { name: "David".to_string(), age: 28, salary: 48000 },
-        Person { name: "Eve".to_string(), age: 32, salary: 55000 },
-    ];
+    impl FromRow<'_> for Person {
+        fn from_row(row: &Row) -> Result<Self, CsvError> {
+            Ok(Person {
+                name: row.field("name")?.into_owned(),
+                age: row.parse("age")?,
+                salary: row.parse("salary")?,
+            })
+        }
+    }
+
+    let input = "name,age,salary\r\n\
+                 Alice,30,50000\r\n\
+                 Bob,25,45000\r\n\
+                 Charlie,35,60000\r\n\
+                 David,28,48000\r\n\
+                 Eve,32,55000\r\n";
+
+    // 读取本身也是迭代器：任何一行出错，collect 就返回那个错误
+    let people: Vec<Person> = csv::read(input)
+        .and_then(|rows| rows.collect())
+        .expect("合法的 CSV");
 
     // 找出年龄大于 28 且薪水大于 50000 的人的名字
     let high_earners: Vec<String> = people.iter()
@@ -595,6 +622,7 @@ pub fn practical_data_pipeline() {
         .map(|p| p.name.clone())
         .collect();
     println!("高收入者: {:?}", high_earners);
+    assert_eq!(high_earners, ["Charlie", "Eve"]);
 
     // 计算平均薪水
     let avg_salary = people.iter()
@@ -615,6 +643,7 @@ pub fn practical_data_pipeline() {
     println!("  - 链式调用多个操作");
     println!("  - 声明式编程风格");
     println!("  - 惰性求值，高效执行");
+    println!("  - 输入也是迭代器：csv::read 之后 collect 成 Result<Vec<_>, _>");
 }
 
 /// 示例 17: 实际应用 - 文本处理
@@ -699,6 +728,266 @@ pub fn practical_lazy_evaluation() {
     println!("  - 内存效率高");
 }
 
+/// 示例 19: 解析器组合子
+///
+/// 解析器是闭包，组合子是接收闭包、返回闭包的函数
+pub fn parser_combinators() {
+    println!("\n=== 示例 19: 解析器组合子 ===");
+
+    use combinator::{
+        PResult, ParseError, alt, char, cut, delimited, many0, map, map_opt, pair, parse_all, preceded,
+        separated_list, tag, take_while1, ws,
+    };
+
+    // 任何签名合适的函数都是解析器，不一定要用组合子
+    fn digit(input: &str) -> PResult<'_, u32> {
+        match input.chars().next().and_then(|c| c.to_digit(10)) {
+            Some(d) => Ok((&input[1..], d)),
+            None => Err(ParseError::new(input, "数字")),
+        }
+    }
+
+    let input = "42 剩下的";
+    println!("digit({:?}) = {:?}", input, digit(input));
+
+    // 组合子把小的解析器拼起来
+    let number = map(many0(digit), |ds: Vec<u32>| ds.into_iter().fold(0, |n, d| n * 10 + d));
+    println!("many0(digit) 再 fold: {:?}", number("2024年"));
+
+    // 结果借用输入：tag 返回的 &str 指向原来的字符串
+    let source = String::from("let x");
+    let keyword = tag("let");
+    let (rest, matched) = keyword(&source).unwrap();
+    println!("tag(\"let\"): 匹配 {:?}，剩下 {:?}，指向输入内部: {}", matched, rest, std::ptr::eq(matched.as_ptr(), source.as_ptr()));
+
+    // 列表: [1, 2, 3]
+    let integer = map_opt(take_while1(|c| c.is_ascii_digit(), "数字"), |s: &str| s.parse::<i64>().ok(), "i64 范围内的整数");
+    let list = delimited(ws(char('[')), separated_list(ws(char(',')), ws(integer)), ws(char(']')));
+    for text in ["[1, 2, 3]", "[ ]", "[1, 2,]", "[1 2]", "[1, 2"] {
+        match parse_all(&list, text) {
+            Ok(items) => println!("  {:<26} -> {:?}", text, items),
+            Err(e) => println!("  {:<26} -> {}", text, e.describe(text)),
+        }
+    }
+
+    // alt 依次尝试，pair 依次执行
+    let unit = alt((tag("毫秒"), tag("ms"), tag("秒"), tag("s")));
+    let duration = pair(take_while1(|c| c.is_ascii_digit(), "数字"), unit);
+    for text in ["500ms", "3秒", "20毫秒", "5分钟"] {
+        match parse_all(&duration, text) {
+            Ok((n, unit)) => println!("  {:<8} -> ({:?}, {:?})", text, n, unit),
+            Err(e) => println!("  {:<8} -> {}", text, e.describe(text)),
+        }
+    }
+
+    // cut：读到 '+' 之后必须有数字；不用 cut 时 many0 回溯到 '+' 之前，错误变成"多余的 '+'"
+    let loose = pair(digit, many0(preceded(char('+'), digit)));
+    let strict = pair(digit, many0(preceded(char('+'), cut(digit))));
+    let text = "1+2+";
+    println!("\n{:?} 不用 cut: {}", text, parse_all(&loose, text).unwrap_err().describe(text));
+    println!("{:?} 用 cut:   {}", text, parse_all(&strict, text).unwrap_err().describe(text));
+
+    // 逐个检查
+    assert_eq!(tag("ab")("abc"), Ok(("c", "ab")));
+    assert_eq!(char('中')("中文"), Ok(("文", '中')));
+    assert_eq!(many0(char('a'))("aab"), Ok(("b", vec!['a', 'a'])));
+    assert_eq!(many0(char('a'))("b"), Ok(("b", vec![])));
+    assert_eq!(alt((char('x'), char('y')))("yes"), Ok(("es", 'y')));
+    assert_eq!(alt((char('x'), char('y')))("no").unwrap_err().to_string(), "期望 'x' 或 'y'");
+    assert_eq!(pair(char('a'), char('b'))("abc"), Ok(("c", ('a', 'b'))));
+    assert_eq!(delimited(char('('), digit, char(')'))("(7)!"), Ok(("!", 7)));
+    assert_eq!(separated_list(char(','), digit)("1,2,3;"), Ok((";", vec![1, 2, 3])));
+    assert_eq!(separated_list(char(','), digit)(";"), Ok((";", vec![])));
+    // alt 报告走得最远的错误：第二个分支读完了 "ab"，在 'x' 处失败
+    let error = alt((tag("abc!"), map(pair(tag("ab"), char('c')), |_| "abc")))("abx").unwrap_err();
+    assert_eq!(error.describe("abx"), "第 3 个字符: 期望 'c'，但遇到了 'x'");
+    println!("✓ tag / char / many0 / alt / map / pair / delimited / separated_list");
+
+    println!("\n三个主题在这里汇合:");
+    println!("  - 生命周期：PResult<'a, O> 中剩下的输入和结果都借用原输入");
+    println!("  - 闭包：组合子返回 move 闭包，捕获作为参数的解析器");
+    println!("  - 泛型和 trait：Parser<'a, O> 是 Fn 的 trait 别名，Alt 为元组实现");
+    println!("  - cut 关掉回溯，让错误停在真正出错的地方");
+}
+
+/// 示例 20: 组合子实战 - JSON 解析器
+///
+/// 完整的 JSON 语法，一百行左右；随机生成的文档打印后再解析，结果不变
+pub fn json_parser() {
+    println!("\n=== 示例 20: 组合子实战 - JSON 解析器 ===");
+
+    use crate::util::XorShift;
+    use json::Json;
+
+    let text = r#"{
+        "名称": "cargo-learn",
+        "版本": 1.5,
+        "标签": ["rust", "教学", "\u4e2d\u6587", "\uD83E\uDD80"],
+        "发布": true,
+        "依赖": {"anyhow": "1", "thiserror": "2"},
+        "说明": "第一行\n第二行\t\"引号\"",
+        "空": null
+    }"#;
+    let doc = json::parse(text).expect("合法的 JSON");
+    println!("紧凑输出: {}", doc);
+    println!("标签: {:?}", doc.get("标签"));
+    println!("依赖.thiserror: {:?}", doc.get("依赖").and_then(|d| d.get("thiserror")));
+    assert_eq!(doc.get("版本"), Some(&Json::Number(1.5)));
+    assert_eq!(
+        doc.get("标签"),
+        Some(&Json::Array(["rust", "教学", "中文", "🦀"].map(|s| Json::String(s.to_string())).to_vec()))
+    );
+
+    println!("\n错误信息:");
+    let bad = [
+        "[1, 2,]",
+        "{\"a\" 1}",
+        "{\"a\": tru}",
+        "01",
+        "-",
+        "1e999",
+        "\"\\x\"",
+        "\"\\uD800\"",
+        "[1] 2",
+        "\"未结束",
+    ];
+    for text in bad {
+        let error = json::parse(text).unwrap_err();
+        println!("  {:<14} {}", text, error.describe(text));
+    }
+
+    // 数字和转义严格按照 RFC 8259
+    assert_eq!(json::parse("-0.5e+2"), Ok(Json::Number(-50.0)));
+    assert_eq!(json::parse(r#""\u00e9\/\b""#), Ok(Json::String("é/\u{8}".to_string())));
+    for text in ["01", "1.", ".5", "+1", "-", "1e", "1e999", "NaN", r#""\uDC00""#, r#""\uD83E\u0041""#, "\"\t\""] {
+        assert!(json::parse(text).is_err(), "{}", text);
+    }
+    assert_eq!(json::parse(r#"{"a": 1, "a": 2}"#).unwrap().get("a"), Some(&Json::Number(2.0)));
+    // 空白只有空格、\t、\n、\r；全角空格、垂直制表符、换页、不换行空格都不算
+    assert_eq!(json::parse(" [1,\t2,\r\n3] "), Ok(Json::Array(vec![Json::Number(1.0), Json::Number(2.0), Json::Number(3.0)])));
+    for text in ["[1,\u{3000}2]", "[1,\u{000B}2]", "\u{000C}1", "{\u{00A0}\"a\": 1}", "1\u{2028}"] {
+        assert!(json::parse(text).is_err(), "{:?}", text);
+    }
+    println!("\n✓ 数字、转义、代理项对、重复的键、只有四种空白");
+
+    // 随机文档：打印再解析，得到同一个值
+    fn random_json(rng: &mut XorShift, depth: u32) -> Json {
+        const TEXTS: [&str; 6] = ["", "a", "中文", "\"引号\"", "\\\n\t\u{1}", "🦀"];
+        let kinds = if depth == 0 { 4 } else { 6 };
+        match rng.below(kinds) {
+            0 => Json::Null,
+            1 => Json::Bool(rng.below(2) == 0),
+            2 => {
+                let numbers = [0.0, -1.0, 3.25, 1e-7, 6.02e23, -123456789.0, 0.1];
+                Json::Number(*rng.pick(&numbers))
+            }
+            3 => Json::String(rng.pick(&TEXTS).to_string()),
+            4 => Json::Array((0..rng.below(4)).map(|_| random_json(rng, depth - 1)).collect()),
+            _ => Json::Object(
+                (0..rng.below(4))
+                    .map(|i| (format!("键{}", i), random_json(rng, depth - 1)))
+                    .collect(),
+            ),
+        }
+    }
+    let mut rng = XorShift::new(0x2545_F491_4F6C_DD1D);
+    for _ in 0..500 {
+        let value = random_json(&mut rng, 4);
+        let printed = value.to_string();
+        assert_eq!(json::parse(&printed).as_ref(), Ok(&value), "{}", printed);
+        // 在记号之间加上空白，结果也一样
+        let spaced = printed.replace(',', " ,\n ").replace(':', " : ");
+        if !printed.contains('"') {
+            assert_eq!(json::parse(&spaced), Ok(value));
+        }
+    }
+    println!("\n✓ 500 个随机文档打印后再解析，得到相同的值");
+
+    println!("\n要点:");
+    println!("  - value / array / object 互相递归，写成普通函数，函数本身就是解析器");
+    println!("  - label 把 \"期望 'null' 或 'true' 或 ...\" 换成 \"期望 JSON 值\"");
+    println!("  - 字符串里普通字符成段借用，转义才单独处理");
+}
+
+/// 示例 21: 组合子实战 - Expr 算术语言
+///
+/// 和 enums::parser 同一门语言的第二个解析器，结果是完整加括号的语法树
+pub fn expr_combinator_parser() {
+    println!("\n=== 示例 21: 组合子实战 - Expr 算术语言 ===");
+
+    use crate::util::XorShift;
+    use expr_grammar::Ast;
+
+    // 优先级和结合性：完整加括号的输出就是解析出来的结构
+    let cases = [
+        ("1 + 2 * 3", "(1 + (2 * 3))"),
+        ("1 - 2 - 3", "((1 - 2) - 3)"),
+        ("8 / 4 / 2", "((8 / 4) / 2)"),
+        ("-2 ^ 2", "(-(2 ^ 2))"),
+        ("2 ^ 3 ^ 2", "(2 ^ (3 ^ 2))"),
+        ("2 ^ -1", "(2 ^ (-1))"),
+        ("(1 + 2) * -x", "((1 + 2) * (-x))"),
+        ("max(1, 半径 ^ 2, x) / 2", "(max(1, (半径 ^ 2), x) / 2)"),
+        ("sqrt(16) - .5e1", "(sqrt(16) - 5)"),
+    ];
+    for (text, expected) in cases {
+        let tree = expr_grammar::parse(text).expect("合法的表达式");
+        println!("  {:<24} => {}", text, tree);
+        assert_eq!(tree.to_string(), expected);
+    }
+
+    println!("\n错误:");
+    for text in ["1 +", "2 * (3", "foo(1,)", "1 2", "3 $ 4"] {
+        let error = expr_grammar::parse(text).unwrap_err();
+        println!("  {:<10} {}", text, error.describe(text));
+    }
+
+    // 随机语法树：打印成完整加括号的文本再解析，得到同一棵树；
+    // 删掉一个字符后可能不再合法，但解析器不能 panic
+    fn random_ast(rng: &mut XorShift, depth: u32) -> Ast {
+        const NUMBERS: [f64; 4] = [0.0, 1.0, 2.5, 10.0];
+        const NAMES: [&str; 3] = ["x", "半径", "_y2"];
+        if depth == 0 || rng.below(4) == 0 {
+            return match rng.below(2) {
+                0 => Ast::Number(*rng.pick(&NUMBERS)),
+                _ => Ast::Var(rng.pick(&NAMES).to_string()),
+            };
+        }
+        match rng.below(4) {
+            0 => Ast::Neg(Box::new(random_ast(rng, depth - 1))),
+            1 => {
+                let args = (0..rng.below(3)).map(|_| random_ast(rng, depth - 1)).collect();
+                Ast::Call(String::from("max"), args)
+            }
+            _ => {
+                let op = *rng.pick(&['+', '-', '*', '/', '^']);
+                Ast::Binary(op, Box::new(random_ast(rng, depth - 1)), Box::new(random_ast(rng, depth - 1)))
+            }
+        }
+    }
+    let mut rng = XorShift::new(0x9E37_79B9_7F4A_7C15);
+    let mut broken = 0;
+    for _ in 0..2000 {
+        let tree = random_ast(&mut rng, 5);
+        let text = tree.to_string();
+        assert_eq!(expr_grammar::parse(&text).as_ref(), Ok(&tree), "{}", text);
+
+        let chars: Vec<char> = text.chars().collect();
+        let cut = rng.below(chars.len() as u64) as usize;
+        let damaged: String = chars[..cut].iter().chain(&chars[cut + 1..]).collect();
+        if let Err(error) = expr_grammar::parse(&damaged) {
+            assert!(error.describe(&damaged).contains('期'), "{:?}", damaged);
+            broken += 1;
+        }
+    }
+    println!("\n✓ 2000 棵随机语法树打印后再解析得到同一棵树；删掉一个字符后有 {} 个不再合法", broken);
+
+    println!("\n对比:");
+    println!("  - enums::parser 先分词，再用优先级爬升，错误带 Span");
+    println!("  - 组合子版本每个优先级一个函数，语法就写在代码的形状里");
+    println!("  - 左结合用 many0 收集 (运算符, 操作数)，再用 fold 从左往右合成");
+}
+
 /// 运行所有示例
 pub fn run_all_examples() {
     println!("\n╔════════════════════════════════════════╗");
@@ -723,6 +1012,9 @@ pub fn run_all_examples() {
     practical_data_pipeline();
     practical_text_processing();
     practical_lazy_evaluation();
+    parser_combinators();
+    json_parser();
+    expr_combinator_parser();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  闭包和迭代器是 Rust 的强大特性！    ║");
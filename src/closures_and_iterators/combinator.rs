@@ -0,0 +1,326 @@
+//! # 解析器组合子
+//!
+//! 仿照 nom 的思路，只用标准库。一个解析器就是一个闭包：
+//!
+//! ```text
+//! Fn(&'a str) -> Result<(&'a str, O), ParseError>
+//!      输入             剩下的输入  结果
+//! ```
+//!
+//! 组合子接收解析器、返回新的解析器（返回闭包的函数），小的解析器拼成大的：
+//!
+//! ```text
+//! let list = delimited(char('['), separated_list(char(','), number), char(']'));
+//! list("[1,2,3] 后面") == Ok((" 后面", vec![1, 2, 3]))
+//! ```
+//!
+//! - 生命周期：剩下的输入和 `tag` 之类的结果都是原输入的切片，`'a` 把它们联系起来
+//! - 闭包：每个组合子用 `move` 闭包捕获传进来的解析器
+//! - 泛型和 trait：`Parser<'a, O>` 是 `Fn` 的别名，`Alt` 为元组实现，`alt((a, b, c))` 依次尝试
+//!
+//! `alt`、`opt`、`many0` 在失败时回溯，试下一个分支或者停下来。读到 `+` 之后缺少操作数时，
+//! 回溯会把错误变成含糊的"期望输入结束"；用 `cut` 标记"已经确定是这个分支"，错误原样向上传递。
+
+use std::fmt;
+
+/// 解析结果：成功时返回剩下的输入和结果
+pub type PResult<'a, O> = Result<(&'a str, O), ParseError>;
+
+/// 所有 `Fn(&'a str) -> PResult<'a, O>` 的闭包和函数都是解析器
+pub trait Parser<'a, O>: Fn(&'a str) -> PResult<'a, O> {}
+
+impl<'a, O, F> Parser<'a, O> for F where F: Fn(&'a str) -> PResult<'a, O> {}
+
+/// 解析失败
+///
+/// 位置记为剩下的输入的长度：组合子只看得到剩下的部分，看不到完整的输入，
+/// 用 `offset` 换算成完整输入中的位置。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 出错时剩下的输入还有多少字节
+    pub remaining: usize,
+    /// 这个位置上可以接受的内容
+    pub expected: Vec<String>,
+    /// 由 `cut` 标记：不再回溯，`alt`、`opt`、`many0` 直接返回这个错误
+    pub fatal: bool,
+}
+
+impl ParseError {
+    pub fn new(input: &str, expected: impl Into<String>) -> Self {
+        ParseError {
+            remaining: input.len(),
+            expected: vec![expected.into()],
+            fatal: false,
+        }
+    }
+
+    /// 标记为不可回溯
+    pub fn cut(mut self) -> Self {
+        self.fatal = true;
+        self
+    }
+
+    /// 保留走得更远的错误；位置相同时合并两者的期望
+    pub fn or(mut self, other: ParseError) -> ParseError {
+        if other.remaining < self.remaining {
+            return other;
+        }
+        if other.remaining == self.remaining {
+            for expected in other.expected {
+                if !self.expected.contains(&expected) {
+                    self.expected.push(expected);
+                }
+            }
+        }
+        self
+    }
+
+    /// 在完整输入中的字节下标
+    pub fn offset(&self, source: &str) -> usize {
+        source.len() - self.remaining
+    }
+
+    /// 带位置和实际内容的描述，列号按字符从 1 开始
+    pub fn describe(&self, source: &str) -> String {
+        let offset = self.offset(source);
+        let column = source[..offset].chars().count() + 1;
+        let found = match source[offset..].chars().next() {
+            Some(c) => format!("'{}'", c),
+            None => "输入结束".to_string(),
+        };
+        format!("第 {} 个字符: {}，但遇到了 {}", column, self, found)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "期望 {}", self.expected.join(" 或 "))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 解析整个输入，后面不能有多余的内容
+pub fn parse_all<'a, O>(parser: impl Parser<'a, O>, input: &'a str) -> Result<O, ParseError> {
+    match parser(input)? {
+        ("", output) => Ok(output),
+        (rest, _) => Err(ParseError::new(rest, "输入结束")),
+    }
+}
+
+/// 固定的字符串
+pub fn tag<'a>(expected: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: &'a str| match input.strip_prefix(expected) {
+        Some(rest) => Ok((rest, &input[..expected.len()])),
+        None => Err(ParseError::new(input, format!("'{}'", expected))),
+    }
+}
+
+/// 一个指定的字符
+pub fn char<'a>(expected: char) -> impl Parser<'a, char> {
+    move |input: &'a str| match input.strip_prefix(expected) {
+        Some(rest) => Ok((rest, expected)),
+        None => Err(ParseError::new(input, format!("'{}'", expected))),
+    }
+}
+
+/// 一个满足条件的字符
+pub fn satisfy<'a>(pred: impl Fn(char) -> bool, expected: &'static str) -> impl Parser<'a, char> {
+    move |input: &'a str| match input.chars().next() {
+        Some(c) if pred(c) => Ok((&input[c.len_utf8()..], c)),
+        _ => Err(ParseError::new(input, expected)),
+    }
+}
+
+/// 连续满足条件的字符，可以为空
+pub fn take_while<'a>(pred: impl Fn(char) -> bool) -> impl Parser<'a, &'a str> {
+    move |input: &'a str| {
+        let end = input.find(|c| !pred(c)).unwrap_or(input.len());
+        Ok((&input[end..], &input[..end]))
+    }
+}
+
+/// 连续满足条件的字符，至少一个
+pub fn take_while1<'a>(pred: impl Fn(char) -> bool, expected: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: &'a str| {
+        let end = input.find(|c| !pred(c)).unwrap_or(input.len());
+        if end == 0 {
+            return Err(ParseError::new(input, expected));
+        }
+        Ok((&input[end..], &input[..end]))
+    }
+}
+
+/// 转换结果
+pub fn map<'a, O, O2>(parser: impl Parser<'a, O>, f: impl Fn(O) -> O2) -> impl Parser<'a, O2> {
+    move |input: &'a str| parser(input).map(|(rest, output)| (rest, f(output)))
+}
+
+/// 转换结果，`f` 返回 `None` 时在这个解析器开始的位置报告 `expected`
+pub fn map_opt<'a, O, O2>(
+    parser: impl Parser<'a, O>,
+    f: impl Fn(O) -> Option<O2>,
+    expected: &'static str,
+) -> impl Parser<'a, O2> {
+    move |input: &'a str| {
+        let (rest, output) = parser(input)?;
+        match f(output) {
+            Some(output) => Ok((rest, output)),
+            None => Err(ParseError::new(input, expected)),
+        }
+    }
+}
+
+/// 解析器消费的那一段原文
+pub fn recognize<'a, O>(parser: impl Parser<'a, O>) -> impl Parser<'a, &'a str> {
+    move |input: &'a str| {
+        let (rest, _) = parser(input)?;
+        Ok((rest, &input[..input.len() - rest.len()]))
+    }
+}
+
+/// 失败时不消费输入，返回 `None`
+pub fn opt<'a, O>(parser: impl Parser<'a, O>) -> impl Parser<'a, Option<O>> {
+    move |input: &'a str| match parser(input) {
+        Ok((rest, output)) => Ok((rest, Some(output))),
+        Err(error) if error.fatal => Err(error),
+        Err(_) => Ok((input, None)),
+    }
+}
+
+/// 失败时不再回溯，比如读到 `[` 之后，后面的错误就是数组的错误
+pub fn cut<'a, O>(parser: impl Parser<'a, O>) -> impl Parser<'a, O> {
+    move |input: &'a str| parser(input).map_err(ParseError::cut)
+}
+
+/// 在开始的位置就失败时，用一个名字代替一长串期望（"期望 JSON 值"）；
+/// 已经消费了一部分输入之后的错误和 `cut` 过的错误更具体，原样保留
+pub fn label<'a, O>(parser: impl Parser<'a, O>, name: &'static str) -> impl Parser<'a, O> {
+    move |input: &'a str| {
+        parser(input).map_err(|error| match error.remaining == input.len() && !error.fatal {
+            true => ParseError::new(input, name),
+            false => error,
+        })
+    }
+}
+
+/// 依次执行两个解析器，返回两个结果
+pub fn pair<'a, A, B>(first: impl Parser<'a, A>, second: impl Parser<'a, B>) -> impl Parser<'a, (A, B)> {
+    move |input: &'a str| {
+        let (rest, a) = first(input)?;
+        let (rest, b) = second(rest)?;
+        Ok((rest, (a, b)))
+    }
+}
+
+/// 只保留第二个结果
+pub fn preceded<'a, A, B>(first: impl Parser<'a, A>, second: impl Parser<'a, B>) -> impl Parser<'a, B> {
+    map(pair(first, second), |(_, b)| b)
+}
+
+/// 只保留第一个结果
+pub fn terminated<'a, A, B>(first: impl Parser<'a, A>, second: impl Parser<'a, B>) -> impl Parser<'a, A> {
+    map(pair(first, second), |(a, _)| a)
+}
+
+/// 只保留中间的结果，比如括号里的内容
+pub fn delimited<'a, A, B, C>(
+    open: impl Parser<'a, A>,
+    parser: impl Parser<'a, B>,
+    close: impl Parser<'a, C>,
+) -> impl Parser<'a, B> {
+    preceded(open, terminated(parser, close))
+}
+
+/// 重复零次或多次，直到失败；失败的那一次不消费输入
+pub fn many0<'a, O>(parser: impl Parser<'a, O>) -> impl Parser<'a, Vec<O>> {
+    move |mut input: &'a str| {
+        let mut outputs = Vec::new();
+        loop {
+            match parser(input) {
+                // 成功但没有消费输入，再试一次结果也一样，会陷入死循环
+                Ok((rest, _)) if rest.len() == input.len() => break,
+                Ok((rest, output)) => {
+                    outputs.push(output);
+                    input = rest;
+                }
+                Err(error) if error.fatal => return Err(error),
+                Err(_) => break,
+            }
+        }
+        Ok((input, outputs))
+    }
+}
+
+/// 用分隔符隔开的零个或多个元素
+///
+/// 读到分隔符之后必须有一个元素（自动 `cut`）：`[1, 2,]` 报告 `]` 处缺少元素，而不是缺少 `]`
+pub fn separated_list<'a, S, O>(separator: impl Parser<'a, S>, element: impl Parser<'a, O>) -> impl Parser<'a, Vec<O>> {
+    move |input: &'a str| {
+        let mut outputs = Vec::new();
+        let mut input = match element(input) {
+            Ok((rest, first)) => {
+                outputs.push(first);
+                rest
+            }
+            Err(error) if error.fatal => return Err(error),
+            Err(_) => return Ok((input, outputs)),
+        };
+        loop {
+            match separator(input) {
+                Ok((rest, _)) => {
+                    let (rest, output) = element(rest).map_err(ParseError::cut)?;
+                    outputs.push(output);
+                    input = rest;
+                }
+                Err(error) if error.fatal => return Err(error),
+                Err(_) => return Ok((input, outputs)),
+            }
+        }
+    }
+}
+
+/// 前后的空白（包括换行）都跳过
+pub fn ws<'a, O>(parser: impl Parser<'a, O>) -> impl Parser<'a, O> {
+    delimited(take_while(char::is_whitespace), parser, take_while(char::is_whitespace))
+}
+
+/// 依次尝试的一组解析器，为 2 到 6 个元素的元组实现
+pub trait Alt<'a, O> {
+    fn choice(&self, input: &'a str) -> PResult<'a, O>;
+}
+
+macro_rules! impl_alt {
+    ($first:ident $($rest:ident)+) => {
+        impl<'a, O, $first: Parser<'a, O>, $($rest: Parser<'a, O>),+> Alt<'a, O> for ($first, $($rest),+) {
+            #[allow(non_snake_case)]
+            fn choice(&self, input: &'a str) -> PResult<'a, O> {
+                let ($first, $($rest),+) = self;
+                let mut error = match $first(input) {
+                    Ok(result) => return Ok(result),
+                    Err(error) if error.fatal => return Err(error),
+                    Err(error) => error,
+                };
+                $(
+                    match $rest(input) {
+                        Ok(result) => return Ok(result),
+                        Err(e) if e.fatal => return Err(e),
+                        Err(e) => error = error.or(e),
+                    }
+                )+
+                Err(error)
+            }
+        }
+    };
+}
+
+impl_alt!(A B);
+impl_alt!(A B C);
+impl_alt!(A B C D);
+impl_alt!(A B C D E);
+impl_alt!(A B C D E F);
+
+/// 依次尝试，返回第一个成功的结果；都失败时报告走得最远的错误，`cut` 过的错误直接返回
+pub fn alt<'a, O>(parsers: impl Alt<'a, O>) -> impl Parser<'a, O> {
+    move |input: &'a str| parsers.choice(input)
+}
@@ -0,0 +1,118 @@
+//! # 用解析器组合子解析 `Expr` 算术语言
+//!
+//! `enums::parser` 先分词再用优先级爬升解析；这里直接在字符上按优先级分层，
+//! 每一层是一个函数。语法和 `enums::parser` 相同，结果是本模块自己的 `Ast`，
+//! 这样本模块单独启用时也能编译：
+//!
+//! ```text
+//! expr   := term (('+' | '-') term)*          左结合
+//! term   := unary (('*' | '/') unary)*        左结合
+//! unary  := '-' unary | power                 -2^2 = -(2^2)
+//! power  := atom ('^' unary)?                 右结合：2^3^2 = 2^(3^2)，2^-1 也合法
+//! atom   := NUMBER | IDENT '(' args ')' | IDENT | '(' expr ')'
+//! ```
+//!
+//! 读到运算符、`(` 之后用 `cut` 提交，`1 +` 报告缺少操作数，而不是回溯到 `1` 再报告多余的 `+`。
+//! 错误的位置由 `ParseError` 给出。
+
+use super::combinator::{
+    PResult, ParseError, alt, char, cut, label, many0, map, map_opt, opt, pair, parse_all, preceded, recognize,
+    satisfy, separated_list, take_while, take_while1, terminated, ws,
+};
+use std::fmt;
+
+/// 语法树，形状和 `enums::expr::Expr` 相同；二元运算用运算符字符区分
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ast {
+    Number(f64),
+    Var(String),
+    Neg(Box<Ast>),
+    /// `+ - * / ^`
+    Binary(char, Box<Ast>, Box<Ast>),
+    Call(String, Vec<Ast>),
+}
+
+impl Ast {
+    fn binary(op: char, left: Ast, right: Ast) -> Ast {
+        Ast::Binary(op, Box::new(left), Box::new(right))
+    }
+}
+
+/// 每个运算都加上括号，结构一目了然，重新解析得到同一棵树
+impl fmt::Display for Ast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ast::Number(n) => write!(f, "{}", n),
+            Ast::Var(name) => write!(f, "{}", name),
+            Ast::Neg(inner) => write!(f, "(-{})", inner),
+            Ast::Binary(op, left, right) => write!(f, "({} {} {})", left, op, right),
+            Ast::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// 解析一个完整的表达式
+pub fn parse(input: &str) -> Result<Ast, ParseError> {
+    parse_all(expr, input)
+}
+
+/// 一串左结合的二元运算：第一个操作数之后跟着若干 (运算符, 操作数)，用 fold 从左往右合成
+fn left_assoc(first: Ast, rest: Vec<(char, Ast)>) -> Ast {
+    rest.into_iter().fold(first, |left, (op, right)| Ast::binary(op, left, right))
+}
+
+fn expr(input: &str) -> PResult<'_, Ast> {
+    let op = ws(alt((char('+'), char('-'))));
+    map(pair(term, many0(pair(op, cut(term)))), |(first, rest)| left_assoc(first, rest))(input)
+}
+
+fn term(input: &str) -> PResult<'_, Ast> {
+    let op = ws(alt((char('*'), char('/'))));
+    map(pair(unary, many0(pair(op, cut(unary)))), |(first, rest)| left_assoc(first, rest))(input)
+}
+
+fn unary(input: &str) -> PResult<'_, Ast> {
+    let negate = map(preceded(ws(char('-')), cut(unary)), |e| Ast::Neg(Box::new(e)));
+    label(alt((negate, power)), "数字、变量或 '('")(input)
+}
+
+fn power(input: &str) -> PResult<'_, Ast> {
+    let exponent = preceded(ws(char('^')), cut(unary));
+    map(pair(atom, opt(exponent)), |(base, exponent)| match exponent {
+        Some(exponent) => Ast::binary('^', base, exponent),
+        None => base,
+    })(input)
+}
+
+fn atom(input: &str) -> PResult<'_, Ast> {
+    let args = preceded(ws(char('(')), cut(terminated(separated_list(char(','), expr), ws(char(')')))));
+    let call = map(pair(identifier, args), |(name, args)| Ast::Call(name.to_string(), args));
+    let variable = map(identifier, |name| Ast::Var(name.to_string()));
+    let group = preceded(char('('), cut(terminated(expr, ws(char(')')))));
+    ws(alt((map(number, Ast::Number), call, variable, group)))(input)
+}
+
+/// 字母或 `_` 开头，和 `enums::parser` 的规则一样，可以是中文
+fn identifier(input: &str) -> PResult<'_, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_alphabetic() || c == '_', "变量名"),
+        take_while(|c| c.is_alphanumeric() || c == '_'),
+    ))(input)
+}
+
+/// `12`、`3.5`、`.5`、`1e-3`
+fn number(input: &str) -> PResult<'_, f64> {
+    let digits = || take_while1(|c| c.is_ascii_digit(), "数字");
+    let mantissa = alt((recognize(pair(digits(), opt(preceded(char('.'), opt(digits()))))), recognize(preceded(char('.'), digits()))));
+    let exponent = pair(alt((char('e'), char('E'))), pair(opt(alt((char('+'), char('-')))), digits()));
+    map_opt(recognize(pair(mantissa, opt(exponent))), |s: &str| s.parse().ok(), "数字")(input)
+}
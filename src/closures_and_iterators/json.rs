@@ -0,0 +1,190 @@
+//! # 用解析器组合子写的 JSON 解析器
+//!
+//! 语法完全按照 RFC 8259，每条规则对应一个函数：
+//!
+//! ```text
+//! ws     := (' ' | '\t' | '\n' | '\r')*
+//! value  := ws (null | bool | number | string | array | object) ws
+//! array  := '[' ws (value (',' value)*)? ']'
+//! object := '{' ws (member (',' member)*)? '}'
+//! member := ws string ws ':' value
+//! number := '-'? ('0' | [1-9][0-9]*) ('.' [0-9]+)? ([eE] [+-]? [0-9]+)?
+//! ```
+//!
+//! `value` 和 `array` / `object` 互相递归，所以写成普通函数（函数也实现了 `Fn`），
+//! 每次调用时用组合子现场拼出解析器。读到 `[`、`{`、`"`、`:`、`\\` 之后用 `cut` 提交，
+//! 错误指向真正出错的位置。
+
+use super::combinator::{
+    PResult, ParseError, Parser, alt, char, cut, delimited, label, many0, map, map_opt, opt, pair, parse_all,
+    preceded, recognize, satisfy, separated_list, tag, take_while, take_while1, terminated,
+};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// 保留成员的原始顺序
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// 对象的成员；有重复的键时取最后一个
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// 紧凑的 JSON 文本，可以再解析回来
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// 解析一个完整的 JSON 文档
+pub fn parse(input: &str) -> Result<Json, ParseError> {
+    parse_all(value, input)
+}
+
+fn value(input: &str) -> PResult<'_, Json> {
+    let any = alt((
+        map(tag("null"), |_| Json::Null),
+        map(alt((tag("true"), tag("false"))), |b| Json::Bool(b == "true")),
+        map(number, Json::Number),
+        map(string, Json::String),
+        array,
+        object,
+    ));
+    padded(label(any, "JSON 值"))(input)
+}
+
+/// JSON 的空白只有这四种；`combinator::ws` 跳过任何 Unicode 空白（比如全角空格），这里不能用
+fn json_ws<'a>() -> impl Parser<'a, &'a str> {
+    take_while(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+}
+
+fn padded<'a, O>(parser: impl Parser<'a, O>) -> impl Parser<'a, O> {
+    delimited(json_ws(), parser, json_ws())
+}
+
+fn number(input: &str) -> PResult<'_, f64> {
+    let digits = || take_while1(|c| c.is_ascii_digit(), "数字");
+    // 整数部分不能有前导零：0 或者 1-9 开头
+    let integer = alt((tag("0"), recognize(pair(satisfy(|c| ('1'..='9').contains(&c), "数字"), take_while(|c| c.is_ascii_digit())))));
+    let fraction = preceded(char('.'), digits());
+    let exponent = pair(alt((char('e'), char('E'))), pair(opt(alt((char('+'), char('-')))), digits()));
+    let (rest, text) = recognize(pair(opt(char('-')), pair(integer, pair(opt(fraction), opt(exponent)))))(input)?;
+    // 1e999 之类超出 f64 范围的数字解析成无穷大，JSON 中不允许；语法已经是数字了，不再尝试其他分支
+    match text.parse::<f64>() {
+        Ok(n) if n.is_finite() => Ok((rest, n)),
+        _ => Err(ParseError::new(input, "有限大小的数字").cut()),
+    }
+}
+
+fn string(input: &str) -> PResult<'_, String> {
+    // 普通字符一段一段地借用，只在拼接时复制
+    let literal = map(take_while1(|c| c != '"' && c != '\\' && c >= ' ', "字符"), |s: &str| s.to_string());
+    let escaped = map(preceded(char('\\'), cut(escape)), String::from);
+    let content = map(many0(alt((literal, escaped))), |parts| parts.concat());
+    preceded(char('"'), cut(terminated(content, char('"'))))(input)
+}
+
+/// `\` 后面的部分
+fn escape(input: &str) -> PResult<'_, char> {
+    let any = alt((
+        alt((char('"'), char('\\'), char('/'))),
+        map(char('n'), |_| '\n'),
+        map(char('t'), |_| '\t'),
+        map(char('r'), |_| '\r'),
+        alt((map(char('b'), |_| '\u{8}'), map(char('f'), |_| '\u{c}'))),
+        preceded(char('u'), unicode),
+    ));
+    label(any, "转义字符")(input)
+}
+
+/// `\uXXXX`；基本平面以外的字符写成一对代理项，比如 🦀 是 `\uD83E\uDD80`
+fn unicode(input: &str) -> PResult<'_, char> {
+    let hex4 = || {
+        map_opt(
+            recognize(pair(pair(hex_digit(), hex_digit()), pair(hex_digit(), hex_digit()))),
+            |s: &str| u32::from_str_radix(s, 16).ok(),
+            "4 位十六进制数",
+        )
+    };
+    let surrogate_pair = map_opt(
+        pair(hex4(), preceded(label(tag("\\u"), "低位代理项 \\uDC00-\\uDFFF"), hex4())),
+        |(high, low)| {
+            let valid = (0xD800..0xDC00).contains(&high) && (0xDC00..0xE000).contains(&low);
+            valid.then(|| char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))).flatten()
+        },
+        "有效的代理项对",
+    );
+    alt((surrogate_pair, map_opt(hex4(), char::from_u32, "有效的 Unicode 字符")))(input)
+}
+
+fn hex_digit<'a>() -> impl Parser<'a, char> {
+    satisfy(|c| c.is_ascii_hexdigit(), "十六进制数字")
+}
+
+fn array(input: &str) -> PResult<'_, Json> {
+    let items = separated_list(char(','), value);
+    let body = preceded(json_ws(), terminated(items, char(']')));
+    map(preceded(char('['), cut(body)), Json::Array)(input)
+}
+
+fn object(input: &str) -> PResult<'_, Json> {
+    let member = pair(padded(string), preceded(cut(char(':')), cut(value)));
+    let members = separated_list(char(','), member);
+    let body = preceded(json_ws(), terminated(members, char('}')));
+    map(preceded(char('{'), cut(body)), Json::Object)(input)
+}
@@ -4,6 +4,9 @@
 
 use std::collections::{HashMap, BTreeMap, HashSet, BTreeSet};
 
+// 字符串驻留：用 Symbol 代替重复的字符串，包括线程安全的版本（示例 19-21）
+pub mod interner;
+
 /// 示例 1: Vector 基础
 pub fn vector_basics() {
     println!("\n=== 示例 1: Vector 基础 ===");
@@ -447,16 +450,46 @@ pub fn btreeset_basics() {
 }
 
 /// 示例 16: 实际应用 - 学生成绩管理
+///
+/// 成绩从 CSV 读取（见 lifetimes::csv），需要在 main.rs 中同时启用 mod lifetimes
 pub fn practical_student_scores() {
     println!("\n=== 示例 16: 实际应用 - 学生成绩管理 ===");
 
-    let mut scores: HashMap<String, Vec<i32>> = HashMap::new();
+    use crate::lifetimes::csv::{self, CsvError, FromRow, Row};
+    use std::borrow::Cow;
+
+    // 一行一个成绩，没有转义的姓名直接借用输入
+    struct Grade<'a> {
+        name: Cow<'a, str>,
+        score: i32,
+    }
+
+    impl<'a> FromRow<'a> for Grade<'a> {
+        fn from_row(row: &Row<'_, 'a>) -> Result<Self, CsvError> {
+            Ok(Grade { name: row.field("姓名")?, score: row.parse("分数")? })
+        }
+    }
 
-    // 添加成绩
-    scores.entry(String::from("Alice")).or_insert(Vec::new()).push(85);
-    scores.entry(String::from("Alice")).or_insert(Vec::new()).push(90);
-    scores.entry(String::from("Bob")).or_insert(Vec::new()).push(78);
-    scores.entry(String::from("Bob")).or_insert(Vec::new()).push(82);
+    let input = "姓名,分数\n\
+                 Alice,85\n\
+                 Alice,90\n\
+                 Bob,78\n\
+                 Carol,一百\n\
+                 Bob,82\n\
+                 \"Smith, Dan\",95\n";
+
+    let mut scores: HashMap<Cow<str>, Vec<i32>> = HashMap::new();
+
+    // 添加成绩：每读到一行，就把分数放进这个人的列表；坏的行单独报错
+    for grade in csv::read::<Grade>(input).expect("有表头") {
+        match grade {
+            Ok(grade) => scores.entry(grade.name).or_insert(Vec::new()).push(grade.score),
+            Err(e) => println!("跳过: {}", e),
+        }
+    }
+    assert_eq!(scores["Alice"], [85, 90]);
+    assert_eq!(scores["Bob"], [78, 82]);
+    assert_eq!(scores["Smith, Dan"], [95]);
 
     // 计算平均分
     for (name, score_list) in &scores {
@@ -509,6 +542,202 @@ pub fn practical_text_analysis() {
     let unique_words: HashSet<_> = text.split_whitespace().collect();
     println!("唯一单词数: {}", unique_words.len());
     println!("总单词数: {}", text.split_whitespace().count());
+    println!("用字符串驻留统计、并比较三种键的版本见示例 21");
+}
+
+/// 示例 19: 字符串驻留
+///
+/// 每个不同的字符串只保存一份，用 4 字节的 Symbol 代替
+pub fn string_interner() {
+    println!("\n=== 示例 19: 字符串驻留 ===");
+
+    use interner::Interner;
+
+    let mut interner = Interner::new();
+    let rust = interner.intern("rust");
+    let go = interner.intern("go");
+    let rust_again = interner.intern(&String::from("rust"));
+    println!("rust = {:?}, go = {:?}, 再次驻留 rust = {:?}", rust, go, rust_again);
+    assert_eq!(rust, rust_again);
+    assert_ne!(rust, go);
+    assert_eq!(interner.resolve(go), "go");
+    assert_eq!(interner.get("java"), None);
+    println!("Symbol 占 {} 字节，String 占 {} 字节（还不算堆上的内容）", size_of::<interner::Symbol>(), size_of::<String>());
+
+    // 一个简单的分词器：标识符驻留成符号，句子变成 Vec<Symbol>
+    let source = "let total = price * count; let price = total / count; total";
+    let tokens: Vec<_> = source
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+        .map(|w| interner.intern(w))
+        .collect();
+    println!("\n{:?}", source);
+    println!("记号: {:?}", tokens.iter().map(|s| s.index()).collect::<Vec<_>>());
+    println!("{} 个记号，{} 个不同的字符串", tokens.len(), interner.len());
+
+    // 按驻留的顺序遍历，每次运行都一样
+    print!("驻留表:");
+    for (symbol, text) in interner.iter() {
+        print!(" {}={}", symbol.index(), text);
+    }
+    println!();
+    let order: Vec<&str> = interner.iter().map(|(_, s)| s).collect();
+    assert_eq!(order, ["rust", "go", "let", "total", "price", "count"]);
+
+    // resolve 返回的 &str 借用驻留器：输入释放后，符号和驻留器里的字符串仍然可用
+    let symbols = {
+        let temporary = String::from("count price");
+        temporary.split(' ').map(|w| interner.intern(w)).collect::<Vec<_>>()
+    };
+    let names: Vec<&str> = symbols.iter().map(|&s| interner.resolve(s)).collect();
+    println!("输入释放后解析: {:?}", names);
+    // interner.intern("x"); // 错误：names 还借用着 interner，不能再可变借用
+    assert!(!interner.is_empty());
+
+    println!("\n要点:");
+    println!("  - 比较和哈希 Symbol 只是比较一个 u32");
+    println!("  - resolve 的结果借用 Interner，借用期间不能再驻留新的字符串");
+    println!("  - 编号连续分配，可以直接当作 Vec 的下标");
+}
+
+/// 示例 20: 线程安全的字符串驻留
+///
+/// 多个线程共享一个 SyncInterner，同一个字符串在所有线程中得到同一个符号
+pub fn sync_string_interner() {
+    println!("\n=== 示例 20: 线程安全的字符串驻留 ===");
+
+    use interner::{Symbol, SyncInterner};
+    use std::sync::Arc;
+    use std::thread;
+
+    let interner = Arc::new(SyncInterner::new());
+    let words = ["苹果", "香蕉", "橙子", "葡萄", "西瓜", "apple", "banana", "orange"];
+
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let interner = Arc::clone(&interner);
+            thread::spawn(move || {
+                // 每个线程按不同的顺序驻留同一组单词，重复多次
+                (0..1000)
+                    .map(|i| {
+                        let word = words[(i * (t + 1) + t) % words.len()];
+                        (word, interner.intern(word))
+                    })
+                    .collect::<Vec<(&str, Symbol)>>()
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+
+    // 所有线程对同一个单词得到同一个符号，解析回来是原来的单词
+    for &(word, symbol) in &results {
+        assert_eq!(&*interner.resolve(symbol), word);
+    }
+    assert_eq!(interner.len(), words.len());
+    let snapshot = interner.snapshot();
+    // 编号从 0 开始连续，每个单词只出现一次
+    assert!(snapshot.iter().enumerate().all(|(i, (symbol, _))| symbol.index() == i));
+    let mut texts: Vec<&str> = snapshot.iter().map(|(_, s)| &**s).collect();
+    texts.sort();
+    let mut expected = words.to_vec();
+    expected.sort();
+    assert_eq!(texts, expected);
+    println!("4 个线程驻留了 {} 次，得到 {} 个符号:", results.len(), interner.len());
+    for (symbol, text) in &snapshot {
+        println!("  {:?} = {}", symbol, text);
+    }
+    println!("✓ 同一个单词在所有线程中的符号相同，编号连续");
+
+    let empty = SyncInterner::new();
+    assert!(empty.is_empty());
+
+    println!("\n要点:");
+    println!("  - 先用读锁查找，找不到再拿写锁，拿到写锁后还要再查一次");
+    println!("  - 字符串在锁里面，resolve 返回 Arc<str> 而不是 &str");
+    println!("  - 编号的顺序取决于线程的调度，但同一个字符串的编号只分配一次");
+}
+
+/// 示例 21: 单词计数的三种键
+///
+/// 示例 18 的单词频率统计，比较 HashMap<&str, usize>、HashMap<String, usize> 和 HashMap<Symbol, usize>
+pub fn word_count_benchmark() {
+    println!("\n=== 示例 21: 单词计数的三种键 ===");
+
+    use crate::util::XorShift;
+    use interner::{Interner, Symbol};
+    use std::time::Instant;
+
+    // 生成一段文本：2000 个不同的单词，编号小的单词出现得多
+    let mut rng = XorShift::new(42);
+    let vocabulary: Vec<String> = (0..2000)
+        .map(|i| {
+            let len = 3 + (i % 9);
+            (0..len).map(|j| (b'a' + ((i * 7 + j * 13) % 26) as u8) as char).collect::<String>() + &i.to_string()
+        })
+        .collect();
+    let words: Vec<&str> = (0..300_000)
+        .map(|_| &*vocabulary[rng.below(2000).min(rng.below(2000)) as usize])
+        .collect();
+    let text = words.join(" ");
+
+    // 1. 借用输入的 &str：不分配，但结果不能比 text 活得更久
+    let start = Instant::now();
+    let mut by_str: HashMap<&str, usize> = HashMap::new();
+    for word in text.split_whitespace() {
+        *by_str.entry(word).or_insert(0) += 1;
+    }
+    let str_time = start.elapsed();
+
+    // 2. String：每个单词都复制一份，即使键已经存在
+    let start = Instant::now();
+    let mut by_string: HashMap<String, usize> = HashMap::new();
+    for word in text.split_whitespace() {
+        *by_string.entry(word.to_string()).or_insert(0) += 1;
+    }
+    let string_time = start.elapsed();
+
+    // 3. Symbol：每个不同的单词只复制一次，计数时只哈希一个 u32
+    let start = Instant::now();
+    let mut interner = Interner::new();
+    let mut by_symbol: HashMap<Symbol, usize> = HashMap::new();
+    for word in text.split_whitespace() {
+        *by_symbol.entry(interner.intern(word)).or_insert(0) += 1;
+    }
+    let symbol_time = start.elapsed();
+
+    // 3b. 先把文本转成符号序列，之后的统计（可以重复很多次）不再碰字符串
+    let tokens: Vec<Symbol> = text.split_whitespace().map(|w| interner.intern(w)).collect();
+    let start = Instant::now();
+    let mut by_index = vec![0usize; interner.len()];
+    for symbol in &tokens {
+        by_index[symbol.index()] += 1;
+    }
+    let index_time = start.elapsed();
+
+    // 三种方法的结果相同
+    assert_eq!(by_str.len(), by_string.len());
+    assert_eq!(by_str.len(), by_symbol.len());
+    for (word, &count) in &by_str {
+        assert_eq!(by_string[*word], count);
+        assert_eq!(by_symbol[&interner.get(word).unwrap()], count);
+        assert_eq!(by_index[interner.get(word).unwrap().index()], count);
+    }
+
+    println!("{} 个单词，{} 个不同的单词:", words.len(), by_str.len());
+    println!("  {:<26} {:>12?}  不分配，借用 text", "HashMap<&str, usize>", str_time);
+    println!("  {:<26} {:>12?}  每个单词分配一次", "HashMap<String, usize>", string_time);
+    println!("  {:<26} {:>12?}  每个不同的单词分配一次", "HashMap<Symbol, usize>", symbol_time);
+    println!("  {:<26} {:>12?}  已经转成符号，用符号作下标计数", "Vec<usize>", index_time);
+
+    let mut top: Vec<(Symbol, usize)> = by_symbol.into_iter().collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let top: Vec<String> = top.iter().take(3).map(|&(s, n)| format!("{}×{}", interner.resolve(s), n)).collect();
+    println!("最常见的单词: {}", top.join(", "));
+
+    println!("\n说明:");
+    println!("  - 用 cargo run --release 运行时的时间才有参考价值，debug 构建没有优化");
+    println!("  - &str 通常最快，但结果绑定在输入上；String 的结果独立，代价是每个单词一次分配");
+    println!("  - Symbol 兼顾两者：结果独立于输入，键只有 4 字节，同一批符号可以反复统计");
 }
 
 /// 运行所有示例
@@ -535,6 +764,9 @@ pub fn run_all_examples() {
     practical_student_scores();
     practical_dedup_and_sort();
     practical_text_analysis();
+    string_interner();
+    sync_string_interner();
+    word_count_benchmark();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  集合类型是 Rust 程序的基础工具！   ║");
@@ -0,0 +1,140 @@
+//! # 字符串驻留（string interning）
+//!
+//! 文本分析和词法分析中同一个单词会出现成千上万次。驻留把每个不同的字符串只保存一份，
+//! 用一个 4 字节的 `Symbol` 代替它：
+//!
+//! - 比较和哈希 `Symbol` 只是比较一个 `u32`，不用逐字节比较字符串
+//! - `HashMap<Symbol, usize>` 的键不需要分配，也不借用输入，可以比输入活得更久
+//! - `resolve` 返回的 `&str` 借用 `Interner`，`Interner` 还在就一直有效
+//!
+//! 每个字符串在 `Interner` 中只分配一次：哈希表的键和按编号排列的列表共享同一个 `Rc<str>`。
+//! `SyncInterner` 是线程安全的版本，用 `RwLock` 保护，字符串换成 `Arc<str>`。
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+
+/// 驻留后的字符串，只在产生它的驻留器中有意义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// 编号，从 0 开始连续分配，可以直接当作 `Vec` 的下标
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// 下一个编号；超过 `u32` 的范围时 panic
+fn next_symbol(len: usize) -> Symbol {
+    Symbol(u32::try_from(len).expect("驻留的字符串超过了 u32::MAX 个"))
+}
+
+#[derive(Debug, Default)]
+pub struct Interner {
+    map: HashMap<Rc<str>, Symbol>,
+    /// 第 i 个元素是 `Symbol(i)` 对应的字符串
+    strings: Vec<Rc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// 返回字符串的符号；第一次见到时复制一份保存起来
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&symbol) = self.map.get(s) {
+            return symbol;
+        }
+        let symbol = next_symbol(self.strings.len());
+        let s: Rc<str> = Rc::from(s);
+        self.strings.push(Rc::clone(&s));
+        self.map.insert(s, symbol);
+        symbol
+    }
+
+    /// 只查找，不驻留
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.map.get(s).copied()
+    }
+
+    /// 符号对应的字符串，借用驻留器
+    ///
+    /// # Panics
+    /// 符号来自别的驻留器、编号超出范围时 panic
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// 按驻留的顺序（也就是编号的顺序）遍历，顺序是确定的
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> {
+        self.strings.iter().enumerate().map(|(i, s)| (Symbol(i as u32), &**s))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    map: HashMap<Arc<str>, Symbol>,
+    strings: Vec<Arc<str>>,
+}
+
+/// 线程安全的驻留器，可以放在 `Arc` 中被多个线程共享
+///
+/// 字符串在锁里面，不能返回借用它的 `&str`（锁释放后引用就不受保护了），
+/// 所以 `resolve` 返回 `Arc<str>`，复制它只是增加引用计数。
+#[derive(Debug, Default)]
+pub struct SyncInterner {
+    table: RwLock<Table>,
+}
+
+impl SyncInterner {
+    pub fn new() -> Self {
+        SyncInterner::default()
+    }
+
+    /// 先用读锁查找，大部分单词都已经驻留过；找不到时再拿写锁
+    pub fn intern(&self, s: &str) -> Symbol {
+        if let Some(&symbol) = self.table.read().unwrap().map.get(s) {
+            return symbol;
+        }
+        let mut table = self.table.write().unwrap();
+        // 释放读锁到拿到写锁之间，别的线程可能已经驻留了同一个字符串
+        if let Some(&symbol) = table.map.get(s) {
+            return symbol;
+        }
+        let symbol = next_symbol(table.strings.len());
+        let s: Arc<str> = Arc::from(s);
+        table.strings.push(Arc::clone(&s));
+        table.map.insert(s, symbol);
+        symbol
+    }
+
+    /// # Panics
+    /// 符号来自别的驻留器、编号超出范围时 panic
+    pub fn resolve(&self, symbol: Symbol) -> Arc<str> {
+        Arc::clone(&self.table.read().unwrap().strings[symbol.index()])
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.read().unwrap().strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.read().unwrap().strings.is_empty()
+    }
+
+    /// 当前所有字符串的快照，按编号排列
+    pub fn snapshot(&self) -> Vec<(Symbol, Arc<str>)> {
+        let table = self.table.read().unwrap();
+        table.strings.iter().enumerate().map(|(i, s)| (Symbol(i as u32), Arc::clone(s))).collect()
+    }
+}
@@ -1,8 +1,13 @@
-/// Rust 并发编程教学代码
-///
-/// 并发编程允许程序同时执行多个任务
-/// Rust 的类型系统保证了并发安全
+//! Rust 并发编程教学代码
+//!
+//! 并发编程允许程序同时执行多个任务
+//! Rust 的类型系统保证了并发安全
+
+pub mod actor;
+pub mod lock_free;
+pub mod rate_limit;
 
+use rate_limit::RateLimiter;
 use std::sync::{Arc, Mutex, RwLock, mpsc};
 use std::thread;
 use std::time::Duration;
@@ -446,7 +451,7 @@ pub fn thread_local_storage() {
     use std::cell::RefCell;
 
     thread_local! {
-        static COUNTER: RefCell<u32> = RefCell::new(0);
+        static COUNTER: RefCell<u32> = const { RefCell::new(0) };
     }
 
     let mut handles = vec![];
@@ -506,67 +511,84 @@ pub fn scoped_threads() {
     println!("  - 避免 Arc 和 move");
 }
 
-/// 示例 15: 简单的线程池
-///
-/// 使用通道实现简单的线程池
-pub fn simple_thread_pool() {
-    println!("\n=== 示例 15: 简单的线程池 ===");
+/// 线程池中的任务
+type Job = Box<dyn FnOnce() + Send + 'static>;
 
-    type Job = Box<dyn FnOnce() + Send + 'static>;
+/// 简单的线程池
+///
+/// 定义在模块级别，示例 15 演示基本用法，示例 24 在此基础上做限流
+struct ThreadPool {
+    workers: Vec<Option<thread::JoinHandle<()>>>,
+    sender: Option<mpsc::Sender<Job>>,
+}
 
-    struct ThreadPool {
-        workers: Vec<Option<thread::JoinHandle<()>>>,
-        sender: Option<mpsc::Sender<Job>>,
+impl ThreadPool {
+    fn new(size: usize) -> ThreadPool {
+        ThreadPool::build(size, None)
     }
 
-    impl ThreadPool {
-        fn new(size: usize) -> ThreadPool {
-            let (sender, receiver) = mpsc::channel::<Job>();
-            let receiver = Arc::new(Mutex::new(receiver));
-            let mut workers = Vec::with_capacity(size);
+    /// 创建限流的线程池：工作线程每执行一个任务前都要先从限流器拿到许可
+    fn throttled(size: usize, limiter: Arc<dyn RateLimiter>) -> ThreadPool {
+        ThreadPool::build(size, Some(limiter))
+    }
 
-            for id in 0..size {
-                let receiver = Arc::clone(&receiver);
-                let worker = thread::spawn(move || loop {
-                    let job = receiver.lock().unwrap().recv();
-                    match job {
-                        Ok(job) => {
-                            println!("工作线程 {} 执行任务", id);
-                            job();
-                        }
-                        Err(_) => {
-                            println!("工作线程 {} 关闭", id);
-                            break;
+    fn build(size: usize, limiter: Option<Arc<dyn RateLimiter>>) -> ThreadPool {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut workers = Vec::with_capacity(size);
+
+        for id in 0..size {
+            let receiver = Arc::clone(&receiver);
+            let limiter = limiter.clone();
+            let worker = thread::spawn(move || loop {
+                let job = receiver.lock().unwrap().recv();
+                match job {
+                    Ok(job) => {
+                        if let Some(limiter) = &limiter {
+                            limiter.acquire();
                         }
+                        println!("工作线程 {} 执行任务", id);
+                        job();
                     }
-                });
-                workers.push(Some(worker));
-            }
-
-            ThreadPool {
-                workers,
-                sender: Some(sender),
-            }
+                    Err(_) => {
+                        println!("工作线程 {} 关闭", id);
+                        break;
+                    }
+                }
+            });
+            workers.push(Some(worker));
         }
 
-        fn execute<F>(&self, f: F)
-        where
-            F: FnOnce() + Send + 'static,
-        {
-            self.sender.as_ref().unwrap().send(Box::new(f)).unwrap();
+        ThreadPool {
+            workers,
+            sender: Some(sender),
         }
     }
 
-    impl Drop for ThreadPool {
-        fn drop(&mut self) {
-            drop(self.sender.take());
-            for worker in &mut self.workers {
-                if let Some(thread) = worker.take() {
-                    thread.join().unwrap();
-                }
+    fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.sender.as_ref().unwrap().send(Box::new(f)).unwrap();
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.take() {
+                thread.join().unwrap();
             }
         }
     }
+}
+
+/// 示例 15: 简单的线程池
+///
+/// 使用通道实现简单的线程池
+pub fn simple_thread_pool() {
+    println!("\n=== 示例 15: 简单的线程池 ===");
 
     // 使用线程池
     let pool = ThreadPool::new(4);
@@ -689,6 +711,554 @@ pub fn practical_producer_consumer() {
     println!("  - 通道天然支持此模式");
 }
 
+/// 示例 18: 无锁栈（Treiber Stack）
+///
+/// 用 AtomicPtr + compare_exchange 实现多线程共享的栈
+pub fn lock_free_treiber_stack() {
+    println!("\n=== 示例 18: 无锁栈（Treiber Stack） ===");
+
+    use lock_free::TreiberStack;
+
+    // 单线程下和普通栈一样：后进先出
+    let stack = TreiberStack::new();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    println!("弹出: {:?}", stack.pop());
+    println!("弹出: {:?}", stack.pop());
+    println!("弹出: {:?}", stack.pop());
+    println!("弹出: {:?}（栈为空）", stack.pop());
+
+    // 压力测试：4 个线程 push，4 个线程 pop
+    // 每个值都必须恰好被取出一次，不能丢失也不能重复
+    let producers = 4;
+    let per_producer = 10_000;
+    let stack = Arc::new(TreiberStack::new());
+
+    let mut handles = vec![];
+    for p in 0..producers {
+        let stack = Arc::clone(&stack);
+        handles.push(thread::spawn(move || {
+            for i in 0..per_producer {
+                stack.push(p * per_producer + i);
+            }
+            Vec::new()
+        }));
+    }
+    for _ in 0..producers {
+        let stack = Arc::clone(&stack);
+        handles.push(thread::spawn(move || {
+            let mut popped = Vec::new();
+            for _ in 0..per_producer {
+                if let Some(value) = stack.pop() {
+                    popped.push(value);
+                }
+            }
+            popped
+        }));
+    }
+
+    let mut all: Vec<usize> = handles
+        .into_iter()
+        .flat_map(|h| h.join().unwrap())
+        .collect();
+    // 消费者可能比生产者跑得快，剩下的值在主线程取完
+    while let Some(value) = stack.pop() {
+        all.push(value);
+    }
+    all.sort_unstable();
+
+    let expected: Vec<usize> = (0..producers * per_producer).collect();
+    assert_eq!(all, expected, "每个值都应该恰好弹出一次");
+    assert!(stack.is_empty());
+    println!("压力测试: {} 个值全部恰好弹出一次 ✓", all.len());
+
+    println!("\nTreiber 栈:");
+    println!("  - push/pop 都是 CAS 循环，失败就重试");
+    println!("  - push 成功用 Release 发布节点，pop 用 Acquire 读取节点（见示例 10）");
+    println!("  - 弹出的节点延迟到栈销毁时释放，避免 use-after-free 和 ABA 问题");
+}
+
+/// 示例 19: 单生产者单消费者环形缓冲区
+///
+/// 只有一个写者时，不需要 CAS，load + store 就能实现无锁队列
+pub fn lock_free_spsc_ring_buffer() {
+    println!("\n=== 示例 19: 单生产者单消费者环形缓冲区 ===");
+
+    use lock_free::spsc;
+
+    // 容量为 2 的缓冲区，满了之后 push 会把值退回
+    let (mut producer, mut consumer) = spsc::channel(2);
+    println!("容量: {}", producer.capacity());
+    println!("push(\"a\"): {:?}", producer.push("a"));
+    println!("push(\"b\"): {:?}", producer.push("b"));
+    println!("push(\"c\"): {:?}（已满）", producer.push("c"));
+    println!("pop(): {:?}", consumer.pop());
+    println!("push(\"c\"): {:?}", producer.push("c"));
+    println!("pop(): {:?}", consumer.pop());
+    println!("pop(): {:?}", consumer.pop());
+    println!("pop(): {:?}（已空）", consumer.pop());
+
+    // 压力测试：生产者按顺序写入 0..N，消费者必须按相同顺序读出
+    let total = 100_000u64;
+    let (mut producer, mut consumer) = spsc::channel(64);
+
+    let producer_handle = thread::spawn(move || {
+        for i in 0..total {
+            let mut value = i;
+            // 缓冲区满时让出 CPU，等待消费者
+            while let Err(v) = producer.push(value) {
+                value = v;
+                thread::yield_now();
+            }
+        }
+    });
+
+    let consumer_handle = thread::spawn(move || {
+        let mut expected = 0;
+        let mut sum = 0;
+        while expected < total {
+            match consumer.pop() {
+                Some(value) => {
+                    assert_eq!(value, expected, "SPSC 队列必须保持 FIFO 顺序");
+                    sum += value;
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        sum
+    });
+
+    producer_handle.join().unwrap();
+    let sum = consumer_handle.join().unwrap();
+    assert_eq!(sum, total * (total - 1) / 2);
+    println!("压力测试: {} 个值按顺序到达，总和 {} ✓", total, sum);
+
+    println!("\nSPSC 环形缓冲区:");
+    println!("  - tail 只由生产者写，head 只由消费者写");
+    println!("  - 写入数据后 store(tail, Release)，读取前 load(tail, Acquire)");
+    println!("  - Producer / Consumer 句柄不能 Clone，类型系统保证单生产者单消费者");
+}
+
+/// 示例 20: 无锁结构与锁、通道的性能对比
+///
+/// 相同的工作量分别交给无锁结构和传统同步方式完成
+pub fn lock_free_benchmark() {
+    println!("\n=== 示例 20: 无锁结构与锁、通道的性能对比 ===");
+
+    use lock_free::{TreiberStack, spsc};
+    use std::time::Instant;
+
+    let threads = 4;
+    let ops = 50_000;
+
+    // 多线程栈：TreiberStack vs Mutex<Vec<T>>
+    let stack = Arc::new(TreiberStack::new());
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..ops {
+                    stack.push(i);
+                    stack.pop();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let treiber_duration = start.elapsed();
+
+    let stack = Arc::new(Mutex::new(Vec::new()));
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..ops {
+                    stack.lock().unwrap().push(i);
+                    stack.lock().unwrap().pop();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let mutex_duration = start.elapsed();
+
+    println!("{} 个线程各 push+pop {} 次:", threads, ops);
+    println!("  TreiberStack:   {:?}", treiber_duration);
+    println!("  Mutex<Vec<T>>:  {:?}", mutex_duration);
+
+    // 单生产者单消费者：环形缓冲区 vs mpsc::sync_channel
+    let total = 200_000;
+
+    let (mut producer, mut consumer) = spsc::channel(1024);
+    let start = Instant::now();
+    let handle = thread::spawn(move || {
+        for i in 0..total {
+            let mut value = i;
+            while let Err(v) = producer.push(value) {
+                value = v;
+                thread::yield_now();
+            }
+        }
+    });
+    let mut received = 0;
+    while received < total {
+        match consumer.pop() {
+            Some(_) => received += 1,
+            None => thread::yield_now(),
+        }
+    }
+    handle.join().unwrap();
+    let ring_duration = start.elapsed();
+
+    let (tx, rx) = mpsc::sync_channel(1024);
+    let start = Instant::now();
+    let handle = thread::spawn(move || {
+        for i in 0..total {
+            tx.send(i).unwrap();
+        }
+    });
+    let received = rx.iter().count();
+    handle.join().unwrap();
+    let channel_duration = start.elapsed();
+    assert_eq!(received, total);
+
+    println!("单生产者单消费者传递 {} 个值:", total);
+    println!("  spsc 环形缓冲区:     {:?}", ring_duration);
+    println!("  mpsc::sync_channel:  {:?}", channel_duration);
+
+    println!("\n性能对比:");
+    println!("  - 结果受 CPU 核数和编译模式影响，建议使用 cargo run --release 观察");
+    println!("  - 无锁不等于更快：高争用时 CAS 会不断失败重试");
+    println!("  - 无锁结构的优势在于不会因为持锁线程被挂起而阻塞所有线程");
+    println!("  - 标准库的 Mutex 和 mpsc 已经高度优化，优先使用它们");
+}
+
+/// 示例 21: Actor 模型 - 邮箱、地址与请求响应
+///
+/// 把状态封装在独立线程中，只通过消息访问
+pub fn actor_basics() {
+    println!("\n=== 示例 21: Actor 模型 - 邮箱、地址与请求响应 ===");
+
+    use actor::{Actor, ReplyTo};
+
+    // 计数器 Actor：状态只在自己的线程中，不需要 Mutex
+    struct Counter {
+        count: i64,
+    }
+
+    // 类型化的消息：只能发送 Counter 能处理的消息
+    enum CounterMsg {
+        Add(i64),
+        Get(ReplyTo<i64>),
+    }
+
+    impl Actor for Counter {
+        type Message = CounterMsg;
+
+        fn handle(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add(n) => self.count += n,
+                CounterMsg::Get(reply) => reply.send(self.count),
+            }
+        }
+
+        fn started(&mut self) {
+            println!("Counter 启动，初始值 {}", self.count);
+        }
+
+        fn stopped(&mut self) {
+            println!("Counter 停止，最终值 {}", self.count);
+        }
+    }
+
+    let (addr, handle) = actor::spawn(Counter { count: 0 });
+
+    // Addr 可以 clone 给多个线程，就像 tx.clone()
+    let senders: Vec<_> = (1..=4)
+        .map(|i| {
+            let addr = addr.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    addr.send(CounterMsg::Add(i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.join().unwrap();
+    }
+
+    // 请求/响应：消息里携带回复通道
+    let count = addr.ask(CounterMsg::Get).unwrap();
+    println!("当前计数: {}", count);
+    assert_eq!(count, (1..=4).sum::<i64>() * 100);
+
+    // 有序关闭：stop 之前发送的消息都会被处理
+    addr.send(CounterMsg::Add(1000)).unwrap();
+    addr.stop().unwrap();
+    let report = handle.join();
+    println!("Actor 报告: {:?}", report);
+    assert_eq!(report.processed, 402);
+
+    // 停止后邮箱关闭，继续发送会得到错误
+    match addr.send(CounterMsg::Add(1)) {
+        Ok(_) => println!("发送成功"),
+        Err(e) => println!("发送失败: {}", e),
+    }
+
+    println!("\nActor 模型:");
+    println!("  - 每个 Actor 一个线程、一个邮箱（mpsc 通道）");
+    println!("  - 状态不共享，只通过消息修改，无需加锁");
+    println!("  - Addr 是邮箱的发送端，可以任意 clone");
+    println!("  - 回复通道实现请求/响应");
+}
+
+/// 示例 22: Actor 监督与重启
+///
+/// 处理消息时 panic 的 Actor 会被重新创建，而不是拖垮整个程序
+pub fn actor_supervision() {
+    println!("\n=== 示例 22: Actor 监督与重启 ===");
+
+    use actor::{Actor, ActorError, ReplyTo};
+    use std::panic;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // 默认的 panic hook 会把 Actor 线程里被捕获的 panic 也打印到 stderr；
+    // 示例期间只保留主线程的 panic 信息，断言失败时仍然看得到
+    let default_hook = Arc::new(panic::take_hook());
+    let main_thread = thread::current().id();
+    {
+        let default_hook = Arc::clone(&default_hook);
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().id() == main_thread {
+                default_hook(info);
+            }
+        }));
+    }
+
+    // 一个"脆弱"的解析器：遇到非法输入直接 panic
+    struct Parser {
+        parsed: Vec<i32>,
+    }
+
+    enum ParserMsg {
+        Parse(&'static str),
+        Parsed(ReplyTo<Vec<i32>>),
+    }
+
+    impl Actor for Parser {
+        type Message = ParserMsg;
+
+        fn handle(&mut self, msg: ParserMsg) {
+            match msg {
+                ParserMsg::Parse(text) => {
+                    let n: i32 = text.parse().expect("输入必须是数字");
+                    self.parsed.push(n);
+                }
+                ParserMsg::Parsed(reply) => reply.send(self.parsed.clone()),
+            }
+        }
+
+        fn started(&mut self) {
+            println!("Parser 启动");
+        }
+    }
+
+    // 最多重启 1 次
+    let (addr, handle) = actor::spawn_supervised(|| Parser { parsed: Vec::new() }, 1);
+
+    addr.send(ParserMsg::Parse("1")).unwrap();
+    addr.send(ParserMsg::Parse("2")).unwrap();
+    println!("第一次 panic 之前: {:?}", addr.ask(ParserMsg::Parsed).unwrap());
+
+    // 触发 panic：监督者捕获后重启 Actor，状态被重置
+    addr.send(ParserMsg::Parse("abc")).unwrap();
+    addr.send(ParserMsg::Parse("3")).unwrap();
+    let after_restart = addr.ask(ParserMsg::Parsed).unwrap();
+    println!("重启之后: {:?}", after_restart);
+    assert_eq!(after_restart, vec![3]);
+
+    // 第二次 panic 超过重启上限，Actor 终止
+    addr.send(ParserMsg::Parse("xyz")).unwrap();
+    let result = addr.ask(ParserMsg::Parsed);
+    println!("超过重启上限后请求: {:?}", result);
+    assert!(matches!(result, Err(ActorError::MailboxClosed | ActorError::NoReply)));
+
+    let report = handle.join();
+    println!("Actor 报告: {:?}", report);
+    assert_eq!(report.restarts, 1);
+    assert!(report.failed);
+    assert!(report.last_panic.is_some_and(|m| m.starts_with("输入必须是数字")));
+
+    // 启动时 panic 同样受监督：前两次 started 失败，第三次成功
+    struct Flaky;
+
+    impl Actor for Flaky {
+        type Message = ReplyTo<&'static str>;
+
+        fn handle(&mut self, reply: ReplyTo<&'static str>) {
+            reply.send("ok");
+        }
+    }
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&attempts);
+    let (addr, handle) = actor::spawn_supervised(
+        move || {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("连接尚未就绪");
+            }
+            Flaky
+        },
+        2,
+    );
+    assert_eq!(addr.ask(|reply| reply), Ok("ok"));
+    drop(addr);
+    let report = handle.join();
+    println!("启动两次失败后的报告: {:?}", report);
+    assert_eq!((report.processed, report.restarts, report.failed), (1, 2, false));
+
+    // 启动一直失败：重启次数用完后 join 返回失败报告，而不是 panic
+    let (addr, handle) = actor::spawn_supervised(|| -> Flaky { panic!("配置缺失") }, 1);
+    assert!(addr.ask(|reply| reply).is_err());
+    let report = handle.join();
+    println!("一直无法启动的报告: {:?}", report);
+    assert!(report.failed);
+    assert_eq!(report.last_panic.as_deref(), Some("配置缺失"));
+
+    // 恢复原来的 panic hook
+    drop(panic::take_hook());
+    if let Ok(hook) = Arc::try_unwrap(default_hook) {
+        panic::set_hook(hook);
+    }
+
+    println!("\n监督策略:");
+    println!("  - catch_unwind 捕获创建、启动和处理消息时的 panic");
+    println!("  - 丢弃可能已损坏的状态，用工厂函数重新创建");
+    println!("  - 限制重启次数，避免无限崩溃循环");
+    println!("  - \"let it crash\"：让出错的部分重启，而不是到处防御");
+}
+
+/// 示例 23: 限流器 - 令牌桶、漏桶与滑动窗口
+///
+/// 三种限流算法，使用假时钟让结果完全确定
+pub fn rate_limiters() {
+    println!("\n=== 示例 23: 限流器 - 令牌桶、漏桶与滑动窗口 ===");
+
+    use crate::util::FakeClock;
+    use rate_limit::{LeakyBucket, SlidingWindow, TokenBucket};
+
+    // 假时钟：时间只在 advance 或 sleep 时前进
+    let clock = Arc::new(FakeClock::new());
+    let second = Duration::from_secs(1);
+
+    // 令牌桶：容量 3，每秒补充 1 个 —— 允许 3 个请求的突发
+    let bucket = TokenBucket::new(3, 1.0, clock.clone());
+    let burst: Vec<bool> = (0..4).map(|_| bucket.try_acquire()).collect();
+    println!("令牌桶 连续 4 次 try_acquire: {:?}", burst);
+    assert_eq!(burst, [true, true, true, false]);
+    clock.advance(second);
+    println!("令牌桶 1 秒后: {}", bucket.try_acquire());
+
+    // 阻塞的 acquire 在假时钟上会"睡"到下一个令牌生成
+    let before = clock.elapsed();
+    bucket.acquire();
+    println!("令牌桶 acquire 等待了: {:?}", clock.elapsed() - before);
+    assert_eq!(clock.elapsed() - before, second);
+
+    // 漏桶：容量 2，每秒漏出 2 个 —— 请求之间至少间隔 500ms
+    let clock = Arc::new(FakeClock::new());
+    let bucket = LeakyBucket::new(2, 2.0, clock.clone());
+    let burst: Vec<bool> = (0..3).map(|_| bucket.try_acquire()).collect();
+    println!("漏桶 连续 3 次 try_acquire: {:?}", burst);
+    assert_eq!(burst, [true, true, false]);
+    clock.advance(Duration::from_millis(500));
+    println!("漏桶 500ms 后: {}", bucket.try_acquire());
+    let before = clock.elapsed();
+    for _ in 0..4 {
+        bucket.acquire();
+    }
+    println!("漏桶 再 acquire 4 次耗时: {:?}（平滑输出）", clock.elapsed() - before);
+    assert_eq!(clock.elapsed() - before, Duration::from_secs(2));
+
+    // 速率极低、容量很大：间隔 × 容量超出 u64 纳秒，用饱和运算，不会溢出。
+    // 每秒 1e-9 个的间隔是 1e18 纳秒，排队时长截断到约 292 年，所以只能连续通过 10 个
+    for (capacity, rate, expected) in [(100, 1e-9, 10), (u32::MAX, 1e-300, 1), (u32::MAX, 1e9, 1000)] {
+        let bucket = LeakyBucket::new(capacity, rate, Arc::new(FakeClock::new()));
+        let accepted = (0..1000).take_while(|_| bucket.try_acquire()).count();
+        assert_eq!(accepted, expected, "容量 {}，每秒 {} 个", capacity, rate);
+    }
+    println!("漏桶 容量 100、每秒 1e-9 个: 不溢出，连续通过 10 个后拒绝");
+
+    // 滑动窗口：任意 1 秒内最多 2 个请求
+    let clock = Arc::new(FakeClock::new());
+    let window = SlidingWindow::new(2, second, clock.clone());
+    println!("滑动窗口 t=0ms: {}", window.try_acquire());
+    clock.advance(Duration::from_millis(600));
+    println!("滑动窗口 t=600ms: {}", window.try_acquire());
+    println!("滑动窗口 t=600ms: {}（窗口内已有 2 个）", window.try_acquire());
+    clock.advance(Duration::from_millis(400));
+    println!("滑动窗口 t=1000ms: {}（第一个请求滑出窗口）", window.try_acquire());
+    let before = clock.elapsed();
+    window.acquire();
+    println!("滑动窗口 acquire 等待了: {:?}", clock.elapsed() - before);
+    assert_eq!(clock.elapsed() - before, Duration::from_millis(600));
+
+    println!("\n限流算法:");
+    println!("  - 令牌桶: 允许突发，长期速率受限（Mutex 保护令牌数和时间）");
+    println!("  - 漏桶: 输出平滑，请求间隔固定（一个 AtomicU64 + CAS）");
+    println!("  - 滑动窗口: 精确统计窗口内的请求数（Mutex<VecDeque<Instant>>）");
+    println!("  - 通过 Clock trait 注入时间，测试不需要真的等待");
+}
+
+/// 示例 24: 实际应用 - 限流的线程池
+///
+/// 工作线程执行任务前先从共享的令牌桶获取许可
+pub fn practical_throttled_thread_pool() {
+    println!("\n=== 示例 24: 实际应用 - 限流的线程池 ===");
+
+    use crate::util::SystemClock;
+    use rate_limit::TokenBucket;
+    use std::time::Instant;
+
+    // 每秒最多 10 个任务，允许 3 个的突发
+    let limiter = Arc::new(TokenBucket::new(3, 10.0, Arc::new(SystemClock)));
+    let pool = ThreadPool::throttled(4, limiter);
+
+    let start = Instant::now();
+    let finished = Arc::new(Mutex::new(Vec::new()));
+    for i in 0..8 {
+        let finished = Arc::clone(&finished);
+        pool.execute(move || {
+            finished.lock().unwrap().push((i, start.elapsed()));
+        });
+    }
+
+    // drop 会等待所有任务完成
+    drop(pool);
+
+    for (i, at) in finished.lock().unwrap().iter() {
+        println!("任务 {} 在 {:>4} ms 时执行", i, at.as_millis());
+    }
+    // 前 3 个用掉突发令牌，剩下 5 个每 100ms 一个
+    let elapsed = start.elapsed();
+    println!("总耗时: {:?}（至少约 500ms）", elapsed);
+    assert!(elapsed >= Duration::from_millis(450));
+
+    println!("\n线程池限流:");
+    println!("  - 4 个工作线程共享一个 Arc<TokenBucket>");
+    println!("  - 线程再多，整体执行速率也不会超过限流器的速率");
+    println!("  - 适合调用有配额限制的外部服务");
+}
+
 /// 运行所有示例
 pub fn run_all_examples() {
     println!("\n╔════════════════════════════════════════╗");
@@ -712,6 +1282,19 @@ pub fn run_all_examples() {
     simple_thread_pool();
     practical_parallel_computation();
     practical_producer_consumer();
+    lock_free_treiber_stack();
+    lock_free_spsc_ring_buffer();
+    // 性能对比只在 release 构建下有参考价值，默认的 cargo run 跳过
+    if cfg!(debug_assertions) {
+        println!("\n=== 示例 20: 无锁结构与锁、通道的性能对比 ===");
+        println!("（debug 构建跳过，请用 cargo run --release 运行）");
+    } else {
+        lock_free_benchmark();
+    }
+    actor_basics();
+    actor_supervision();
+    rate_limiters();
+    practical_throttled_thread_pool();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  并发是 Rust 的核心优势之一！        ║");
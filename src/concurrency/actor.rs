@@ -0,0 +1,245 @@
+//! # Actor 模型
+//!
+//! 在 `message_passing_basic`（示例 4）和 `message_passing_multiple_producers`（示例 5）
+//! 的基础上，把"一个线程 + 一个通道"封装成 Actor：
+//!
+//! - 每个 Actor 独占自己的状态，只能通过邮箱（mpsc 通道）接收类型化的消息
+//! - `Addr<A>` 是 Actor 的地址，可以 clone 给任意多个线程
+//! - 请求/响应通过随消息发送的回复通道（`ReplyTo<R>`）完成
+//! - 监督：创建、启动或处理消息时 panic 的 Actor 会用工厂函数重新创建（有次数上限）
+//! - 有序关闭：`stop()` 之前已发送的消息都会先被处理完
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use thiserror::Error;
+
+/// Actor trait
+///
+/// 每个 Actor 声明自己能处理的消息类型，状态只在自己的线程中被访问，
+/// 所以 handle 拿到的是 `&mut self`，不需要任何锁。
+pub trait Actor: Send + 'static {
+    /// Actor 能接收的消息类型
+    type Message: Send + 'static;
+
+    /// 处理一条消息
+    fn handle(&mut self, msg: Self::Message);
+
+    /// Actor 启动（包括重启）时调用
+    fn started(&mut self) {}
+
+    /// Actor 正常停止时调用
+    fn stopped(&mut self) {}
+}
+
+/// Actor 相关的错误
+#[derive(Error, Debug, PartialEq)]
+pub enum ActorError {
+    #[error("Actor 已停止，邮箱已关闭")]
+    MailboxClosed,
+
+    #[error("Actor 没有回复（可能在处理消息时 panic 了）")]
+    NoReply,
+}
+
+/// 邮箱中的信封：普通消息或停止信号
+enum Envelope<M> {
+    Message(M),
+    Stop,
+}
+
+/// 回复通道，随请求消息一起发送给 Actor
+pub struct ReplyTo<R>(Sender<R>);
+
+impl<R> ReplyTo<R> {
+    /// 发送回复（请求方已经不再等待时忽略错误）
+    pub fn send(self, value: R) {
+        let _ = self.0.send(value);
+    }
+}
+
+/// Actor 的地址
+///
+/// 内部只是邮箱的发送端，clone 成本很低
+pub struct Addr<A: Actor> {
+    sender: Sender<Envelope<A::Message>>,
+}
+
+// 手动实现 Clone，避免 derive 要求 A: Clone
+impl<A: Actor> Clone for Addr<A> {
+    fn clone(&self) -> Self {
+        Addr {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<A: Actor> Addr<A> {
+    /// 发送一条消息，不等待结果（fire-and-forget）
+    pub fn send(&self, msg: A::Message) -> Result<(), ActorError> {
+        self.sender
+            .send(Envelope::Message(msg))
+            .map_err(|_| ActorError::MailboxClosed)
+    }
+
+    /// 发送一条请求并阻塞等待回复
+    ///
+    /// `make` 接收一个回复通道，返回要发送的消息，例如：
+    /// `addr.ask(CounterMsg::Get)`
+    pub fn ask<R>(&self, make: impl FnOnce(ReplyTo<R>) -> A::Message) -> Result<R, ActorError> {
+        let (tx, rx) = mpsc::channel();
+        self.send(make(ReplyTo(tx)))?;
+        // Actor 处理消息时 panic，回复通道会被丢弃，recv 返回错误
+        rx.recv().map_err(|_| ActorError::NoReply)
+    }
+
+    /// 请求 Actor 停止：之前已发送的消息会先被处理完
+    pub fn stop(&self) -> Result<(), ActorError> {
+        self.sender
+            .send(Envelope::Stop)
+            .map_err(|_| ActorError::MailboxClosed)
+    }
+}
+
+/// Actor 线程结束时的统计信息
+#[derive(Debug, Default, PartialEq)]
+pub struct ActorReport {
+    /// 成功处理的消息数
+    pub processed: usize,
+    /// 被重启的次数
+    pub restarts: usize,
+    /// 是否因为超过重启上限而终止
+    pub failed: bool,
+    /// 最近一次 panic 的信息
+    pub last_panic: Option<String>,
+}
+
+/// panic 的负载通常是 `&str` 或 `String`
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(s) => s.to_string(),
+        None => payload.downcast_ref::<String>().cloned().unwrap_or_else(|| "未知的 panic".to_string()),
+    }
+}
+
+/// Actor 线程的句柄
+pub struct ActorHandle {
+    thread: JoinHandle<ActorReport>,
+}
+
+impl ActorHandle {
+    /// 等待 Actor 线程结束并返回统计信息
+    ///
+    /// 监督者捕获了 Actor 代码中所有的 panic；万一线程本身 panic 了，
+    /// 返回一份 `failed` 的报告而不是把 panic 传给调用方
+    pub fn join(self) -> ActorReport {
+        self.thread.join().unwrap_or_else(|payload| ActorReport {
+            failed: true,
+            last_panic: Some(panic_message(&*payload)),
+            ..ActorReport::default()
+        })
+    }
+}
+
+/// 启动一个不会被重启的 Actor：第一次 panic 就终止
+pub fn spawn<A: Actor>(actor: A) -> (Addr<A>, ActorHandle) {
+    let mut initial = Some(actor);
+    spawn_supervised(
+        move || initial.take().expect("max_restarts 为 0，工厂只会被调用一次"),
+        0,
+    )
+}
+
+/// 启动一个受监督的 Actor
+///
+/// `factory`、`started` 或处理消息时 panic，会丢弃当前状态并调用 `factory` 创建新的 Actor，
+/// 最多重启 `max_restarts` 次；超过上限后 Actor 终止，邮箱关闭。
+pub fn spawn_supervised<A, F>(mut factory: F, max_restarts: usize) -> (Addr<A>, ActorHandle)
+where
+    A: Actor,
+    F: FnMut() -> A + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let thread = thread::spawn(move || run(&mut factory, receiver, max_restarts));
+    (Addr { sender }, ActorHandle { thread })
+}
+
+/// Actor 的消息循环
+fn run<A, F>(factory: &mut F, receiver: Receiver<Envelope<A::Message>>, max_restarts: usize) -> ActorReport
+where
+    A: Actor,
+    F: FnMut() -> A,
+{
+    let mut report = ActorReport::default();
+    let Some(mut actor) = start(factory, &mut report, max_restarts) else {
+        return report;
+    };
+
+    // 所有 Addr 都被丢弃时 recv 返回 Err，同样视为停止
+    while let Ok(envelope) = receiver.recv() {
+        let msg = match envelope {
+            Envelope::Message(msg) => msg,
+            Envelope::Stop => break,
+        };
+
+        // catch_unwind 捕获 handle 中的 panic，线程本身不会因此结束
+        // AssertUnwindSafe：panic 后旧状态会被整体丢弃，不会观察到"改了一半"的数据
+        match panic::catch_unwind(AssertUnwindSafe(|| actor.handle(msg))) {
+            Ok(()) => report.processed += 1,
+            Err(payload) => {
+                if !record_panic(&mut report, &*payload, max_restarts) {
+                    return report;
+                }
+                match start(factory, &mut report, max_restarts) {
+                    Some(new) => actor = new,
+                    None => return report,
+                }
+            }
+        }
+    }
+
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| actor.stopped())) {
+        report.failed = true;
+        report.last_panic = Some(panic_message(&*payload));
+    }
+    report
+}
+
+/// 用工厂创建 Actor 并调用 `started`；其中的 panic 和处理消息时的 panic 一样计入重启次数
+///
+/// 超过重启上限时返回 `None`
+fn start<A, F>(factory: &mut F, report: &mut ActorReport, max_restarts: usize) -> Option<A>
+where
+    A: Actor,
+    F: FnMut() -> A,
+{
+    loop {
+        let started = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut actor = factory();
+            actor.started();
+            actor
+        }));
+        match started {
+            Ok(actor) => return Some(actor),
+            Err(payload) => {
+                if !record_panic(report, &*payload, max_restarts) {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// 记下一次 panic；还能重启时占用一次重启次数并返回 `true`，否则标记为失败
+fn record_panic(report: &mut ActorReport, payload: &(dyn Any + Send), max_restarts: usize) -> bool {
+    report.last_panic = Some(panic_message(payload));
+    if report.restarts < max_restarts {
+        report.restarts += 1;
+        true
+    } else {
+        report.failed = true;
+        false
+    }
+}
@@ -0,0 +1,289 @@
+//! # 无锁数据结构
+//!
+//! 在 `atomic_types`（示例 9）和 `memory_ordering`（示例 10）的基础上，
+//! 用 `AtomicPtr` / `AtomicUsize` 实现两个经典的无锁结构：
+//!
+//! - `TreiberStack<T>`：多生产者多消费者的无锁栈（CAS 循环）
+//! - `spsc::channel`：单生产者单消费者的有界环形缓冲区
+//!
+//! 每一处原子操作都标注了使用的内存顺序以及原因，
+//! 核心都是示例 10 中的 Release / Acquire 配对：
+//! 写方用 Release 发布数据，读方用 Acquire 看到数据。
+
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// Treiber 栈的节点
+///
+/// `next` 在节点被发布（push 成功）之前写入，之后只读；
+/// `retired_next` 只在节点被弹出后由弹出它的线程写入，用于串起待释放链表。
+struct Node<T> {
+    value: MaybeUninit<T>,
+    next: *mut Node<T>,
+    retired_next: *mut Node<T>,
+}
+
+/// Treiber 无锁栈
+///
+/// push 和 pop 都是"读取栈顶 → 准备新值 → compare_exchange"的 CAS 循环，
+/// 失败说明有其他线程抢先修改了栈顶，重新读取后再试即可。
+///
+/// 内存回收：弹出的节点不会立即释放，而是挂到 `retired` 链表上，
+/// 等整个栈被 drop 时统一释放。这样可以避免两个经典问题：
+/// - use-after-free：另一个线程可能刚读到这个节点，正要访问它的 `next`
+/// - ABA：节点地址在栈存活期间不会被复用，CAS 不会被"同一个地址"骗过
+///
+/// 代价是弹出过的节点内存要到栈销毁时才归还。
+/// 生产级实现会使用 hazard pointer 或 epoch（如 crossbeam-epoch）做回收。
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    retired: AtomicPtr<Node<T>>,
+}
+
+// 栈只通过原子操作共享节点，值 T 会被转移到其他线程，所以要求 T: Send
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    /// 创建一个空栈
+    pub fn new() -> Self {
+        TreiberStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// 压入一个值
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node {
+            value: MaybeUninit::new(value),
+            next: ptr::null_mut(),
+            retired_next: ptr::null_mut(),
+        }));
+
+        // 读取当前栈顶只是为了填 next，不需要同步，用 Relaxed
+        let mut current = self.head.load(Ordering::Relaxed);
+        loop {
+            // 节点还没有发布，只有当前线程能看到它，可以直接写
+            unsafe { (*node).next = current };
+
+            // 成功：Release，保证 value 和 next 的写入对之后 Acquire 读到栈顶的线程可见
+            // 失败：拿到最新的栈顶重试，不需要同步，用 Relaxed
+            match self.head.compare_exchange_weak(
+                current,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// 弹出栈顶的值，栈为空时返回 None
+    pub fn pop(&self) -> Option<T> {
+        // Acquire：与 push 的 Release 配对，之后才能安全读取节点的 next 和 value
+        let mut current = self.head.load(Ordering::Acquire);
+        loop {
+            if current.is_null() {
+                return None;
+            }
+
+            // 节点永远不会在栈存活期间被释放，所以这里解引用是安全的
+            let next = unsafe { (*current).next };
+
+            // 失败时同样用 Acquire，因为下一轮要读取新栈顶节点的 next
+            match self.head.compare_exchange_weak(
+                current,
+                next,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // 只有 CAS 成功的线程会走到这里，值只会被读出一次
+                    let value = unsafe { (*current).value.assume_init_read() };
+                    self.retire(current);
+                    return Some(value);
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// 栈是否为空（并发情况下只是一个瞬时快照）
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    /// 把已弹出的节点挂到待释放链表上
+    fn retire(&self, node: *mut Node<T>) {
+        // 待释放链表只在 drop 时（独占访问）遍历，这里不需要发布任何数据，用 Relaxed
+        let mut current = self.retired.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*node).retired_next = current };
+            match self.retired.compare_exchange_weak(
+                current,
+                node,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        // &mut self 保证没有其他线程在访问，直接用 get_mut 读取
+        let mut current = *self.head.get_mut();
+        while !current.is_null() {
+            // 仍在栈中的节点：值还没被取走，需要 drop
+            let mut node = unsafe { Box::from_raw(current) };
+            unsafe { node.value.assume_init_drop() };
+            current = node.next;
+        }
+
+        let mut current = *self.retired.get_mut();
+        while !current.is_null() {
+            // 已弹出的节点：值已经被 pop 读走，只释放节点内存
+            let node = unsafe { Box::from_raw(current) };
+            current = node.retired_next;
+        }
+    }
+}
+
+/// 单生产者单消费者（SPSC）有界环形缓冲区
+///
+/// 只有一个线程写 `tail`、只有一个线程写 `head`，
+/// 所以不需要 CAS，一次 load + 一次 store 就够了。
+/// 生产者和消费者被拆成两个不能 Clone 的句柄，"单"的约束由类型系统保证。
+pub mod spsc {
+    use std::cell::UnsafeCell;
+    use std::mem::MaybeUninit;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Inner<T> {
+        buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+        // 消费者下一个要读的位置（只由消费者写）
+        head: AtomicUsize,
+        // 生产者下一个要写的位置（只由生产者写）
+        tail: AtomicUsize,
+    }
+
+    // 每个槽位同一时刻只会被一方访问，由 head/tail 的 Release/Acquire 保证
+    unsafe impl<T: Send> Sync for Inner<T> {}
+
+    impl<T> Inner<T> {
+        fn capacity(&self) -> usize {
+            self.buffer.len()
+        }
+    }
+
+    impl<T> Drop for Inner<T> {
+        fn drop(&mut self) {
+            // 两个句柄都已销毁，释放还留在缓冲区中的值
+            let head = *self.head.get_mut();
+            let tail = *self.tail.get_mut();
+            for i in head..tail {
+                let slot = &mut self.buffer[i % self.buffer.len()];
+                unsafe { slot.get_mut().assume_init_drop() };
+            }
+        }
+    }
+
+    /// 生产者句柄
+    pub struct Producer<T> {
+        inner: Arc<Inner<T>>,
+    }
+
+    /// 消费者句柄
+    pub struct Consumer<T> {
+        inner: Arc<Inner<T>>,
+    }
+
+    // 句柄可以转移到其他线程（各自只有一个），但不能共享
+    unsafe impl<T: Send> Send for Producer<T> {}
+    unsafe impl<T: Send> Send for Consumer<T> {}
+
+    /// 创建容量为 `capacity` 的环形缓冲区，返回生产者和消费者句柄
+    ///
+    /// # Panics
+    ///
+    /// `capacity` 为 0 时 panic
+    pub fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+        assert!(capacity > 0, "容量必须大于 0");
+        let buffer = (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+        let inner = Arc::new(Inner {
+            buffer,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        });
+        (
+            Producer { inner: Arc::clone(&inner) },
+            Consumer { inner },
+        )
+    }
+
+    impl<T> Producer<T> {
+        /// 写入一个值，缓冲区已满时把值原样退回
+        pub fn push(&mut self, value: T) -> Result<(), T> {
+            let inner = &*self.inner;
+            // tail 只有自己写，Relaxed 读取即可
+            let tail = inner.tail.load(Ordering::Relaxed);
+            // Acquire：与消费者 store(head, Release) 配对，
+            // 确保消费者对这个槽位的读取已经完成，才能覆盖它
+            let head = inner.head.load(Ordering::Acquire);
+
+            if tail - head == inner.capacity() {
+                return Err(value);
+            }
+
+            let slot = &inner.buffer[tail % inner.capacity()];
+            unsafe { (*slot.get()).write(value) };
+
+            // Release：发布槽位中的数据，消费者 Acquire 读到新 tail 后才能读取
+            inner.tail.store(tail + 1, Ordering::Release);
+            Ok(())
+        }
+
+        /// 缓冲区容量
+        pub fn capacity(&self) -> usize {
+            self.inner.capacity()
+        }
+    }
+
+    impl<T> Consumer<T> {
+        /// 读取一个值，缓冲区为空时返回 None
+        pub fn pop(&mut self) -> Option<T> {
+            let inner = &*self.inner;
+            // head 只有自己写，Relaxed 读取即可
+            let head = inner.head.load(Ordering::Relaxed);
+            // Acquire：与生产者 store(tail, Release) 配对，看到槽位中已写入的数据
+            let tail = inner.tail.load(Ordering::Acquire);
+
+            if head == tail {
+                return None;
+            }
+
+            let slot = &inner.buffer[head % inner.capacity()];
+            let value = unsafe { (*slot.get()).assume_init_read() };
+
+            // Release：告诉生产者这个槽位已经读完，可以复用
+            inner.head.store(head + 1, Ordering::Release);
+            Some(value)
+        }
+    }
+}
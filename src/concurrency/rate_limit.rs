@@ -0,0 +1,229 @@
+//! # 限流器
+//!
+//! 三种常见的线程安全限流算法，都由 `Mutex` / 原子类型和 `Instant` 构建：
+//!
+//! - `TokenBucket`：令牌桶，按固定速率补充令牌，允许一定的突发
+//! - `LeakyBucket`：漏桶，请求以恒定间隔"漏出"，用一个 `AtomicU64` + CAS 实现
+//! - `SlidingWindow`：滑动窗口，记录最近一个窗口内的请求时间
+//!
+//! 每种限流器都支持非阻塞的 `try_acquire` 和阻塞的 `acquire`。
+//! 时间通过 `util::Clock` trait 获取，测试时换成 `FakeClock` 就不需要真的等待。
+
+use crate::util::Clock;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 限流器 trait
+///
+/// 实现者只需要提供 `poll`：能拿到许可就返回 `Ok(())`，
+/// 否则返回还需要等待多久。`try_acquire` 和 `acquire` 都基于它。
+pub trait RateLimiter: Send + Sync {
+    /// 尝试获取一个许可，失败时返回建议的等待时间
+    fn poll(&self) -> Result<(), Duration>;
+
+    /// 限流器使用的时钟
+    fn clock(&self) -> &dyn Clock;
+
+    /// 非阻塞：立即返回是否拿到许可
+    fn try_acquire(&self) -> bool {
+        self.poll().is_ok()
+    }
+
+    /// 阻塞：等到拿到许可为止
+    fn acquire(&self) {
+        // 等待结束后可能被其他线程抢先，所以要循环重试
+        while let Err(wait) = self.poll() {
+            self.clock().sleep(wait);
+        }
+    }
+}
+
+/// 令牌桶的可变状态，整体放在一把锁里
+struct TokenState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// 令牌桶
+///
+/// 桶里最多存 `capacity` 个令牌，每秒补充 `rate` 个，每个请求消耗一个。
+/// 空闲时令牌会攒起来，所以允许最多 `capacity` 个请求的突发。
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    state: Mutex<TokenState>,
+    clock: Arc<dyn Clock>,
+}
+
+impl TokenBucket {
+    /// 创建一个装满令牌的桶
+    ///
+    /// # Panics
+    ///
+    /// `capacity` 为 0 或 `rate_per_sec` 不是正数时 panic
+    pub fn new(capacity: u32, rate_per_sec: f64, clock: Arc<dyn Clock>) -> Self {
+        assert!(capacity > 0 && rate_per_sec > 0.0, "容量和速率必须大于 0");
+        TokenBucket {
+            capacity: capacity as f64,
+            rate: rate_per_sec,
+            state: Mutex::new(TokenState {
+                tokens: capacity as f64,
+                last_refill: clock.now(),
+            }),
+            clock,
+        }
+    }
+}
+
+impl RateLimiter for TokenBucket {
+    fn poll(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        // 按经过的时间补充令牌，不超过容量
+        let now = self.clock.now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - state.tokens) / self.rate))
+        }
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+}
+
+/// 漏桶（以"计量器"形式实现，也叫 GCRA 算法）
+///
+/// 请求像水滴一样以固定间隔 `interval` 从桶底漏出，
+/// 桶能额外容纳 `capacity - 1` 个排队的请求，超出的请求被拒绝。
+/// 和令牌桶不同，漏桶的输出速率是平滑的。
+///
+/// 整个状态只有一个数字：下一个请求最早可以"漏出"的时间，
+/// 所以不需要锁，用 `AtomicU64` + compare_exchange 就能实现。
+pub struct LeakyBucket {
+    interval_nanos: u64,
+    // 允许提前的量：(capacity - 1) 个间隔
+    tolerance_nanos: u64,
+    origin: Instant,
+    // 下一个空闲时刻，相对 origin 的纳秒数
+    next_free: AtomicU64,
+    clock: Arc<dyn Clock>,
+}
+
+impl LeakyBucket {
+    /// 每秒漏出 `rate_per_sec` 个请求，最多容纳 `capacity` 个
+    ///
+    /// # Panics
+    ///
+    /// `capacity` 为 0 或 `rate_per_sec` 不是正数时 panic
+    ///
+    /// 时间用 u64 纳秒表示：速率极低、容量很大时，排队的总时长最多按 `u64::MAX / 2` 纳秒（约 292 年）计算
+    pub fn new(capacity: u32, rate_per_sec: f64, clock: Arc<dyn Clock>) -> Self {
+        assert!(capacity > 0 && rate_per_sec > 0.0, "容量和速率必须大于 0");
+        // 浮点数转整数是饱和的，速率再低也不会溢出
+        let interval_nanos = (1e9 / rate_per_sec) as u64;
+        // 留出一半的范围，next_free 饱和到 u64::MAX 之后仍然大于 now + tolerance，桶不会永远判定为空
+        let tolerance_nanos = interval_nanos.saturating_mul(capacity as u64 - 1).min(u64::MAX / 2);
+        LeakyBucket {
+            interval_nanos,
+            tolerance_nanos,
+            origin: clock.now(),
+            next_free: AtomicU64::new(0),
+            clock,
+        }
+    }
+}
+
+impl RateLimiter for LeakyBucket {
+    fn poll(&self) -> Result<(), Duration> {
+        let now = self.clock.now().duration_since(self.origin).as_nanos() as u64;
+        // 状态只有这一个数字，没有其他数据需要同步，Relaxed 就足够
+        let mut next_free = self.next_free.load(Ordering::Relaxed);
+        loop {
+            // 桶满了：要等到排在最前面的请求漏出
+            if next_free.saturating_sub(now) > self.tolerance_nanos {
+                return Err(Duration::from_nanos(next_free - self.tolerance_nanos - now));
+            }
+
+            let updated = next_free.max(now).saturating_add(self.interval_nanos);
+            match self.next_free.compare_exchange_weak(
+                next_free,
+                updated,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(actual) => next_free = actual,
+            }
+        }
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+}
+
+/// 滑动窗口（日志法）
+///
+/// 记录最近 `window` 时间内每个请求的时间戳，数量达到 `limit` 就拒绝。
+/// 精确但需要 O(limit) 的内存。
+pub struct SlidingWindow {
+    limit: usize,
+    window: Duration,
+    log: Mutex<VecDeque<Instant>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl SlidingWindow {
+    /// 任意 `window` 时长内最多允许 `limit` 个请求
+    ///
+    /// # Panics
+    ///
+    /// `limit` 为 0 时 panic
+    pub fn new(limit: usize, window: Duration, clock: Arc<dyn Clock>) -> Self {
+        assert!(limit > 0, "limit 必须大于 0");
+        SlidingWindow {
+            limit,
+            window,
+            log: Mutex::new(VecDeque::with_capacity(limit)),
+            clock,
+        }
+    }
+}
+
+impl RateLimiter for SlidingWindow {
+    fn poll(&self) -> Result<(), Duration> {
+        let mut log = self.log.lock().unwrap();
+        let now = self.clock.now();
+
+        // 移除已经滑出窗口的请求
+        while let Some(&oldest) = log.front() {
+            if now.duration_since(oldest) >= self.window {
+                log.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if log.len() < self.limit {
+            log.push_back(now);
+            Ok(())
+        } else {
+            // 等最早的请求滑出窗口
+            let oldest = *log.front().unwrap();
+            Err(self.window - now.duration_since(oldest))
+        }
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+}
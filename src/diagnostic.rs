@@ -0,0 +1,304 @@
+//! # 带源码片段的诊断信息
+//!
+//! 错误的 `Display` 只有一行，比如 "第 3 行第 8 列: 无法识别的值"。
+//! 这里仿照 rustc、miette、ariadne 的格式，把错误画在源码下面：
+//!
+//! ```text
+//! 错误: 无法识别的值 'localhost'
+//!  --> app.ini:3:8
+//!   |
+//! 3 | host = localhost
+//!   |        ^^^^^^^^^ 字符串需要加引号
+//!   |
+//!   = 帮助: 写成 "localhost"
+//! ```
+//!
+//! - 标签的区间用字符下标表示（不是字节），和 `Expr` 的 `Span` 一致
+//! - 主标签画 `^`，次要标签画 `-`；同一行有多个标签时，左边的说明挂在下面
+//! - 中文等全角字符在终端里占两列，下划线按显示宽度对齐；制表符展开成 4 个空格
+//! - 一个标签只画在它起始的那一行，跨行的部分截断到行尾
+//!
+//! 配置文件（`error_handling::config`）和表达式语言（`enums::diagnostic`）都用它报告错误，
+//! 所以放在始终启用的顶层模块，两个模块各自单独启用时都能编译。
+
+// 只启用部分模块时，没有调用者
+#![allow(dead_code)]
+
+use std::ops::Range;
+
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "错误",
+            Severity::Warning => "警告",
+        }
+    }
+}
+
+/// 源码中的一段和对它的说明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// 字符下标，左闭右开；空区间画成一个字符宽
+    pub span: Range<usize>,
+    pub message: String,
+    pub primary: bool,
+}
+
+impl Label {
+    pub fn primary(span: Range<usize>, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+            primary: true,
+        }
+    }
+
+    pub fn secondary(span: Range<usize>, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+            primary: false,
+        }
+    }
+}
+
+/// 一条诊断：标题、若干标签、若干帮助和附注
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    /// (前缀, 内容)，前缀是 "帮助" 或 "注"
+    notes: Vec<(&'static str, String)>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.notes.push(("帮助", help.into()));
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(("注", note.into()));
+        self
+    }
+
+    /// 渲染成多行文本，`name` 是显示在 `-->` 后面的文件名
+    pub fn render(&self, name: &str, source: &str) -> String {
+        let lines = split_lines(source);
+
+        // 每个标签落在哪一行、从哪一列开始（都从 0 开始，列是字符下标）
+        let mut placed: Vec<(usize, usize, usize, &Label)> = self
+            .labels
+            .iter()
+            .map(|label| {
+                let (line, start) = locate(&lines, label.span.start);
+                let line_len = lines[line].1.chars().count();
+                let end = (start + label.span.end.saturating_sub(label.span.start)).min(line_len);
+                (line, start, end, label)
+            })
+            .collect();
+        placed.sort_by_key(|&(line, start, _, _)| (line, start));
+
+        let max_line = placed.iter().map(|p| p.0 + 1).max().unwrap_or(1);
+        let gutter = max_line.to_string().len();
+        let pad = " ".repeat(gutter);
+
+        let mut out = format!("{}: {}\n", self.severity.label(), self.message);
+        // 位置取第一个主标签，没有就取第一个标签
+        let anchor = placed.iter().find(|p| p.3.primary).or(placed.first());
+        match anchor {
+            Some(&(line, start, _, _)) => out += &format!("{}--> {}:{}:{}\n", pad, name, line + 1, start + 1),
+            None => out += &format!("{}--> {}\n", pad, name),
+        }
+
+        if !placed.is_empty() {
+            out += &format!("{} |\n", pad);
+            let mut line_numbers: Vec<usize> = placed.iter().map(|p| p.0).collect();
+            line_numbers.dedup();
+            let mut previous: Option<usize> = None;
+            for &line in &line_numbers {
+                // 中间只隔一行时直接显示那一行，隔得更多时用 ... 省略
+                match previous {
+                    Some(prev) if line == prev + 2 => {
+                        out += &source_row(gutter, prev + 1, lines[prev + 1].1);
+                    }
+                    Some(prev) if line > prev + 2 => out += "...\n",
+                    _ => {}
+                }
+                let text = lines[line].1;
+                out += &source_row(gutter, line, text);
+                let on_line: Vec<_> = placed
+                    .iter()
+                    .filter(|p| p.0 == line)
+                    .map(|&(_, start, end, label)| (start, end, label))
+                    .collect();
+                for row in label_rows(text, &on_line) {
+                    out += format!("{} | {}", pad, row).trim_end();
+                    out += "\n";
+                }
+                previous = Some(line);
+            }
+        }
+
+        if !self.notes.is_empty() {
+            if !placed.is_empty() {
+                out += &format!("{} |\n", pad);
+            }
+            for (prefix, note) in &self.notes {
+                out += &format!("{} = {}: {}\n", pad, prefix, note);
+            }
+        }
+        out
+    }
+}
+
+/// 每一行的起始字符下标和内容（去掉 \r）
+fn split_lines(source: &str) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in source.split('\n') {
+        lines.push((offset, line.strip_suffix('\r').unwrap_or(line)));
+        offset += line.chars().count() + 1;
+    }
+    lines
+}
+
+/// 字符下标所在的行和列；超出末尾时落在最后一行的末尾
+fn locate(lines: &[(usize, &str)], offset: usize) -> (usize, usize) {
+    let line = lines.iter().rposition(|&(start, _)| start <= offset).unwrap_or(0);
+    (line, offset - lines[line].0)
+}
+
+/// 第 `line` 行（从 1 开始）第 `column` 列对应的字符下标，用于把行列位置转换成标签区间
+pub fn offset_of(source: &str, line: usize, column: usize) -> usize {
+    let lines = split_lines(source);
+    match lines.get(line.saturating_sub(1)) {
+        Some(&(start, _)) => start + column.saturating_sub(1),
+        None => source.chars().count(),
+    }
+}
+
+/// 字符在终端中占的列数：东亚全角字符占 2 列
+fn char_width(c: char) -> usize {
+    match c {
+        '\t' => TAB_WIDTH,
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{303E}'
+        | '\u{3041}'..='\u{33FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{20000}'..='\u{3FFFD}' => 2,
+        _ => 1,
+    }
+}
+
+/// `text` 中第 `chars` 个字符（字符下标）在终端里占的列数
+pub fn width_of(text: &str, chars: Range<usize>) -> usize {
+    text.chars()
+        .skip(chars.start)
+        .take(chars.end.saturating_sub(chars.start))
+        .map(char_width)
+        .sum()
+}
+
+/// 带行号的源码行，行号从 1 开始显示
+fn source_row(gutter: usize, line: usize, text: &str) -> String {
+    let text = text.replace('\t', &" ".repeat(TAB_WIDTH));
+    let row = format!("{:>gutter$} | {}", line + 1, text, gutter = gutter);
+    format!("{}\n", row.trim_end())
+}
+
+/// 一行源码下面的标签行
+///
+/// ```text
+/// a + b * c
+/// ^   -   - 最右边的说明跟在下划线后面
+/// |   |
+/// |   中间的说明
+/// 最左边的说明
+/// ```
+fn label_rows(text: &str, labels: &[(usize, usize, &Label)]) -> Vec<String> {
+    // 转换成显示列：(起始列, 宽度, 标签)
+    let columns: Vec<(usize, usize, &Label)> = labels
+        .iter()
+        .map(|&(start, end, label)| {
+            let col = width_of(text, 0..start);
+            let width = width_of(text, start..end).max(1);
+            (col, width, label)
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut underline = String::new();
+    for &(col, width, label) in &columns {
+        let col = col.max(underline.chars().count());
+        underline += &" ".repeat(col - underline.chars().count());
+        underline += &(if label.primary { "^" } else { "-" }).repeat(width);
+    }
+    let (_, _, last) = columns[columns.len() - 1];
+    if !last.message.is_empty() {
+        underline += " ";
+        underline += &last.message;
+    }
+    rows.push(underline);
+
+    // 其余标签从右往左，先画一行竖线，再把说明写在竖线的位置
+    let pending: Vec<_> = columns[..columns.len() - 1]
+        .iter()
+        .filter(|(_, _, label)| !label.message.is_empty())
+        .collect();
+    for i in (0..pending.len()).rev() {
+        let bars = |count: usize| {
+            let mut row = String::new();
+            for &&(col, _, _) in &pending[..count] {
+                row += &" ".repeat(col.saturating_sub(row.chars().count()));
+                row += "|";
+            }
+            row
+        };
+        rows.push(bars(i + 1));
+        let mut row = bars(i);
+        let (col, _, label) = pending[i];
+        row += &" ".repeat(col.saturating_sub(row.chars().count()));
+        row += &label.message;
+        rows.push(row);
+    }
+    rows
+}
@@ -1,6 +1,15 @@
 // Rust 枚举教学代码
 // 主题：枚举定义、模式匹配、Option、Result
 
+pub mod calc;
+pub mod diagnostic;
+pub mod expr;
+pub mod fsm;
+pub mod list;
+pub mod parser;
+pub mod symbolic;
+pub mod vm;
+
 /// 示例 1: 基本枚举定义
 pub fn basic_enum() {
     println!("\n=== 示例 1: 基本枚举定义 ===");
@@ -356,6 +365,7 @@ pub fn recursive_enum() {
     print!("链表: ");
     print_list(&list);
     println!("递归枚举需要使用 Box 来避免无限大小");
+    println!("泛型的持久化版本见示例 27（list 模块）");
 }
 
 /// 示例 13: 枚举的内存布局
@@ -423,39 +433,12 @@ pub fn practical_state_machine() {
 }
 
 /// 示例 15: 实际应用 - 表达式求值
+///
+/// Expr 定义在 expr 模块中，示例 16、17 在它的基础上实现完整的算术语言
 pub fn practical_expression_eval() {
     println!("\n=== 示例 15: 实际应用 - 表达式求值 ===");
 
-    #[derive(Debug)]
-    enum Expr {
-        Number(f64),
-        Add(Box<Expr>, Box<Expr>),
-        Subtract(Box<Expr>, Box<Expr>),
-        Multiply(Box<Expr>, Box<Expr>),
-        Divide(Box<Expr>, Box<Expr>),
-    }
-
-    impl Expr {
-        fn eval(&self) -> f64 {
-            match self {
-                Expr::Number(n) => *n,
-                Expr::Add(left, right) => left.eval() + right.eval(),
-                Expr::Subtract(left, right) => left.eval() - right.eval(),
-                Expr::Multiply(left, right) => left.eval() * right.eval(),
-                Expr::Divide(left, right) => left.eval() / right.eval(),
-            }
-        }
-
-        fn to_string(&self) -> String {
-            match self {
-                Expr::Number(n) => n.to_string(),
-                Expr::Add(left, right) => format!("({} + {})", left.to_string(), right.to_string()),
-                Expr::Subtract(left, right) => format!("({} - {})", left.to_string(), right.to_string()),
-                Expr::Multiply(left, right) => format!("({} * {})", left.to_string(), right.to_string()),
-                Expr::Divide(left, right) => format!("({} / {})", left.to_string(), right.to_string()),
-            }
-        }
-    }
+    use expr::Expr;
 
     let expr = Expr::Add(
         Box::new(Expr::Multiply(
@@ -468,8 +451,829 @@ pub fn practical_expression_eval() {
         ))
     );
 
-    println!("表达式: {}", expr.to_string());
-    println!("结果: {}", expr.eval());
+    println!("表达式: {}", expr);
+    println!("结果: {:?}", expr.eval());
+
+    // 除以零不再得到 inf，而是返回错误
+    let bad = Expr::Divide(
+        Box::new(Expr::Number(1.0)),
+        Box::new(Expr::Subtract(
+            Box::new(Expr::Number(2.0)),
+            Box::new(Expr::Number(2.0))
+        ))
+    );
+    match bad.eval() {
+        Ok(value) => println!("{} = {}", bad, value),
+        Err(e) => println!("{} 求值失败: {}", bad, e),
+    }
+}
+
+/// 示例 16: 表达式语言 - 词法分析与语法分析
+///
+/// 从文本构建 Expr：tokenize 切分 token，优先级爬升处理运算符优先级
+pub fn expression_parsing() {
+    println!("\n=== 示例 16: 表达式语言 - 词法分析与语法分析 ===");
+
+    // 词法分析：每个 token 都带有位置
+    let tokens = parser::tokenize("max(1, 2.5e1) ^ 2").unwrap();
+    println!("tokens:");
+    for token in &tokens {
+        println!("  {:?} @ {}..{}", token.kind, token.span.start, token.span.end);
+    }
+
+    // 语法分析：优先级和结合性决定了树的形状
+    let cases = [
+        ("1 + 2 * 3", 7.0),
+        ("(1 + 2) * 3", 9.0),
+        ("10 - 4 - 3", 3.0),      // 左结合: (10 - 4) - 3
+        ("2 ^ 3 ^ 2", 512.0),     // 右结合: 2 ^ (3 ^ 2)
+        ("-2 ^ 2", -4.0),         // 负号低于乘方: -(2 ^ 2)
+        ("2 * -3", -6.0),
+        ("sqrt(16) + min(3, 1, 2)", 5.0),
+    ];
+    for (input, expected) in cases {
+        let expr = parser::parse(input).unwrap();
+        let value = expr.eval().unwrap();
+        println!("  {:<26} => {:<28} = {}", input, expr.to_string(), value);
+        assert_eq!(value, expected);
+    }
+
+    println!("\n解析过程:");
+    println!("  - 词法分析: 字符 → token（数字、标识符、运算符、括号）");
+    println!("  - 语法分析: token → Expr 树（递归下降 + 优先级爬升）");
+    println!("  - 左结合运算右侧只接受更高优先级，右结合运算接受同级");
+}
+
+/// 示例 17: 表达式语言 - 变量、函数与错误位置
+///
+/// 解释器保存 let 定义的变量，所有错误都带有出错位置
+pub fn expression_interpreter() {
+    println!("\n=== 示例 17: 表达式语言 - 变量、函数与错误位置 ===");
+
+    use calc::{Interpreter, Outcome};
+
+    let mut interpreter = Interpreter::new();
+    let session = [
+        "let r = 2",
+        "let area = pi * r ^ 2",
+        "max(area, 10) / r",
+        "1 / (r - 2)",
+        "sqrt(1 - r)",
+        "radius * 2",
+        "min()",
+        "2 * (3 + 4",
+        "3 + * 4",
+        "1 + 2 ￥",
+        "10 ^ 400",
+    ];
+
+    for line in session {
+        println!("> {}", line);
+        match interpreter.execute(line) {
+            Ok(Outcome::Value(value)) => println!("{}", value),
+            Ok(Outcome::Assigned(name, value)) => println!("{} = {}", name, value),
+            Err(e) => {
+                if let Some(caret) = calc::caret_line(line, &e) {
+                    println!("  {}", caret);
+                }
+                println!("错误: {}", e);
+            }
+        }
+    }
+
+    println!("\n变量:");
+    for (name, value) in interpreter.variables() {
+        println!("  {} = {}", name, value);
+    }
+
+    // REPL 只依赖 BufRead 和 Write：终端用 stdin/stdout，这里用内存中的脚本
+    let script = "let x = 3\nx * 2\n:vars\n1 / (x - 3)\n   1 +\nlet 价格 = 价格 * 2\n  :quit\n不会执行\n";
+    let mut output = Vec::new();
+    calc::repl(std::io::Cursor::new(script), &mut output).unwrap();
+    let transcript = String::from_utf8(output).unwrap();
+    println!("\nREPL 脚本输出:");
+    print!("{}", transcript);
+    // 输入不会回显，所以结果紧跟在提示符后面；:quit 之后的行不再执行
+    let expected = [
+        "> x = 3",
+        "> 6",
+        ">   e = 2.718281828459045",
+        "  pi = 3.141592653589793",
+        "  x = 3",
+        ">   ^^^^^^^^^^^",
+        "错误: 第 1 列: 除数为零",
+        // 开头的空格保留，^ 对齐到 + 后面的输入结束处
+        ">         ^",
+        "错误: 第 7 列: 期望数字、变量或 '('，但输入已经结束",
+        // 中文变量名每个字占两列，前面的 "let 价格 = " 占 11 列
+        ">              ^^^^",
+        "错误: 第 10 列: 未定义的变量 '价格'",
+        "> ",
+    ];
+    assert_eq!(transcript.lines().collect::<Vec<_>>(), expected);
+
+    println!("\n错误处理:");
+    println!("  - 所有错误都是 ExprError 枚举的变体（thiserror 派生）");
+    println!("  - 解析器生成的 Expr::Spanned 节点记录源码位置");
+    println!("  - 除以零、负数开方、溢出都返回错误，而不是 inf / NaN");
+    println!("  - 交互式计算器：在 main.rs 中把 calc::repl 接到 stdin/stdout");
+}
+
+/// 示例 18: 表达式语言 - 字节码编译与栈式虚拟机
+///
+/// 把 Expr 树编译成扁平的指令序列，再在栈式虚拟机上执行
+pub fn bytecode_vm() {
+    println!("\n=== 示例 18: 表达式语言 - 字节码编译与栈式虚拟机 ===");
+
+    use expr::Env;
+    use vm::Vm;
+
+    let source = "-x ^ 2 + max(x, 3) * (y - 1) / 2";
+    let expr = parser::parse(source).unwrap();
+    let chunk = vm::compile(&expr);
+
+    println!("源码: {}", source);
+    println!("反汇编:");
+    print!("{}", chunk.disassemble());
+
+    let mut env = Env::new();
+    env.insert(String::from("x"), 4.0);
+    env.insert(String::from("y"), 5.0);
+
+    let mut machine = Vm::new();
+    let result = machine.run(&chunk, &env);
+    println!("x = 4, y = 5 时的结果: {:?}", result);
+    assert_eq!(result, expr.eval_with(&env));
+
+    // 错误和位置信息与树遍历求值完全相同
+    env.insert(String::from("y"), 1.0);
+    let source = "x / (y - 1)";
+    let chunk = vm::compile(&parser::parse(source).unwrap());
+    let error = machine.run(&chunk, &env).unwrap_err();
+    println!("\n> {}", source);
+    if let Some(caret) = calc::caret_line(source, &error) {
+        println!("  {}", caret);
+    }
+    println!("错误: {}", error);
+
+    // if 编译成跳转：只执行选中的分支，没有选中的 1 / (x - 4) 不会报除零
+    let source = "if(x - 4, 1 / (x - 4), max(x, y))";
+    let expr = parser::parse(source).unwrap();
+    let chunk = vm::compile(&expr);
+    println!("\n源码: {}", source);
+    println!("反汇编:");
+    print!("{}", chunk.disassemble());
+    assert!(chunk.code().iter().any(|i| matches!(i, vm::Instr::JumpIfZero(_))));
+    assert!(chunk.code().iter().any(|i| matches!(i, vm::Instr::Jump(_))));
+    for x in [4.0, 6.0] {
+        env.insert(String::from("x"), x);
+        let result = machine.run(&chunk, &env);
+        println!("x = {} 时的结果: {:?}", x, result);
+        assert_eq!(result, expr.eval_with(&env));
+    }
+
+    println!("\n栈式虚拟机:");
+    println!("  - 指令是后序遍历的结果：先算操作数，再执行运算");
+    println!("  - 变量名在编译时换成槽位编号，函数名换成函数编号");
+    println!("  - Spanned 节点不生成指令，它的位置记录在内部每条指令上");
+    println!("  - if(c, a, b) 编译成 JZ / JUMP，跳转目标在生成分支之后回填");
+    println!("  - 栈和槽位在多次 run 之间复用");
+}
+
+/// 示例 19: 表达式语言 - 虚拟机与树遍历求值的一致性
+///
+/// 随机生成大量表达式，检查两种求值方式的结果（包括错误）完全相同
+pub fn vm_matches_tree_walk() {
+    println!("\n=== 示例 19: 表达式语言 - 虚拟机与树遍历求值的一致性 ===");
+
+    use crate::util::XorShift;
+    use expr::{Env, Expr, Span};
+    use vm::Vm;
+
+    // 随机表达式：包含未定义的变量 w、未知函数 foo、除零和负数开方等出错情况
+    fn random_expr(rng: &mut XorShift, depth: u32) -> Expr {
+        let leaf = depth == 0 || rng.below(4) == 0;
+        let node = if leaf {
+            match rng.below(3) {
+                0 => Expr::Var(["x", "y", "z", "w"][rng.below(4) as usize].to_string()),
+                _ => Expr::Number(rng.below(7) as f64 - 2.0),
+            }
+        } else {
+            let sub = |rng: &mut XorShift| Box::new(random_expr(rng, depth - 1));
+            match rng.below(8) {
+                0 => Expr::Neg(sub(rng)),
+                1 => Expr::Add(sub(rng), sub(rng)),
+                2 => Expr::Subtract(sub(rng), sub(rng)),
+                3 => Expr::Multiply(sub(rng), sub(rng)),
+                4 => Expr::Divide(sub(rng), sub(rng)),
+                5 => Expr::Pow(sub(rng), sub(rng)),
+                6 => {
+                    let name = ["sqrt", "min", "max", "foo", "if"][rng.below(5) as usize];
+                    let args = (0..rng.below(4)).map(|_| random_expr(rng, depth - 1)).collect();
+                    Expr::Call(name.to_string(), args)
+                }
+                // 条件经常是 0（变量 z 为 0），两个分支都会被选中
+                _ => {
+                    let args = (0..3).map(|_| random_expr(rng, depth - 1)).collect();
+                    Expr::Call(String::from("if"), args)
+                }
+            }
+        };
+        // 随机加上位置，检查错误位置也一致
+        if rng.below(3) == 0 {
+            let start = rng.below(50) as usize;
+            Expr::Spanned(Span::new(start, start + 1), Box::new(node))
+        } else {
+            node
+        }
+    }
+
+    let mut env = Env::new();
+    env.insert(String::from("x"), 3.0);
+    env.insert(String::from("y"), -0.5);
+    env.insert(String::from("z"), 0.0);
+
+    // 固定种子让结果可以复现
+    let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+    let mut machine = Vm::new();
+    let cases = 10_000;
+    let mut values = 0;
+
+    for _ in 0..cases {
+        let expr = random_expr(&mut rng, 6);
+        let expected = expr.eval_with(&env);
+        let actual = machine.run(&vm::compile(&expr), &env);
+        assert_eq!(actual, expected, "表达式 {} 的结果不一致", expr);
+        if expected.is_ok() {
+            values += 1;
+        }
+    }
+
+    println!("✓ {} 个随机表达式的结果完全一致", cases);
+    println!("  其中 {} 个求值成功，{} 个返回相同的错误", values, cases - values);
+
+    println!("\n基于性质的测试:");
+    println!("  - 性质: 对任意表达式 e，Vm::run(compile(e)) == e.eval()");
+    println!("  - 固定种子的伪随机数，失败时可以复现");
+    println!("  - 生成器有意包含出错的情况，检查错误种类和位置也一致");
+    println!("  - if 的分支里也有出错的情况，检查两边都只求值选中的分支");
+}
+
+/// 示例 20: 表达式语言 - 树遍历与虚拟机的性能对比
+pub fn vm_benchmark() {
+    println!("\n=== 示例 20: 表达式语言 - 树遍历与虚拟机的性能对比 ===");
+
+    use expr::Env;
+    use std::time::Instant;
+    use vm::Vm;
+
+    let source = "x * x + 3 * x - sqrt(y) / 2 + max(x, y, 1) ^ 2 - (x - y) * (x + y)";
+    let expr = parser::parse(source).unwrap();
+    let chunk = vm::compile(&expr);
+    println!("表达式: {}", source);
+    println!("编译为 {} 条指令", chunk.code().len());
+
+    let iterations = 200_000;
+    let mut env = Env::new();
+    env.insert(String::from("y"), 2.0);
+
+    let start = Instant::now();
+    let mut tree_sum = 0.0;
+    for i in 0..iterations {
+        env.insert(String::from("x"), i as f64 * 0.001);
+        tree_sum += expr.eval_with(&env).unwrap();
+    }
+    let tree_duration = start.elapsed();
+
+    let mut machine = Vm::new();
+    let start = Instant::now();
+    let mut vm_sum = 0.0;
+    for i in 0..iterations {
+        env.insert(String::from("x"), i as f64 * 0.001);
+        vm_sum += machine.run(&chunk, &env).unwrap();
+    }
+    let vm_duration = start.elapsed();
+
+    assert_eq!(tree_sum, vm_sum);
+    println!("求值 {} 次:", iterations);
+    println!("  树遍历:   {:?}", tree_duration);
+    println!("  虚拟机:   {:?}", vm_duration);
+    println!(
+        "  加速比:   {:.2}x",
+        tree_duration.as_secs_f64() / vm_duration.as_secs_f64()
+    );
+
+    println!("\n差异来源:");
+    println!("  - 树遍历每个节点都是一次递归调用和一次 Box 指针跳转");
+    println!("  - 树遍历每次访问变量都要查一次 HashMap，虚拟机每次运行只查一次");
+    println!("  - 虚拟机的指令连续存放，对 CPU 缓存更友好");
+    println!("  - 编译只做一次，反复求值时优势更明显（用 --release 对比更准确）");
+}
+
+/// 示例 21: 表达式语言 - 符号求导
+///
+/// 按求导法则把一棵 Expr 树变换成它的导数，再化简
+pub fn symbolic_differentiation() {
+    println!("\n=== 示例 21: 表达式语言 - 符号求导 ===");
+
+    use expr::Env;
+
+    let cases = [
+        "x ^ 3 + 2 * x ^ 2 - 5 * x + 7",
+        "x * sqrt(x)",
+        "(x + 1) / (x - 1)",
+        "2 ^ x",
+        "x ^ x",
+        "ln(x ^ 2 + 1)",
+        "y * x ^ 2 + y",
+    ];
+
+    for source in cases {
+        let expr = parser::parse(source).unwrap();
+        let raw = expr.derivative("x").unwrap();
+        let simplified = raw.simplify();
+        println!("d/dx {}", source);
+        println!("  未化简: {}", raw.pretty());
+        println!("  化简后: {}", simplified.pretty());
+
+        // 用数值差分验证：f'(x) ≈ (f(x + h) - f(x - h)) / 2h
+        let h = 1e-6;
+        for x in [0.5, 1.7, 3.0] {
+            let mut env = Env::new();
+            env.insert(String::from("y"), 2.0);
+            env.insert(String::from("x"), x + h);
+            let ahead = expr.eval_with(&env).unwrap();
+            env.insert(String::from("x"), x - h);
+            let behind = expr.eval_with(&env).unwrap();
+            env.insert(String::from("x"), x);
+            let numeric = (ahead - behind) / (2.0 * h);
+            let symbolic = simplified.eval_with(&env).unwrap();
+            assert!((numeric - symbolic).abs() < 1e-4 * numeric.abs().max(1.0));
+        }
+    }
+    println!("✓ 所有导数都与数值差分的结果一致");
+
+    // 不可导的函数返回错误，位置指向出错的调用
+    let source = "x + max(x, 1)";
+    let error = parser::parse(source).unwrap().derivative("x").unwrap_err();
+    println!("\nd/dx {}", source);
+    if let Some(caret) = calc::caret_line(source, &error) {
+        println!("     {}", caret);
+    }
+    println!("错误: {}", error);
+
+    println!("\n求导法则:");
+    println!("  - 每种 Expr 变体对应一条法则，子表达式递归求导");
+    println!("  - 乘法法则、除法法则、幂法则、链式法则");
+    println!("  - 求导的结果是新的 Expr，可以继续化简、求值或再次求导");
+}
+
+/// 示例 22: 表达式语言 - 代数化简
+pub fn algebraic_simplification() {
+    println!("\n=== 示例 22: 表达式语言 - 代数化简 ===");
+
+    let cases = [
+        ("x * 1 + 0", "x"),
+        ("0 * y + x ^ 1", "x"),
+        ("2 * 3 + x * (4 - 4)", "6"),
+        ("x + x + x", "3 * x"),
+        ("2 * x + y - x + 3 - 1", "x + y + 2"),
+        ("x * 2 * x / x ^ 3", "2 / x"),
+        ("x - x", "0"),
+        ("-(-x)", "x"),
+        ("y * x - 3 * (x * y)", "-2 * x * y"),
+        ("x / 4 - x / 2", "-0.25 * x"),
+        ("1 / 0 + x", "1 / 0 + x"),
+    ];
+
+    for (input, expected) in cases {
+        let simplified = parser::parse(input).unwrap().simplify();
+        println!("  {:<24} => {}", input, simplified.pretty());
+        assert_eq!(simplified.pretty().to_string(), expected);
+    }
+
+    println!("\n化简规则:");
+    println!("  - 常量折叠: 子节点都是数字就直接求值（出错的 1 / 0 保留原样）");
+    println!("  - 恒等式: x * 1、x + 0、x ^ 1、x * 0、--x");
+    println!("  - 合并同类项: 和式展开为 (系数, 项) 的列表，相同的项系数相加");
+    println!("  - 合并同底数: 积式展开为 (底数, 指数) 的列表，相同的底数指数相加");
+    println!("  - 反复应用直到表达式不再变化（不动点）");
+}
+
+/// 示例 23: 表达式语言 - 最少括号的输出
+pub fn minimal_parentheses() {
+    println!("\n=== 示例 23: 表达式语言 - 最少括号的输出 ===");
+
+    let cases = [
+        ("1 + 2 * 3", "1 + 2 * 3"),
+        ("(1 + 2) * 3", "(1 + 2) * 3"),
+        ("1 - (2 - 3)", "1 - (2 - 3)"),
+        ("(1 - 2) - 3", "1 - 2 - 3"),
+        ("2 ^ 3 ^ 2", "2 ^ 3 ^ 2"),
+        ("(2 ^ 3) ^ 2", "(2 ^ 3) ^ 2"),
+        ("(-2) ^ 2", "(-2) ^ 2"),
+        ("-(2 ^ 2)", "-2 ^ 2"),
+        ("-(x * y)", "-(x * y)"),
+        ("max((1), ((2 + 3)))", "max(1, 2 + 3)"),
+    ];
+
+    println!("  {:<22} {:<30} pretty", "输入", "to_string（完全加括号）");
+    for (input, expected) in cases {
+        let expr = parser::parse(input).unwrap();
+        let pretty = expr.pretty().to_string();
+        println!("  {:<22} {:<30} {}", input, expr.to_string(), pretty);
+        assert_eq!(pretty, expected);
+
+        // 去掉的括号都是多余的：重新解析得到结构相同的树
+        let reparsed = parser::parse(&pretty).unwrap();
+        assert_eq!(reparsed.without_spans(), expr.without_spans());
+    }
+    println!("✓ 输出重新解析后得到相同的表达式树");
+
+    println!("\n加括号的规则:");
+    println!("  - 子表达式的优先级低于父节点时加括号");
+    println!("  - 左结合运算的右侧、右结合运算（^）的左侧，同级也要加括号");
+    println!("  - 负数和一元负号的优先级低于乘方，所以 (-2) ^ 2 需要括号");
+}
+
+/// 示例 24: 状态机框架 - 红绿灯
+///
+/// 用通用的 Fsm 重写示例 14：转换写在表里，持续时间放在进入动作中
+pub fn fsm_traffic_light() {
+    println!("\n=== 示例 24: 状态机框架 - 红绿灯 ===");
+
+    use fsm::FsmBuilder;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Light {
+        Red,
+        Green,
+        Yellow,
+        Flashing,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Event {
+        Timer,
+        Fault,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Stats {
+        seconds: u32,
+        faults: u32,
+    }
+
+    let mut light = FsmBuilder::new(Light::Red, Stats::default())
+        .transition(Light::Red, Event::Timer, Light::Green)
+        .transition(Light::Green, Event::Timer, Light::Yellow)
+        .transition(Light::Yellow, Event::Timer, Light::Red)
+        .transition(Light::Red, Event::Fault, Light::Flashing)
+        .transition(Light::Green, Event::Fault, Light::Flashing)
+        .transition(Light::Yellow, Event::Fault, Light::Flashing)
+        .transition(Light::Flashing, Event::Reset, Light::Red)
+        .on_entry(Light::Red, |s: &mut Stats| {
+            s.seconds += 60;
+            println!("  红灯 - 停止 (持续 60 秒)");
+        })
+        .on_entry(Light::Green, |s: &mut Stats| {
+            s.seconds += 55;
+            println!("  绿灯 - 通行 (持续 55 秒)");
+        })
+        .on_entry(Light::Yellow, |s: &mut Stats| {
+            s.seconds += 3;
+            println!("  黄灯 - 准备 (持续 3 秒)");
+        })
+        .on_entry(Light::Flashing, |s: &mut Stats| {
+            s.faults += 1;
+            println!("  黄灯闪烁 - 故障，注意通行");
+        })
+        .build();
+
+    for event in [Event::Timer, Event::Timer, Event::Timer, Event::Fault] {
+        light.fire(event).unwrap();
+    }
+    // 故障状态下计时器事件是非法的
+    match light.fire(Event::Timer) {
+        Ok(state) => println!("  意外地进入了 {:?}", state),
+        Err(e) => println!("  错误: {}", e),
+    }
+    light.fire(Event::Reset).unwrap();
+
+    println!("当前状态: {:?}，可以处理的事件: {:?}", light.state(), light.available_events());
+    println!("累计时长 {} 秒，故障 {} 次", light.context().seconds, light.context().faults);
+    assert_eq!(light.context().seconds, 60 + 55 + 3 + 60 + 60);
+
+    println!("\nDOT 状态图:");
+    print!("{}", light.to_dot("traffic_light"));
+
+    // Debug 输出带引号和反斜杠的状态、带空格的图名：DOT 中都要转义
+    let quoting = FsmBuilder::new(String::from("say \"hi\""), ())
+        .transition(String::from("say \"hi\""), '\\', String::from("done"))
+        .build();
+    let dot = quoting.to_dot("my graph");
+    assert!(dot.starts_with("digraph \"my graph\" {"));
+    assert!(dot.contains(r#"    "\"say \\\"hi\\\"\"" -> "\"done\"" [label="'\\\\'"];"#), "{}", dot);
+    println!("✓ 图名、状态和标签中的引号与反斜杠都已转义");
+
+    println!("\n对比示例 14:");
+    println!("  - 状态转换从 match 变成了数据（转换表），可以遍历、导出成图");
+    println!("  - 每个状态的行为放在进入动作里，和转换规则分开");
+    println!("  - 非法事件返回 FsmError，而不是被 match 静默忽略");
+}
+
+/// 示例 25: 状态机框架 - 守卫、动作与历史
+pub fn fsm_order_workflow() {
+    println!("\n=== 示例 25: 状态机框架 - 守卫、动作与历史 ===");
+
+    use fsm::{FsmBuilder, FsmError};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum OrderState {
+        Created,
+        Paid,
+        Shipped,
+        Delivered,
+        Cancelled,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum OrderEvent {
+        Pay,
+        Ship,
+        Deliver,
+        Cancel,
+    }
+
+    struct Account {
+        balance: f64,
+        amount: f64,
+        stock: u32,
+    }
+
+    use OrderEvent::*;
+    use OrderState::*;
+
+    let account = Account {
+        balance: 50.0,
+        amount: 80.0,
+        stock: 1,
+    };
+    let mut order = FsmBuilder::new(Created, account)
+        .guarded(Created, Pay, Paid, "余额充足", |a: &Account| a.balance >= a.amount)
+        .with_action(|a: &mut Account| a.balance -= a.amount)
+        .guarded(Paid, Ship, Shipped, "有库存", |a: &Account| a.stock > 0)
+        .transition(Shipped, Deliver, Delivered)
+        .transition(Created, Cancel, Cancelled)
+        .transition(Paid, Cancel, Cancelled)
+        .with_action(|a: &mut Account| a.balance += a.amount)
+        .on_exit(Paid, |_: &mut Account| println!("  (离开 Paid: 锁定订单金额)"))
+        .on_entry(Shipped, |a: &mut Account| a.stock -= 1)
+        .on_entry(Delivered, |_: &mut Account| println!("  (进入 Delivered: 发送签收通知)"))
+        .build();
+
+    let fire = |order: &mut fsm::Fsm<OrderState, OrderEvent, Account>, event: OrderEvent| {
+        let label = format!("{:?}", event);
+        match order.fire(event) {
+            Ok(state) => println!("{:<8} → {:?}", label, state),
+            Err(e) => println!("{:<8} ✗ {}", label, e),
+        }
+    };
+
+    fire(&mut order, Ship); // 还没付款
+    fire(&mut order, Pay); // 余额不足，被守卫拒绝
+    order.context_mut().balance += 100.0;
+    fire(&mut order, Pay);
+    fire(&mut order, Pay); // 不能重复付款
+    fire(&mut order, Ship);
+    fire(&mut order, Deliver);
+    fire(&mut order, Cancel); // 已经签收
+    println!("余额: {}，库存: {}", order.context().balance, order.context().stock);
+
+    assert_eq!(order.state(), &Delivered);
+    assert_eq!(order.context().balance, 70.0);
+    assert_eq!(
+        order.fire(Pay),
+        Err(FsmError::InvalidTransition {
+            state: Delivered,
+            event: Pay
+        })
+    );
+
+    println!("\n历史记录:");
+    for (i, record) in order.history().iter().enumerate() {
+        match &record.to {
+            Some(to) => println!("  {}. {:?} --{:?}--> {:?}", i + 1, record.from, record.event, to),
+            None => println!("  {}. {:?} --{:?}--> (拒绝)", i + 1, record.from, record.event),
+        }
+    }
+
+    println!("\nDOT 状态图:");
+    print!("{}", order.to_dot("order"));
+
+    println!("\n守卫与动作:");
+    println!("  - 守卫只读上下文，决定转换能否发生；被拒绝时状态不变");
+    println!("  - 执行顺序: 退出动作 → 转换动作 → 进入动作");
+    println!("  - 被拒绝的事件也记录在历史中，方便排查");
+}
+
+/// 示例 26: 类型状态 - 编译期检查的状态机
+pub fn fsm_typestate() {
+    println!("\n=== 示例 26: 类型状态 - 编译期检查的状态机 ===");
+
+    use fsm::typestate::Order;
+
+    let order = Order::new(1, 80.0);
+    println!("订单 {} 状态: {}", order.id, order.state_name());
+    let order = order.pay();
+    println!("订单 {} 状态: {}", order.id, order.state_name());
+    let order = order.ship("SF1234567");
+    println!("订单 {} 状态: {}，运单号 {:?}", order.id, order.state_name(), order.tracking);
+    let order = order.deliver();
+    println!("订单 {} 状态: {}", order.id, order.state_name());
+
+    let (cancelled, refund) = Order::new(2, 35.5).pay().cancel();
+    println!("订单 {} 状态: {}，退款 {}", cancelled.id, cancelled.state_name(), refund);
+    assert_eq!(refund, 35.5);
+    let cancelled = Order::new(3, 10.0).cancel();
+    println!("订单 {} 状态: {}", cancelled.id, cancelled.state_name());
+
+    // 非法转换无法通过编译：
+    // Order::new(4, 1.0).ship("x");   // 错误: Order<Created> 没有 ship 方法
+    // order.pay();                     // 错误: order 已经被 deliver() 移动
+    // cancelled.pay();                 // 错误: Order<Cancelled> 没有 pay 方法
+
+    println!("\n两种实现的对比:");
+    println!("  - 运行时 Fsm: 转换表是数据，可以遍历、导出状态图，状态可以动态决定");
+    println!("  - 类型状态: 每个状态是一个类型，非法转换是编译错误");
+    println!("  - 类型状态的转换消耗 self，旧状态的值不能再被使用");
+    println!("  - 标记类型和 PhantomData 都是零大小，运行时没有额外开销");
+}
+
+/// 示例 27: 持久化链表 - 共享尾部
+///
+/// 示例 12 的 Cons 链表换成 Rc 后，多个链表可以共享同一段尾部
+pub fn persistent_list() {
+    println!("\n=== 示例 27: 持久化链表 - 共享尾部 ===");
+
+    use list::List;
+
+    let a: List<i32> = (1..=3).collect();
+    let b = a.push_front(0);
+    let c = a.tail();
+
+    println!("a = {}", a);
+    println!("b = a.push_front(0) = {}", b);
+    println!("c = a.tail() = {}", c);
+    println!("b.tail() 与 a 共享节点: {}", b.tail().ptr_eq(&a));
+    println!("c 与 a.tail() 共享节点: {}", c.ptr_eq(&a.tail()));
+    println!("a.head() = {:?}，空链表的 head = {:?}", a.head(), List::<i32>::new().head());
+
+    let words: List<String> = ["rust", "is", "fun"].iter().map(|s| s.to_string()).collect();
+    let lengths = words.map(|w| w.len());
+    println!("\nwords = {:?}", words);
+    println!("words.map(len) = {}", lengths);
+    println!("words.reverse() = {}", words.reverse());
+
+    let numbers: List<i32> = (1..=10).collect();
+    let evens = numbers.filter(|n| n % 2 == 0);
+    let small = numbers.filter(|n| *n > 3);
+    println!("\nnumbers = {}", numbers);
+    println!("filter(偶数) = {} (长度 {})", evens, evens.len());
+    println!("filter(> 3) = {}，与 numbers 的尾部共享: {}", small, {
+        let mut tail = numbers.clone();
+        for _ in 0..3 {
+            tail = tail.tail();
+        }
+        small.ptr_eq(&tail)
+    });
+
+    let sum: i32 = numbers.iter().sum();
+    println!("求和: {}", sum);
+
+    println!("\n持久化数据结构:");
+    println!("  - 所有操作都返回新链表，旧版本保持不变，可以放心共享");
+    println!("  - push_front、tail、clone 都是 O(1)，只复制 Rc 指针");
+    println!("  - filter 只复制最后一个被删除元素之前的部分");
+}
+
+/// 示例 28: 持久化链表 - 正确性检查
+pub fn persistent_list_checks() {
+    println!("\n=== 示例 28: 持久化链表 - 正确性检查 ===");
+
+    use list::List;
+    use std::rc::Rc;
+
+    // 基本操作
+    let list: List<i32> = vec![1, 2, 3].into_iter().collect();
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.head(), Some(&1));
+    assert_eq!(list.tail().head(), Some(&2));
+    assert!(List::<i32>::new().tail().is_empty());
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(list.map(|x| x * 10), [10, 20, 30].into_iter().collect());
+    assert_eq!(list.reverse(), [3, 2, 1].into_iter().collect());
+    assert_eq!(list.filter(|x| *x != 2), [1, 3].into_iter().collect());
+    assert_eq!(list.filter(|_| false), List::new());
+    assert_eq!(list.to_string(), "[1, 2, 3]");
+    assert_eq!(format!("{:?}", List::<i32>::new()), "[]");
+    println!("✓ push_front / head / tail / iter / map / filter / reverse / Display");
+
+    // 持久化：旧版本不受影响
+    let longer = list.push_front(0);
+    let shorter = list.tail();
+    assert_eq!(list, [1, 2, 3].into_iter().collect());
+    assert_eq!(longer.len(), 4);
+    assert_eq!(shorter.len(), 2);
+    assert!(longer.tail().ptr_eq(&list));
+    println!("✓ 旧版本在派生出新链表后保持不变，尾部共享");
+
+    // 释放：共享的节点在最后一个使用者释放后才被释放
+    let tracker = Rc::new(());
+    let base: List<Rc<()>> = (0..3).map(|_| Rc::clone(&tracker)).collect();
+    let extended = base.push_front(Rc::clone(&tracker));
+    assert_eq!(Rc::strong_count(&tracker), 5);
+    drop(base);
+    assert_eq!(Rc::strong_count(&tracker), 5, "base 的节点仍被 extended 共享");
+    drop(extended);
+    assert_eq!(Rc::strong_count(&tracker), 1);
+    println!("✓ 共享的节点在最后一个链表释放后才释放");
+
+    // 一百万个节点：递归的 Drop 会栈溢出，这里是循环
+    let big: List<u32> = (0..1_000_000).collect();
+    let shared_tail = big.tail().tail();
+    assert_eq!(big.iter().map(|&x| x as u64).sum::<u64>(), 499_999_500_000);
+    drop(big);
+    assert_eq!(shared_tail.len(), 999_998);
+    assert_eq!(shared_tail.head(), Some(&2));
+    drop(shared_tail);
+    let reversed: List<u32> = (0..1_000_000).collect::<List<u32>>().reverse();
+    assert_eq!(reversed.head(), Some(&999_999));
+    drop(reversed);
+    println!("✓ 释放一百万个节点的链表不会栈溢出（包括共享尾部的情况）");
+
+    println!("\n非递归 Drop:");
+    println!("  - 默认的 drop 递归释放 next，链表多长调用栈就有多深");
+    println!("  - 自定义 Drop 用循环逐个取出节点，Rc::try_unwrap 失败说明后面的节点被共享，停止");
+}
+
+/// 示例 29: 表达式语言 - 带源码片段的错误诊断
+///
+/// 把 ExprError 交给顶层 diagnostic 模块的渲染器，和配置文件的错误用同一种格式
+pub fn expression_diagnostics() {
+    println!("\n=== 示例 29: 表达式语言 - 带源码片段的错误诊断 ===");
+
+    use diagnostic::expr_diagnostic;
+    use expr::ExprError;
+
+    // 解析错误和求值错误
+    for input in ["2 * （3 + 4)", "sqrt(16) + 2 *", "1 + foo(2)", "10 / (5 - 5) + 1"] {
+        let result = parser::parse(input).and_then(|expr| expr.eval());
+        if let Err(e) = result {
+            println!("{}", expr_diagnostic(&e).render("<输入>", input));
+        }
+    }
+
+    // 快照：渲染结果和事先确认过的文本逐字比较
+    let render = |input: &str| {
+        let error = parser::parse(input).and_then(|expr| expr.eval()).unwrap_err();
+        expr_diagnostic(&error).render("<输入>", input)
+    };
+    let cases = [
+        ("全角括号", render("2 * （3 + 4)")),
+        ("求值错误", render("1 + sqrt(0 - 4)")),
+        ("没有位置", expr_diagnostic(&ExprError::DivisionByZero { span: None }).render("<输入>", "")),
+    ];
+    let expected = [
+        r#"错误: 无法识别的字符 '（'
+ --> <输入>:1:5
+  |
+1 | 2 * （3 + 4)
+  |     ^^ 无法识别
+  |
+  = 帮助: 这是全角字符，改成 '('
+"#,
+        r#"错误: 不能对负数 -4 开平方
+ --> <输入>:1:5
+  |
+1 | 1 + sqrt(0 - 4)
+  |     ^^^^^^^^^^^ 参数超出定义域
+"#,
+        r#"错误: 除数为零
+ --> <输入>
+  = 注: 这个错误没有记录位置（表达式不是由解析器生成的）
+"#,
+    ];
+    for ((name, rendered), expected) in cases.iter().zip(expected) {
+        assert_eq!(rendered, expected, "快照 \"{}\" 不一致，实际输出:\n{}", name, rendered);
+    }
+    println!("✓ {} 个快照全部一致", cases.len());
+
+    println!("\n要点:");
+    println!("  - Span 是字符下标，直接用作诊断标签的区间");
+    println!("  - 标题去掉 \"第 N 列\"，位置由 --> 那一行显示");
+    println!("  - 全角符号提示对应的半角符号，未定义的变量提示用 let 定义");
 }
 
 /// 运行所有示例
@@ -493,6 +1297,20 @@ pub fn run_all_examples() {
     enum_memory_layout();
     practical_state_machine();
     practical_expression_eval();
+    expression_parsing();
+    expression_interpreter();
+    bytecode_vm();
+    vm_matches_tree_walk();
+    vm_benchmark();
+    symbolic_differentiation();
+    algebraic_simplification();
+    minimal_parentheses();
+    fsm_traffic_light();
+    fsm_order_workflow();
+    fsm_typestate();
+    persistent_list();
+    persistent_list_checks();
+    expression_diagnostics();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  枚举是 Rust 中表达复杂数据的利器！ ║");
@@ -0,0 +1,123 @@
+//! # 计算器解释器与 REPL
+//!
+//! 把词法分析、语法分析和求值串起来：
+//! `Interpreter` 保存 `let` 定义的变量，`repl` 提供交互式命令行。
+
+use std::io::{self, BufRead, Write};
+
+use super::expr::{BUILTINS, Env, ExprError};
+use super::parser::{self, Statement};
+use crate::diagnostic::width_of;
+
+/// 执行一行输入的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// 表达式的值
+    Value(f64),
+    /// let 语句定义（或重新定义）了一个变量
+    Assigned(String, f64),
+}
+
+/// 解释器：保存变量环境，逐行执行输入
+pub struct Interpreter {
+    env: Env,
+}
+
+impl Interpreter {
+    /// 创建解释器，预定义常量 pi 和 e
+    pub fn new() -> Self {
+        let mut env = Env::new();
+        env.insert(String::from("pi"), std::f64::consts::PI);
+        env.insert(String::from("e"), std::f64::consts::E);
+        Interpreter { env }
+    }
+
+    /// 执行一行输入
+    pub fn execute(&mut self, line: &str) -> Result<Outcome, ExprError> {
+        match parser::parse_statement(line)? {
+            Statement::Let { name, value } => {
+                let value = value.eval_with(&self.env)?;
+                self.env.insert(name.clone(), value);
+                Ok(Outcome::Assigned(name, value))
+            }
+            Statement::Expr(expr) => Ok(Outcome::Value(expr.eval_with(&self.env)?)),
+        }
+    }
+
+    /// 当前的变量（按名称排序）
+    pub fn variables(&self) -> Vec<(&str, f64)> {
+        let mut vars: Vec<_> = self.env.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        vars
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 在输入行 `source` 下方用 ^ 标出错误位置
+///
+/// `Span` 是字符下标，中文等全角字符在终端里占两列，所以按显示宽度缩进和画线
+pub fn caret_line(source: &str, error: &ExprError) -> Option<String> {
+    let span = error.span()?;
+    let indent = width_of(source, 0..span.start);
+    let width = width_of(source, span.start..span.end).max(1);
+    Some(format!("{}{}", " ".repeat(indent), "^".repeat(width)))
+}
+
+const PROMPT: &str = "> ";
+
+/// 交互式 REPL：从 `input` 逐行读取，把结果写到 `output`
+///
+/// 支持的命令：
+/// - `:vars` 列出所有变量
+/// - `:help` 显示帮助
+/// - `:quit` 退出（输入结束时也会退出）
+pub fn repl<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut interpreter = Interpreter::new();
+
+    write!(output, "{}", PROMPT)?;
+    output.flush()?;
+
+    for line in input.lines() {
+        // 只去掉行尾：开头的空白留在输入里，错误的位置才能和终端上显示的输入对齐
+        let line = line?;
+        let line = line.trim_end();
+
+        match line.trim_start() {
+            "" => {}
+            ":quit" | ":q" => break,
+            ":vars" => {
+                for (name, value) in interpreter.variables() {
+                    writeln!(output, "  {} = {}", name, value)?;
+                }
+            }
+            ":help" => {
+                writeln!(output, "  表达式: 1 + 2 * 3、-2 ^ 2、(1 + 2) * 3")?;
+                writeln!(output, "  定义变量: let x = 10")?;
+                writeln!(output, "  内置函数: {}", BUILTINS.join(", "))?;
+                writeln!(output, "  命令: :vars :help :quit")?;
+            }
+            _ => match interpreter.execute(line) {
+                Ok(Outcome::Value(value)) => writeln!(output, "{}", value)?,
+                Ok(Outcome::Assigned(name, value)) => writeln!(output, "{} = {}", name, value)?,
+                Err(e) => {
+                    // 提示符占了两列，标记要对齐到输入
+                    if let Some(caret) = caret_line(line, &e) {
+                        writeln!(output, "{}{}", " ".repeat(PROMPT.len()), caret)?;
+                    }
+                    writeln!(output, "错误: {}", e)?;
+                }
+            },
+        }
+
+        write!(output, "{}", PROMPT)?;
+        output.flush()?;
+    }
+
+    writeln!(output)?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! # 表达式语言错误的诊断
+//!
+//! `calc::caret_line` 只在输入下面画一行 `^`。这里把 `ExprError` 转换成顶层 `diagnostic` 模块的
+//! `Diagnostic`，加上说明和帮助。`Span` 本来就是字符下标，可以直接用作标签区间。
+
+use super::expr::{BUILTINS, ExprError, Span};
+use crate::diagnostic::{Diagnostic, Label};
+
+/// 转换成诊断；求值错误没有位置时只有标题和附注
+pub fn expr_diagnostic(error: &ExprError) -> Diagnostic {
+    let message = error.to_string();
+    // Display 以 "第 N 列: " 开头时去掉，位置由 --> 那一行显示
+    let title = match error.span() {
+        Some(_) => message.split_once(": ").map_or(message.as_str(), |(_, rest)| rest),
+        None => message.as_str(),
+    };
+    let diagnostic = Diagnostic::error(title);
+    let label = |span: Span, message: &str| Label::primary(span.start..span.end, message);
+
+    let Some(span) = error.span() else {
+        return diagnostic.with_note("这个错误没有记录位置（表达式不是由解析器生成的）");
+    };
+    match error {
+        ExprError::UnexpectedChar { ch, .. } => {
+            let diagnostic = diagnostic.with_label(label(span, "无法识别"));
+            // 全角符号很常见，提示对应的半角符号
+            match full_width_hint(*ch) {
+                Some(ascii) => diagnostic.with_help(format!("这是全角字符，改成 '{}'", ascii)),
+                None => diagnostic.with_help("支持的运算符有 + - * / ^ ( ) , ="),
+            }
+        }
+        ExprError::InvalidNumber { .. } => diagnostic.with_label(label(span, "不是有效的数字")),
+        ExprError::UnexpectedToken { expected, .. } => {
+            diagnostic.with_label(label(span, &format!("这里需要{}", expected)))
+        }
+        ExprError::UnexpectedEnd { expected, .. } => diagnostic
+            .with_label(label(span, &format!("这里还需要{}", expected)))
+            .with_help("表达式不完整，检查括号是否配对"),
+        ExprError::UndefinedVariable { name, .. } => diagnostic
+            .with_label(label(span, "没有定义"))
+            .with_help(format!("先用 let {} = ... 定义这个变量", name)),
+        ExprError::UnknownFunction { .. } => diagnostic
+            .with_label(label(span, "不是内置函数"))
+            .with_note(format!("内置函数有 {}", BUILTINS.join("、"))),
+        ExprError::WrongArity { expected, .. } => {
+            diagnostic.with_label(label(span, &format!("需要{}参数", expected)))
+        }
+        ExprError::DivisionByZero { .. } => diagnostic.with_label(label(span, "除数的值为 0")),
+        ExprError::Domain { .. } => diagnostic.with_label(label(span, "参数超出定义域")),
+        ExprError::Overflow { .. } => diagnostic
+            .with_label(label(span, "结果超出了 f64 的范围"))
+            .with_note("f64 最大约为 1.8e308"),
+        ExprError::NotDifferentiable { .. } => diagnostic.with_label(label(span, "不能求导")),
+    }
+}
+
+fn full_width_hint(c: char) -> Option<char> {
+    match c {
+        // 全角的 ！ 到 ～，包括 （ ） ， ＋ ＊
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0),
+        _ => None,
+    }
+}
@@ -0,0 +1,300 @@
+//! # 表达式树
+//!
+//! `practical_expression_eval`（示例 15）中的 `Expr` 枚举，提升到模块级别后扩展为
+//! 一门小型算术语言的语法树：变量、一元负号、乘方和内置函数调用。
+//!
+//! 求值不再返回 `inf` / `NaN`，而是返回带位置信息的 `ExprError`。
+
+use std::collections::HashMap;
+use std::fmt;
+
+use thiserror::Error;
+
+/// 源码中的一段区间 `[start, end)`，以字符为单位（不是字节），从 0 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// 覆盖两个区间的最小区间
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // 对用户显示从 1 开始的列号
+        write!(f, "第 {} 列", self.start + 1)
+    }
+}
+
+/// 错误信息的位置前缀：手工构造的表达式树没有位置，此时省略
+fn at(span: &Option<Span>) -> String {
+    match span {
+        Some(span) => format!("{}: ", span),
+        None => String::new(),
+    }
+}
+
+/// 词法分析、语法分析和求值过程中的所有错误
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ExprError {
+    #[error("{span}: 无法识别的字符 '{ch}'")]
+    UnexpectedChar { ch: char, span: Span },
+
+    #[error("{span}: 无效的数字 '{text}'")]
+    InvalidNumber { text: String, span: Span },
+
+    #[error("{span}: 期望{expected}，但遇到了 '{found}'")]
+    UnexpectedToken {
+        expected: &'static str,
+        found: String,
+        span: Span,
+    },
+
+    #[error("{span}: 期望{expected}，但输入已经结束")]
+    UnexpectedEnd { expected: &'static str, span: Span },
+
+    #[error("{}未定义的变量 '{name}'", at(.span))]
+    UndefinedVariable { name: String, span: Option<Span> },
+
+    #[error("{}未知的函数 '{name}'", at(.span))]
+    UnknownFunction { name: String, span: Option<Span> },
+
+    #[error("{}函数 {name} 需要{expected}参数，但传入了 {found} 个", at(.span))]
+    WrongArity {
+        name: String,
+        expected: &'static str,
+        found: usize,
+        span: Option<Span>,
+    },
+
+    #[error("{}除数为零", at(.span))]
+    DivisionByZero { span: Option<Span> },
+
+    #[error("{}{message}", at(.span))]
+    Domain {
+        message: String,
+        span: Option<Span>,
+    },
+
+    #[error("{}计算结果溢出", at(.span))]
+    Overflow { span: Option<Span> },
+
+    #[error("{}函数 {name} 不能符号求导", at(.span))]
+    NotDifferentiable { name: String, span: Option<Span> },
+}
+
+impl ExprError {
+    /// 错误发生的位置
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ExprError::UnexpectedChar { span, .. }
+            | ExprError::InvalidNumber { span, .. }
+            | ExprError::UnexpectedToken { span, .. }
+            | ExprError::UnexpectedEnd { span, .. } => Some(*span),
+            ExprError::UndefinedVariable { span, .. }
+            | ExprError::UnknownFunction { span, .. }
+            | ExprError::WrongArity { span, .. }
+            | ExprError::DivisionByZero { span }
+            | ExprError::Domain { span, .. }
+            | ExprError::Overflow { span }
+            | ExprError::NotDifferentiable { span, .. } => *span,
+        }
+    }
+
+    /// 为还没有位置的求值错误补上位置（最内层的位置优先）
+    pub fn or_at(mut self, new_span: Span) -> Self {
+        match &mut self {
+            ExprError::UndefinedVariable { span, .. }
+            | ExprError::UnknownFunction { span, .. }
+            | ExprError::WrongArity { span, .. }
+            | ExprError::DivisionByZero { span }
+            | ExprError::Domain { span, .. }
+            | ExprError::Overflow { span }
+            | ExprError::NotDifferentiable { span, .. } => {
+                span.get_or_insert(new_span);
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+/// 表达式树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Subtract(Box<Expr>, Box<Expr>),
+    Multiply(Box<Expr>, Box<Expr>),
+    Divide(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+    /// 带源码位置的节点，由解析器生成，只用于错误报告，不影响求值结果
+    Spanned(Span, Box<Expr>),
+}
+
+/// 变量环境：变量名 → 值
+pub type Env = HashMap<String, f64>;
+
+impl Expr {
+    /// 在没有变量的环境中求值
+    pub fn eval(&self) -> Result<f64, ExprError> {
+        self.eval_with(&Env::new())
+    }
+
+    /// 在给定的变量环境中求值
+    pub fn eval_with(&self, env: &Env) -> Result<f64, ExprError> {
+        let value = match self {
+            Expr::Number(n) => *n,
+            Expr::Var(name) => match env.get(name) {
+                Some(value) => *value,
+                None => {
+                    return Err(ExprError::UndefinedVariable {
+                        name: name.clone(),
+                        span: None,
+                    })
+                }
+            },
+            Expr::Neg(inner) => -inner.eval_with(env)?,
+            Expr::Add(left, right) => left.eval_with(env)? + right.eval_with(env)?,
+            Expr::Subtract(left, right) => left.eval_with(env)? - right.eval_with(env)?,
+            Expr::Multiply(left, right) => left.eval_with(env)? * right.eval_with(env)?,
+            Expr::Divide(left, right) => checked_div(left.eval_with(env)?, right.eval_with(env)?)?,
+            Expr::Pow(base, exponent) => checked_pow(base.eval_with(env)?, exponent.eval_with(env)?)?,
+            // if 只求值选中的分支，另一个分支里的错误不会发生
+            Expr::Call(name, args) if is_conditional(name, args.len()) => {
+                let branch = if args[0].eval_with(env)? != 0.0 { &args[1] } else { &args[2] };
+                branch.eval_with(env)?
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval_with(env))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call_builtin(name, &args)?
+            }
+            Expr::Spanned(span, inner) => {
+                return inner.eval_with(env).map_err(|e| e.or_at(*span));
+            }
+        };
+
+        // 统一检查：任何一步产生 inf 都视为溢出
+        if value.is_infinite() {
+            return Err(ExprError::Overflow { span: None });
+        }
+        Ok(value)
+    }
+}
+
+/// 除法，除数为零时返回错误
+pub fn checked_div(left: f64, right: f64) -> Result<f64, ExprError> {
+    if right == 0.0 {
+        return Err(ExprError::DivisionByZero { span: None });
+    }
+    Ok(left / right)
+}
+
+/// 乘方，结果不是实数（NaN）时返回错误
+pub fn checked_pow(base: f64, exponent: f64) -> Result<f64, ExprError> {
+    let result = base.powf(exponent);
+    if result.is_nan() {
+        return Err(ExprError::Domain {
+            message: String::from("负数不能开非整数次方"),
+            span: None,
+        });
+    }
+    Ok(result)
+}
+
+/// 内置函数
+pub const BUILTINS: [&str; 5] = ["sqrt", "ln", "min", "max", "if"];
+
+/// `if(条件, a, b)`：条件不为 0 时取 a，否则取 b，没有选中的分支不求值
+///
+/// 参数个数不对时和其他函数一样交给 `call_builtin` 报错
+pub fn is_conditional(name: &str, argc: usize) -> bool {
+    name == "if" && argc == 3
+}
+
+/// 调用内置函数
+pub fn call_builtin(name: &str, args: &[f64]) -> Result<f64, ExprError> {
+    let arity = |expected: &'static str| ExprError::WrongArity {
+        name: name.to_string(),
+        expected,
+        found: args.len(),
+        span: None,
+    };
+
+    match name {
+        "sqrt" => match args {
+            [x] if *x < 0.0 => Err(ExprError::Domain {
+                message: format!("不能对负数 {} 开平方", x),
+                span: None,
+            }),
+            [x] => Ok(x.sqrt()),
+            _ => Err(arity(" 1 个")),
+        },
+        "ln" => match args {
+            [x] if *x <= 0.0 => Err(ExprError::Domain {
+                message: format!("不能对非正数 {} 取对数", x),
+                span: None,
+            }),
+            [x] => Ok(x.ln()),
+            _ => Err(arity(" 1 个")),
+        },
+        "min" | "max" => {
+            if args.is_empty() {
+                return Err(arity("至少 1 个"));
+            }
+            let pick = if name == "min" { f64::min } else { f64::max };
+            Ok(args.iter().copied().reduce(pick).unwrap())
+        }
+        // 参数已经全部求值过；惰性的版本见 is_conditional
+        "if" => match args {
+            [condition, a, b] => Ok(if *condition != 0.0 { *a } else { *b }),
+            _ => Err(arity(" 3 个")),
+        },
+        _ => Err(ExprError::UnknownFunction {
+            name: name.to_string(),
+            span: None,
+        }),
+    }
+}
+
+impl fmt::Display for Expr {
+    /// 完全加括号的形式，每个二元运算都包一层括号
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Neg(inner) => write!(f, "(-{})", inner),
+            Expr::Add(left, right) => write!(f, "({} + {})", left, right),
+            Expr::Subtract(left, right) => write!(f, "({} - {})", left, right),
+            Expr::Multiply(left, right) => write!(f, "({} * {})", left, right),
+            Expr::Divide(left, right) => write!(f, "({} / {})", left, right),
+            Expr::Pow(base, exponent) => write!(f, "({} ^ {})", base, exponent),
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Spanned(_, inner) => write!(f, "{}", inner),
+        }
+    }
+}
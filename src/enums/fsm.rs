@@ -0,0 +1,368 @@
+//! # 有限状态机
+//!
+//! `practical_state_machine`（示例 14）把状态转换写死在 `next()` 的 match 里。
+//! 这里把它抽象成可复用的框架：状态和事件都是用户定义的枚举，
+//! 转换规则在运行时用转换表描述。
+//!
+//! - 守卫（guard）：转换只在条件满足时发生
+//! - 进入 / 退出动作：进入或离开某个状态时执行
+//! - 非法转换返回 `FsmError`，状态保持不变
+//! - 每个事件都记录在历史中
+//! - 导出 Graphviz DOT 格式的状态图
+//!
+//! 作为对比，`typestate` 子模块用类型系统在编译期保证转换合法。
+
+use std::fmt::{Debug, Write};
+
+use thiserror::Error;
+
+/// 守卫：根据上下文决定转换是否允许
+pub type Guard<C> = Box<dyn Fn(&C) -> bool>;
+
+/// 动作：可以修改上下文
+pub type Action<C> = Box<dyn FnMut(&mut C)>;
+
+/// 状态机的错误
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FsmError<S: Debug, E: Debug> {
+    #[error("状态 {state:?} 下没有事件 {event:?} 的转换")]
+    InvalidTransition { state: S, event: E },
+
+    #[error("状态 {state:?} 下的事件 {event:?} 被守卫拒绝: {guards}")]
+    GuardRejected { state: S, event: E, guards: String },
+}
+
+/// 转换表中的一行
+struct Transition<S, E, C> {
+    from: S,
+    event: E,
+    to: S,
+    guard: Option<(&'static str, Guard<C>)>,
+    action: Option<Action<C>>,
+}
+
+/// 历史记录中的一条
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record<S, E> {
+    pub from: S,
+    pub event: E,
+    /// 转换后的状态，事件被拒绝时为 None
+    pub to: Option<S>,
+}
+
+/// 状态机
+///
+/// `S` 是状态，`E` 是事件，`C` 是守卫和动作共享的上下文数据
+pub struct Fsm<S, E, C> {
+    state: S,
+    context: C,
+    transitions: Vec<Transition<S, E, C>>,
+    entry_actions: Vec<(S, Action<C>)>,
+    exit_actions: Vec<(S, Action<C>)>,
+    history: Vec<Record<S, E>>,
+}
+
+/// 状态机的构建器
+pub struct FsmBuilder<S, E, C> {
+    fsm: Fsm<S, E, C>,
+}
+
+impl<S, E, C> FsmBuilder<S, E, C>
+where
+    S: Clone + PartialEq + Debug,
+    E: Clone + PartialEq + Debug,
+{
+    /// 指定初始状态和上下文
+    pub fn new(initial: S, context: C) -> Self {
+        FsmBuilder {
+            fsm: Fsm {
+                state: initial,
+                context,
+                transitions: Vec::new(),
+                entry_actions: Vec::new(),
+                exit_actions: Vec::new(),
+                history: Vec::new(),
+            },
+        }
+    }
+
+    /// 添加一个无条件的转换
+    pub fn transition(mut self, from: S, event: E, to: S) -> Self {
+        self.fsm.transitions.push(Transition {
+            from,
+            event,
+            to,
+            guard: None,
+            action: None,
+        });
+        self
+    }
+
+    /// 添加一个带守卫的转换，`name` 用于错误信息和状态图
+    ///
+    /// 同一个状态和事件可以有多个带守卫的转换，按添加顺序取第一个通过的
+    pub fn guarded(
+        mut self,
+        from: S,
+        event: E,
+        to: S,
+        name: &'static str,
+        guard: impl Fn(&C) -> bool + 'static,
+    ) -> Self {
+        self.fsm.transitions.push(Transition {
+            from,
+            event,
+            to,
+            guard: Some((name, Box::new(guard))),
+            action: None,
+        });
+        self
+    }
+
+    /// 为最近添加的转换设置转换动作（在退出动作之后、进入动作之前执行）
+    ///
+    /// # Panics
+    ///
+    /// 还没有添加任何转换时 panic
+    pub fn with_action(mut self, action: impl FnMut(&mut C) + 'static) -> Self {
+        let last = self.fsm.transitions.last_mut().expect("先添加转换，再设置动作");
+        last.action = Some(Box::new(action));
+        self
+    }
+
+    /// 进入 `state` 时执行的动作
+    pub fn on_entry(mut self, state: S, action: impl FnMut(&mut C) + 'static) -> Self {
+        self.fsm.entry_actions.push((state, Box::new(action)));
+        self
+    }
+
+    /// 离开 `state` 时执行的动作
+    pub fn on_exit(mut self, state: S, action: impl FnMut(&mut C) + 'static) -> Self {
+        self.fsm.exit_actions.push((state, Box::new(action)));
+        self
+    }
+
+    /// 完成构建，并执行初始状态的进入动作
+    pub fn build(mut self) -> Fsm<S, E, C> {
+        let initial = self.fsm.state.clone();
+        self.fsm.run_actions(Side::Entry, &initial);
+        self.fsm
+    }
+}
+
+enum Side {
+    Entry,
+    Exit,
+}
+
+impl<S, E, C> Fsm<S, E, C>
+where
+    S: Clone + PartialEq + Debug,
+    E: Clone + PartialEq + Debug,
+{
+    /// 当前状态
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// 上下文数据
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// 可变的上下文数据（例如在两次事件之间修改外部条件）
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// 所有处理过的事件
+    pub fn history(&self) -> &[Record<S, E>] {
+        &self.history
+    }
+
+    /// 当前状态下能处理的事件（不检查守卫）
+    pub fn available_events(&self) -> Vec<&E> {
+        let mut events: Vec<&E> = Vec::new();
+        for t in self.transitions.iter().filter(|t| t.from == self.state) {
+            if !events.contains(&&t.event) {
+                events.push(&t.event);
+            }
+        }
+        events
+    }
+
+    /// 处理一个事件
+    ///
+    /// 找到第一个匹配且守卫通过的转换，依次执行：
+    /// 当前状态的退出动作 → 转换动作 → 新状态的进入动作。
+    /// 没有匹配的转换时返回错误，状态和上下文都不变。
+    pub fn fire(&mut self, event: E) -> Result<&S, FsmError<S, E>> {
+        let mut rejected = Vec::new();
+        let mut found = None;
+        for (i, t) in self.transitions.iter().enumerate() {
+            if t.from != self.state || t.event != event {
+                continue;
+            }
+            match &t.guard {
+                Some((name, guard)) if !guard(&self.context) => rejected.push(*name),
+                _ => {
+                    found = Some(i);
+                    break;
+                }
+            }
+        }
+
+        let from = self.state.clone();
+        let Some(index) = found else {
+            self.history.push(Record {
+                from: from.clone(),
+                event: event.clone(),
+                to: None,
+            });
+            return Err(if rejected.is_empty() {
+                FsmError::InvalidTransition { state: from, event }
+            } else {
+                FsmError::GuardRejected {
+                    state: from,
+                    event,
+                    guards: rejected.join(", "),
+                }
+            });
+        };
+
+        let to = self.transitions[index].to.clone();
+        self.run_actions(Side::Exit, &from);
+        if let Some(action) = &mut self.transitions[index].action {
+            action(&mut self.context);
+        }
+        self.state = to.clone();
+        self.run_actions(Side::Entry, &to);
+
+        self.history.push(Record {
+            from,
+            event,
+            to: Some(to),
+        });
+        Ok(&self.state)
+    }
+
+    fn run_actions(&mut self, side: Side, state: &S) {
+        let actions = match side {
+            Side::Entry => &mut self.entry_actions,
+            Side::Exit => &mut self.exit_actions,
+        };
+        for (s, action) in actions.iter_mut() {
+            if s == state {
+                action(&mut self.context);
+            }
+        }
+    }
+
+    /// 导出 Graphviz DOT 格式的状态图，当前状态用双圈标出
+    ///
+    /// 可以用 `dot -Tpng fsm.dot -o fsm.png` 渲染。图名、节点和标签都写成带引号的 ID，
+    /// 其中的 `"` 和 `\` 会转义，所以 Debug 输出里带引号的状态（比如 `String`）也能正确导出
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph {} {{", quoted(name));
+        let _ = writeln!(dot, "    rankdir=LR;");
+        let _ = writeln!(dot, "    node [shape=circle];");
+        let _ = writeln!(dot, "    {} [shape=doublecircle];", quoted(&format!("{:?}", self.state)));
+        for t in &self.transitions {
+            let label = match &t.guard {
+                Some((guard, _)) => format!("{:?} [{}]", t.event, guard),
+                None => format!("{:?}", t.event),
+            };
+            let from = quoted(&format!("{:?}", t.from));
+            let to = quoted(&format!("{:?}", t.to));
+            let _ = writeln!(dot, "    {} -> {} [label={}];", from, to, quoted(&label));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// DOT 中带引号的 ID：引号里只有 `\"` 是转义，`\` 也要转义，否则结尾的 `\` 会吞掉引号
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 类型状态（typestate）：把状态编码进类型
+///
+/// 每个状态是一个独立的类型，转换是消耗 `self` 并返回新类型的方法。
+/// 非法转换根本没有对应的方法，在编译期就会报错；代价是状态在运行时不能动态选择。
+pub mod typestate {
+    use std::marker::PhantomData;
+
+    /// 订单状态：零大小的标记类型
+    pub struct Created;
+    pub struct Paid;
+    pub struct Shipped;
+    pub struct Delivered;
+    pub struct Cancelled;
+
+    /// 订单，`S` 表示当前状态
+    pub struct Order<S> {
+        pub id: u32,
+        pub amount: f64,
+        pub tracking: Option<String>,
+        // PhantomData 不占空间，只让编译器记住状态类型
+        _state: PhantomData<S>,
+    }
+
+    impl Order<Created> {
+        pub fn new(id: u32, amount: f64) -> Self {
+            Order {
+                id,
+                amount,
+                tracking: None,
+                _state: PhantomData,
+            }
+        }
+
+        pub fn pay(self) -> Order<Paid> {
+            self.into_state()
+        }
+
+        pub fn cancel(self) -> Order<Cancelled> {
+            self.into_state()
+        }
+    }
+
+    impl Order<Paid> {
+        pub fn ship(self, tracking: &str) -> Order<Shipped> {
+            let mut order: Order<Shipped> = self.into_state();
+            order.tracking = Some(tracking.to_string());
+            order
+        }
+
+        /// 已付款的订单取消时退款
+        pub fn cancel(self) -> (Order<Cancelled>, f64) {
+            let refund = self.amount;
+            (self.into_state(), refund)
+        }
+    }
+
+    impl Order<Shipped> {
+        pub fn deliver(self) -> Order<Delivered> {
+            self.into_state()
+        }
+    }
+
+    impl<S> Order<S> {
+        /// 状态名称，所有状态共用
+        pub fn state_name(&self) -> &'static str {
+            let full = std::any::type_name::<S>();
+            full.rsplit("::").next().unwrap_or(full)
+        }
+
+        fn into_state<T>(self) -> Order<T> {
+            Order {
+                id: self.id,
+                amount: self.amount,
+                tracking: self.tracking,
+                _state: PhantomData,
+            }
+        }
+    }
+}
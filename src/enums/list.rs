@@ -0,0 +1,223 @@
+//! # 持久化链表
+//!
+//! `recursive_enum`（示例 12）里的 `Cons(i32, Box<List>)` 扩展成泛型的不可变链表。
+//! 把 `Box` 换成 `Rc` 后，多个链表可以共享同一段尾部：
+//!
+//! ```text
+//! a = [1, 2, 3]            a ──► 1 ──► 2 ──► 3
+//! b = a.push_front(0)      b ──► 0 ──┘
+//! c = a.tail()                   c ──────► 2 ──► 3（与 a 共享）
+//! ```
+//!
+//! 所有操作都不修改已有的链表，而是返回新链表，旧版本始终可用（持久化）。
+
+use std::fmt;
+use std::rc::Rc;
+
+/// 链表节点：值和指向下一个节点的共享指针
+struct Node<T> {
+    value: T,
+    next: Option<Rc<Node<T>>>,
+}
+
+/// 不可变链表，clone 只复制头指针，O(1)
+pub struct List<T> {
+    head: Option<Rc<Node<T>>>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    /// 空链表
+    pub fn new() -> Self {
+        List { head: None, len: 0 }
+    }
+
+    /// 在头部加入一个元素，返回新链表；原链表不变，并成为新链表的尾部
+    pub fn push_front(&self, value: T) -> Self {
+        List {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    /// 第一个元素
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// 去掉第一个元素后的链表（与原链表共享节点）；空链表的 tail 还是空链表
+    pub fn tail(&self) -> Self {
+        match &self.head {
+            Some(node) => List {
+                head: node.next.clone(),
+                len: self.len - 1,
+            },
+            None => List::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    /// 两个链表是否从同一个节点开始（共享全部节点）
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// 对每个元素应用 `f`，得到新链表
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> List<U> {
+        self.iter().map(f).collect()
+    }
+
+    /// 逆序的新链表
+    pub fn reverse(&self) -> Self
+    where
+        T: Clone,
+    {
+        // 从头到尾依次 push_front，正好得到逆序
+        self.iter()
+            .fold(List::new(), |list, value| list.push_front(value.clone()))
+    }
+
+    /// 只保留满足条件的元素
+    ///
+    /// 最后一个被删除的元素之后的部分原样共享，不会复制
+    pub fn filter(&self, mut keep: impl FnMut(&T) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        // kept[..copied] 在最后一个被删除的元素之前，需要复制；
+        // 最后一个被删除的元素之后的尾部整体共享
+        let mut kept: Vec<&T> = Vec::new();
+        let mut copied = 0;
+        let mut shared_from = 0;
+        for (i, value) in self.iter().enumerate() {
+            if keep(value) {
+                kept.push(value);
+            } else {
+                copied = kept.len();
+                shared_from = i + 1;
+            }
+        }
+
+        let mut shared = self.clone();
+        for _ in 0..shared_from {
+            shared = shared.tail();
+        }
+        kept[..copied]
+            .iter()
+            .rev()
+            .fold(shared, |list, value| list.push_front((*value).clone()))
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+/// 非递归的 Drop
+///
+/// 默认的 drop 会递归地释放 next，一百万个节点就是一百万层调用，会栈溢出。
+/// 这里改成循环：只要节点没有被其他链表共享（`Rc::try_unwrap` 成功），
+/// 就取出它的 next 继续；遇到共享的节点就停下，剩下的部分归其他链表所有。
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+/// 借用迭代器
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    /// 保持迭代器的顺序：先收集，再从后往前 push_front
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items
+            .into_iter()
+            .rev()
+            .fold(List::new(), |list, value| list.push_front(value))
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
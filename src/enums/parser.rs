@@ -0,0 +1,361 @@
+//! # 词法分析与语法分析
+//!
+//! 把文本转换成 `Expr` 表达式树，分两步：
+//!
+//! 1. 词法分析（tokenize）：字符流 → `Token` 序列，每个 token 记录自己的位置
+//! 2. 语法分析（parse）：`Token` 序列 → `Expr`，使用优先级爬升（precedence climbing）算法
+//!
+//! 语法（优先级从低到高）：
+//!
+//! ```text
+//! statement := "let" IDENT "=" expr | expr
+//! expr      := expr ("+" | "-") expr          优先级 1，左结合
+//!            | expr ("*" | "/") expr          优先级 2，左结合
+//!            | "-" expr                       优先级 3（-2^2 = -(2^2)）
+//!            | expr "^" expr                  优先级 4，右结合
+//!            | NUMBER | IDENT | IDENT "(" args ")" | "(" expr ")"
+//! ```
+
+use super::expr::{Expr, ExprError, Span};
+
+/// Token 的种类
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Number(f64),
+    Ident(String),
+    Let,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    LParen,
+    RParen,
+    Comma,
+    Equals,
+}
+
+impl TokenKind {
+    /// 用于错误信息的文本形式
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Number(n) => n.to_string(),
+            TokenKind::Ident(name) => name.clone(),
+            TokenKind::Let => String::from("let"),
+            TokenKind::Plus => String::from("+"),
+            TokenKind::Minus => String::from("-"),
+            TokenKind::Star => String::from("*"),
+            TokenKind::Slash => String::from("/"),
+            TokenKind::Caret => String::from("^"),
+            TokenKind::LParen => String::from("("),
+            TokenKind::RParen => String::from(")"),
+            TokenKind::Comma => String::from(","),
+            TokenKind::Equals => String::from("="),
+        }
+    }
+}
+
+/// 带位置的 token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// 词法分析：把输入切分成 token
+pub fn tokenize(input: &str) -> Result<Vec<Token>, ExprError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        let start = pos;
+
+        if c.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let kind = if c.is_ascii_digit() || c == '.' {
+            // 数字：整数部分、小数部分、可选的指数部分（1.5e3）
+            while pos < chars.len() && (chars[pos].is_ascii_digit() || chars[pos] == '.') {
+                pos += 1;
+            }
+            if pos < chars.len() && (chars[pos] == 'e' || chars[pos] == 'E') {
+                let mut lookahead = pos + 1;
+                if lookahead < chars.len() && (chars[lookahead] == '+' || chars[lookahead] == '-') {
+                    lookahead += 1;
+                }
+                if lookahead < chars.len() && chars[lookahead].is_ascii_digit() {
+                    pos = lookahead;
+                    while pos < chars.len() && chars[pos].is_ascii_digit() {
+                        pos += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..pos].iter().collect();
+            match text.parse() {
+                Ok(n) => TokenKind::Number(n),
+                Err(_) => {
+                    return Err(ExprError::InvalidNumber {
+                        text,
+                        span: Span::new(start, pos),
+                    })
+                }
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            // 标识符：字母或下划线开头，后面可以跟数字
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            let name: String = chars[start..pos].iter().collect();
+            if name == "let" {
+                TokenKind::Let
+            } else {
+                TokenKind::Ident(name)
+            }
+        } else {
+            pos += 1;
+            match c {
+                '+' => TokenKind::Plus,
+                '-' => TokenKind::Minus,
+                '*' => TokenKind::Star,
+                '/' => TokenKind::Slash,
+                '^' => TokenKind::Caret,
+                '(' => TokenKind::LParen,
+                ')' => TokenKind::RParen,
+                ',' => TokenKind::Comma,
+                '=' => TokenKind::Equals,
+                _ => {
+                    return Err(ExprError::UnexpectedChar {
+                        ch: c,
+                        span: Span::new(start, pos),
+                    })
+                }
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, pos),
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// 一行输入：赋值语句或表达式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Let { name: String, value: Expr },
+    Expr(Expr),
+}
+
+/// 二元运算符的优先级和结合性
+fn binary_precedence(kind: &TokenKind) -> Option<(u8, bool)> {
+    // (优先级, 是否右结合)
+    match kind {
+        TokenKind::Plus | TokenKind::Minus => Some((1, false)),
+        TokenKind::Star | TokenKind::Slash => Some((2, false)),
+        TokenKind::Caret => Some((4, true)),
+        _ => None,
+    }
+}
+
+/// 一元负号的优先级：高于乘除，低于乘方
+const UNARY_PRECEDENCE: u8 = 3;
+
+/// 语法分析器
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    // 输入的字符数，用于"输入已结束"错误的位置
+    end: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Result<Self, ExprError> {
+        Ok(Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            end: input.chars().count(),
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn end_span(&self) -> Span {
+        Span::new(self.end, self.end + 1)
+    }
+
+    /// 取下一个 token，没有则报告"输入已结束"
+    fn expect_any(&mut self, expected: &'static str) -> Result<Token, ExprError> {
+        match self.advance() {
+            Some(token) => Ok(token),
+            None => Err(ExprError::UnexpectedEnd {
+                expected,
+                span: self.end_span(),
+            }),
+        }
+    }
+
+    /// 取下一个 token，并要求它是指定的种类
+    fn expect(&mut self, kind: TokenKind, expected: &'static str) -> Result<Token, ExprError> {
+        let token = self.expect_any(expected)?;
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(unexpected(&token, expected))
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, ExprError> {
+        if !matches!(self.peek(), Some(Token { kind: TokenKind::Let, .. })) {
+            return Ok(Statement::Expr(self.expr(0)?));
+        }
+
+        self.advance();
+        let token = self.expect_any("变量名")?;
+        let name = match token.kind {
+            TokenKind::Ident(name) => name,
+            _ => return Err(unexpected(&token, "变量名")),
+        };
+        self.expect(TokenKind::Equals, " '='")?;
+        let value = self.expr(0)?;
+        Ok(Statement::Let { name, value })
+    }
+
+    /// 一行只能有一条语句，后面不能有多余的 token
+    fn finish(&self) -> Result<(), ExprError> {
+        match self.peek() {
+            Some(token) => Err(unexpected(token, "运算符或行尾")),
+            None => Ok(()),
+        }
+    }
+
+    /// 优先级爬升：解析所有优先级不低于 `min_precedence` 的二元运算
+    fn expr(&mut self, min_precedence: u8) -> Result<Expr, ExprError> {
+        let (mut left, mut left_span) = self.unary()?;
+
+        while let Some(token) = self.peek() {
+            let Some((precedence, right_assoc)) = binary_precedence(&token.kind) else {
+                break;
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            let op = self.advance().unwrap().kind;
+
+            // 左结合：右侧只吃优先级更高的运算；右结合：同级的也归右侧
+            let next_min = if right_assoc { precedence } else { precedence + 1 };
+            let right = self.expr(next_min)?;
+
+            let left_box = Box::new(left);
+            let right_box = Box::new(right);
+            let node = match op {
+                TokenKind::Plus => Expr::Add(left_box, right_box),
+                TokenKind::Minus => Expr::Subtract(left_box, right_box),
+                TokenKind::Star => Expr::Multiply(left_box, right_box),
+                TokenKind::Slash => Expr::Divide(left_box, right_box),
+                TokenKind::Caret => Expr::Pow(left_box, right_box),
+                _ => unreachable!("binary_precedence 只接受二元运算符"),
+            };
+            // 整个二元表达式的区间：从左操作数开始到右操作数的最后一个 token
+            left_span = left_span.to(self.last_span());
+            left = Expr::Spanned(left_span, Box::new(node));
+        }
+
+        Ok(left)
+    }
+
+    /// 最近消费的 token 的位置
+    fn last_span(&self) -> Span {
+        self.tokens[self.pos - 1].span
+    }
+
+    /// 一元负号或基本表达式，同时返回它覆盖的区间
+    fn unary(&mut self) -> Result<(Expr, Span), ExprError> {
+        if let Some(Token { kind: TokenKind::Minus, span }) = self.peek() {
+            let start = *span;
+            self.advance();
+            let operand = self.expr(UNARY_PRECEDENCE)?;
+            let span = start.to(self.last_span());
+            return Ok((Expr::Spanned(span, Box::new(Expr::Neg(Box::new(operand)))), span));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<(Expr, Span), ExprError> {
+        let token = self.expect_any("数字、变量或 '('")?;
+        match token.kind {
+            TokenKind::Number(n) => Ok((Expr::Number(n), token.span)),
+            TokenKind::Ident(name) => {
+                if matches!(self.peek(), Some(Token { kind: TokenKind::LParen, .. })) {
+                    self.advance();
+                    let args = self.arguments()?;
+                    let span = token.span.to(self.last_span());
+                    Ok((Expr::Spanned(span, Box::new(Expr::Call(name, args))), span))
+                } else {
+                    Ok((Expr::Spanned(token.span, Box::new(Expr::Var(name))), token.span))
+                }
+            }
+            TokenKind::LParen => {
+                let inner = self.expr(0)?;
+                let close = self.expect(TokenKind::RParen, " ')'")?;
+                Ok((inner, token.span.to(close.span)))
+            }
+            _ => Err(unexpected(&token, "数字、变量或 '('")),
+        }
+    }
+
+    /// 函数参数列表，左括号已经被消费
+    fn arguments(&mut self) -> Result<Vec<Expr>, ExprError> {
+        let mut args = Vec::new();
+        if matches!(self.peek(), Some(Token { kind: TokenKind::RParen, .. })) {
+            self.advance();
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expr(0)?);
+            let token = self.expect_any(" ',' 或 ')'")?;
+            match token.kind {
+                TokenKind::Comma => continue,
+                TokenKind::RParen => return Ok(args),
+                _ => return Err(unexpected(&token, " ',' 或 ')'")),
+            }
+        }
+    }
+}
+
+fn unexpected(token: &Token, expected: &'static str) -> ExprError {
+    ExprError::UnexpectedToken {
+        expected,
+        found: token.kind.describe(),
+        span: token.span,
+    }
+}
+
+/// 解析一行输入（赋值语句或表达式）
+pub fn parse_statement(input: &str) -> Result<Statement, ExprError> {
+    let mut parser = Parser::new(input)?;
+    let statement = parser.statement()?;
+    parser.finish()?;
+    Ok(statement)
+}
+
+/// 解析一个表达式
+pub fn parse(input: &str) -> Result<Expr, ExprError> {
+    let mut parser = Parser::new(input)?;
+    let expr = parser.expr(0)?;
+    parser.finish()?;
+    Ok(expr)
+}
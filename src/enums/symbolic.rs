@@ -0,0 +1,426 @@
+//! # 符号计算
+//!
+//! 不计算表达式的值，而是把一棵 `Expr` 树变换成另一棵：
+//!
+//! - `derivative`：符号求导 d/dx，按求导法则递归地构造新树
+//! - `simplify`：代数化简（常量折叠、`x * 1`、`x + 0`、合并同类项）
+//! - `pretty`：只在必要时加括号的输出形式
+//!
+//! 这些变换都是"对每种变体分别处理，再递归处理子树"，是递归枚举最典型的用法。
+
+use std::fmt;
+
+use super::expr::{Expr, ExprError};
+
+impl Expr {
+    /// 去掉解析器加上的 `Spanned` 节点，得到纯粹的表达式树
+    pub fn without_spans(&self) -> Expr {
+        map_children(self, Expr::without_spans)
+    }
+
+    /// 表达式是否依赖变量 `var`
+    pub fn depends_on(&self, var: &str) -> bool {
+        match self {
+            Expr::Number(_) => false,
+            Expr::Var(name) => name == var,
+            Expr::Neg(inner) | Expr::Spanned(_, inner) => inner.depends_on(var),
+            Expr::Add(left, right)
+            | Expr::Subtract(left, right)
+            | Expr::Multiply(left, right)
+            | Expr::Divide(left, right)
+            | Expr::Pow(left, right) => left.depends_on(var) || right.depends_on(var),
+            Expr::Call(_, args) => args.iter().any(|arg| arg.depends_on(var)),
+        }
+    }
+
+    /// 对变量 `var` 求导，结果没有化简
+    ///
+    /// `min` / `max` 不可导，返回 `NotDifferentiable`
+    pub fn derivative(&self, var: &str) -> Result<Expr, ExprError> {
+        let d = |e: &Expr| e.derivative(var);
+        // 原表达式的子树会出现在导数中，去掉位置信息
+        let plain = |e: &Expr| e.without_spans();
+
+        let result = match self {
+            Expr::Number(_) => Expr::Number(0.0),
+            Expr::Var(name) => Expr::Number(if name == var { 1.0 } else { 0.0 }),
+            Expr::Neg(u) => neg(d(u)?),
+            Expr::Add(u, v) => add(d(u)?, d(v)?),
+            Expr::Subtract(u, v) => sub(d(u)?, d(v)?),
+            // 乘法法则: (uv)' = u'v + uv'
+            Expr::Multiply(u, v) => add(mul(d(u)?, plain(v)), mul(plain(u), d(v)?)),
+            // 除法法则: (u/v)' = (u'v - uv') / v^2
+            Expr::Divide(u, v) => div(
+                sub(mul(d(u)?, plain(v)), mul(plain(u), d(v)?)),
+                pow(plain(v), Expr::Number(2.0)),
+            ),
+            Expr::Pow(u, v) => {
+                if !v.depends_on(var) {
+                    // 幂法则: (u^n)' = n * u^(n-1) * u'
+                    mul(
+                        mul(plain(v), pow(plain(u), sub(plain(v), Expr::Number(1.0)))),
+                        d(u)?,
+                    )
+                } else if !u.depends_on(var) {
+                    // 指数法则: (a^v)' = a^v * ln(a) * v'
+                    mul(mul(plain(self), ln(plain(u))), d(v)?)
+                } else {
+                    // 一般情况: (u^v)' = u^v * (v' * ln(u) + v * u' / u)
+                    mul(
+                        plain(self),
+                        add(mul(d(v)?, ln(plain(u))), div(mul(plain(v), d(u)?), plain(u))),
+                    )
+                }
+            }
+            Expr::Call(name, args) => {
+                let arity = |expected| ExprError::WrongArity {
+                    name: name.clone(),
+                    expected,
+                    found: args.len(),
+                    span: None,
+                };
+                match (name.as_str(), args.as_slice()) {
+                    // 链式法则: sqrt(u)' = u' / (2 * sqrt(u))
+                    ("sqrt", [u]) => div(d(u)?, mul(Expr::Number(2.0), plain(self))),
+                    // ln(u)' = u' / u
+                    ("ln", [u]) => div(d(u)?, plain(u)),
+                    ("sqrt" | "ln", _) => return Err(arity(" 1 个")),
+                    ("min" | "max" | "if", _) => {
+                        return Err(ExprError::NotDifferentiable {
+                            name: name.clone(),
+                            span: None,
+                        })
+                    }
+                    _ => {
+                        return Err(ExprError::UnknownFunction {
+                            name: name.clone(),
+                            span: None,
+                        })
+                    }
+                }
+            }
+            Expr::Spanned(span, inner) => {
+                return inner.derivative(var).map_err(|e| e.or_at(*span));
+            }
+        };
+        Ok(result)
+    }
+
+    /// 代数化简，反复应用规则直到表达式不再变化
+    ///
+    /// 化简按符号规则进行：`x * 0` 化为 `0`、`x / x` 化为 `1`，
+    /// 不再检查 `x` 是否有定义或是否为零
+    pub fn simplify(&self) -> Expr {
+        let mut current = self.without_spans();
+        // 每一轮都会让树变小或保持不变，设置上限只是为了保险
+        for _ in 0..16 {
+            let next = simplify_once(&current);
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// 只在必要时加括号的输出形式
+    pub fn pretty(&self) -> Pretty<'_> {
+        Pretty(self)
+    }
+}
+
+/// 对每个子节点应用 `f`，节点本身的种类不变（`Spanned` 被去掉）
+fn map_children(expr: &Expr, f: impl Fn(&Expr) -> Expr) -> Expr {
+    let b = |e: &Expr| Box::new(f(e));
+    match expr {
+        Expr::Number(n) => Expr::Number(*n),
+        Expr::Var(name) => Expr::Var(name.clone()),
+        Expr::Neg(inner) => Expr::Neg(b(inner)),
+        Expr::Add(l, r) => Expr::Add(b(l), b(r)),
+        Expr::Subtract(l, r) => Expr::Subtract(b(l), b(r)),
+        Expr::Multiply(l, r) => Expr::Multiply(b(l), b(r)),
+        Expr::Divide(l, r) => Expr::Divide(b(l), b(r)),
+        Expr::Pow(l, r) => Expr::Pow(b(l), b(r)),
+        Expr::Call(name, args) => Expr::Call(name.clone(), args.iter().map(&f).collect()),
+        Expr::Spanned(_, inner) => f(inner),
+    }
+}
+
+// 构造节点的简写，让求导法则读起来接近数学公式
+
+fn neg(e: Expr) -> Expr {
+    Expr::Neg(Box::new(e))
+}
+
+fn add(l: Expr, r: Expr) -> Expr {
+    Expr::Add(Box::new(l), Box::new(r))
+}
+
+fn sub(l: Expr, r: Expr) -> Expr {
+    Expr::Subtract(Box::new(l), Box::new(r))
+}
+
+fn mul(l: Expr, r: Expr) -> Expr {
+    Expr::Multiply(Box::new(l), Box::new(r))
+}
+
+fn div(l: Expr, r: Expr) -> Expr {
+    Expr::Divide(Box::new(l), Box::new(r))
+}
+
+fn pow(l: Expr, r: Expr) -> Expr {
+    Expr::Pow(Box::new(l), Box::new(r))
+}
+
+fn ln(e: Expr) -> Expr {
+    Expr::Call(String::from("ln"), vec![e])
+}
+
+/// 一轮自底向上的化简
+fn simplify_once(expr: &Expr) -> Expr {
+    let expr = map_children(expr, simplify_once);
+
+    // 常量折叠：子节点都是数字时直接求值（求值出错则保留原样，留到运行时报告）
+    if let Some(value) = fold_constant(&expr) {
+        return Expr::Number(value);
+    }
+
+    match expr {
+        Expr::Add(..) | Expr::Subtract(..) | Expr::Neg(_) => simplify_sum(&expr),
+        Expr::Multiply(..) | Expr::Divide(..) => simplify_product(&expr),
+        Expr::Pow(base, exponent) => match (*base, *exponent) {
+            (_, Expr::Number(0.0)) => Expr::Number(1.0),
+            (base, Expr::Number(1.0)) => base,
+            (Expr::Number(1.0), _) => Expr::Number(1.0),
+            (base, exponent) => pow(base, exponent),
+        },
+        other => other,
+    }
+}
+
+fn fold_constant(expr: &Expr) -> Option<f64> {
+    let all_numbers = match expr {
+        Expr::Number(_) | Expr::Var(_) | Expr::Spanned(..) => return None,
+        Expr::Neg(inner) => matches!(**inner, Expr::Number(_)),
+        Expr::Add(l, r)
+        | Expr::Subtract(l, r)
+        | Expr::Multiply(l, r)
+        | Expr::Divide(l, r)
+        | Expr::Pow(l, r) => matches!(**l, Expr::Number(_)) && matches!(**r, Expr::Number(_)),
+        Expr::Call(_, args) => args.iter().all(|arg| matches!(arg, Expr::Number(_))),
+    };
+    if all_numbers { expr.eval().ok() } else { None }
+}
+
+/// 乘积的标准形式：数字系数 × Π(底数 ^ 指数)
+///
+/// 因子按底数的输出文本排序，`y * x` 和 `x * y` 得到相同的标准形式
+type Factors = Vec<(Expr, f64)>;
+
+/// 和式化简：把 `+`、`-`、负号展开成 (项, 系数) 的列表，合并同类项
+///
+/// `2 * x + y - x + 3 - 1` → 项 [(x, 1), (y, 1)]，常数 2 → `x + y + 2`
+fn simplify_sum(expr: &Expr) -> Expr {
+    let mut terms: Vec<(Factors, f64)> = Vec::new();
+    let mut constant = 0.0;
+    collect_sum(expr, 1.0, &mut terms, &mut constant);
+
+    let mut result: Option<Expr> = None;
+    let parts = terms.into_iter().chain(std::iter::once((Vec::new(), constant)));
+
+    for (factors, coef) in parts {
+        if coef == 0.0 {
+            continue;
+        }
+        result = Some(match result {
+            // 第一项直接带符号：-2 * x 而不是 -(2 * x)
+            None => build_product(coef, factors),
+            // 后面的项用 + / - 表达符号
+            Some(acc) if coef < 0.0 => sub(acc, build_product(-coef, factors)),
+            Some(acc) => add(acc, build_product(coef, factors)),
+        });
+    }
+
+    result.unwrap_or(Expr::Number(0.0))
+}
+
+fn collect_sum(expr: &Expr, sign: f64, terms: &mut Vec<(Factors, f64)>, constant: &mut f64) {
+    match expr {
+        Expr::Add(l, r) => {
+            collect_sum(l, sign, terms, constant);
+            collect_sum(r, sign, terms, constant);
+        }
+        Expr::Subtract(l, r) => {
+            collect_sum(l, sign, terms, constant);
+            collect_sum(r, -sign, terms, constant);
+        }
+        Expr::Neg(inner) => collect_sum(inner, -sign, terms, constant),
+        Expr::Number(n) => *constant += sign * n,
+        // 其他的项都看作乘积：3 * x 是系数为 3 的项 x
+        _ => {
+            let (coef, factors) = split_product(expr);
+            add_term(terms, factors, sign * coef);
+        }
+    }
+}
+
+/// 相同的项合并系数（或指数），新项按出现顺序追加
+fn add_term<T: PartialEq>(terms: &mut Vec<(T, f64)>, term: T, amount: f64) {
+    match terms.iter_mut().find(|(t, _)| *t == term) {
+        Some((_, total)) => *total += amount,
+        None => terms.push((term, amount)),
+    }
+}
+
+/// 积式化简：合并相同的底数
+///
+/// `x * 2 * x / x ^ 3` → 系数 2，因子 [(x, -1)] → `2 / x`
+fn simplify_product(expr: &Expr) -> Expr {
+    let (coef, factors) = split_product(expr);
+    build_product(coef, factors)
+}
+
+/// 把乘积展开成标准形式
+fn split_product(expr: &Expr) -> (f64, Factors) {
+    let mut coef = 1.0;
+    let mut factors = Vec::new();
+    collect_product(expr, 1.0, &mut coef, &mut factors);
+
+    // x / x：指数抵消为 0 的因子去掉
+    factors.retain(|(_, exponent)| *exponent != 0.0);
+    factors.sort_by_cached_key(|(base, _)| base.pretty().to_string());
+    (coef, factors)
+}
+
+fn collect_product(expr: &Expr, sign: f64, coef: &mut f64, factors: &mut Factors) {
+    match expr {
+        Expr::Multiply(l, r) => {
+            collect_product(l, sign, coef, factors);
+            collect_product(r, sign, coef, factors);
+        }
+        Expr::Divide(l, r) => {
+            collect_product(l, sign, coef, factors);
+            collect_product(r, -sign, coef, factors);
+        }
+        Expr::Neg(inner) => {
+            *coef = -*coef;
+            collect_product(inner, sign, coef, factors);
+        }
+        // 除以 0 不能并进系数，保留为因子，求值时照常报错
+        Expr::Number(n) if sign > 0.0 || *n != 0.0 => *coef *= n.powf(sign),
+        Expr::Pow(base, exponent) if matches!(**exponent, Expr::Number(_)) => {
+            let Expr::Number(k) = **exponent else { unreachable!() };
+            add_term(factors, (**base).clone(), sign * k);
+        }
+        _ => add_term(factors, expr.clone(), sign),
+    }
+}
+
+/// 从标准形式重建表达式：系数在最前，正指数放分子，负指数放分母
+fn build_product(coef: f64, factors: Factors) -> Expr {
+    if coef == 0.0 {
+        return Expr::Number(0.0);
+    }
+
+    let power = |base: Expr, exponent: f64| {
+        if exponent == 1.0 {
+            base
+        } else {
+            pow(base, Expr::Number(exponent))
+        }
+    };
+    // 左结合地连乘，输出时不需要括号：2 * x * y
+    let mut numerator = if coef.abs() == 1.0 { None } else { Some(Expr::Number(coef)) };
+    let mut denominator: Option<Expr> = None;
+    for (base, exponent) in factors {
+        let (side, exponent) = if exponent > 0.0 {
+            (&mut numerator, exponent)
+        } else {
+            (&mut denominator, -exponent)
+        };
+        let factor = power(base, exponent);
+        *side = Some(match side.take() {
+            Some(acc) => mul(acc, factor),
+            None => factor,
+        });
+    }
+
+    let numerator = match numerator {
+        None => Expr::Number(coef),
+        Some(n) if coef == -1.0 => neg(n),
+        Some(n) => n,
+    };
+    match denominator {
+        Some(d) => div(numerator, d),
+        None => numerator,
+    }
+}
+
+/// 最少括号的输出形式，由 `Expr::pretty` 创建
+///
+/// 只在去掉括号会改变树的结构时才加括号：
+/// `(1 + 2) * 3`、`1 - (2 - 3)`、`(-2) ^ 2`、`(2 ^ 3) ^ 2`
+pub struct Pretty<'a>(&'a Expr);
+
+/// 绑定强度：数字越大结合越紧
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Add(..) | Expr::Subtract(..) => 1,
+        Expr::Multiply(..) | Expr::Divide(..) => 2,
+        // 负数字面量打印出来带负号，和一元负号一样对待
+        Expr::Neg(_) => 3,
+        Expr::Number(n) if *n < 0.0 => 3,
+        Expr::Pow(..) => 4,
+        Expr::Spanned(_, inner) => precedence(inner),
+        Expr::Number(_) | Expr::Var(_) | Expr::Call(..) => 5,
+    }
+}
+
+impl Pretty<'_> {
+    /// 输出子表达式，绑定强度低于 `min` 时加括号
+    fn child(&self, f: &mut fmt::Formatter, expr: &Expr, min: u8) -> fmt::Result {
+        if precedence(expr) < min {
+            write!(f, "({})", Pretty(expr))
+        } else {
+            write!(f, "{}", Pretty(expr))
+        }
+    }
+
+    fn binary(&self, f: &mut fmt::Formatter, l: &Expr, op: &str, r: &Expr) -> fmt::Result {
+        let p = precedence(self.0);
+        // 左结合：左边同级不加括号，右边同级要加；乘方右结合，正好相反
+        let (left_min, right_min) = if op == "^" { (p + 1, p) } else { (p, p + 1) };
+        self.child(f, l, left_min)?;
+        write!(f, " {} ", op)?;
+        self.child(f, r, right_min)
+    }
+}
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Neg(inner) => {
+                write!(f, "-")?;
+                self.child(f, inner, 3)
+            }
+            Expr::Add(l, r) => self.binary(f, l, "+", r),
+            Expr::Subtract(l, r) => self.binary(f, l, "-", r),
+            Expr::Multiply(l, r) => self.binary(f, l, "*", r),
+            Expr::Divide(l, r) => self.binary(f, l, "/", r),
+            Expr::Pow(l, r) => self.binary(f, l, "^", r),
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Pretty(arg))?;
+                }
+                write!(f, ")")
+            }
+            Expr::Spanned(_, inner) => write!(f, "{}", Pretty(inner)),
+        }
+    }
+}
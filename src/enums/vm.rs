@@ -0,0 +1,254 @@
+//! # 字节码编译器与栈式虚拟机
+//!
+//! `Expr::eval` 是树遍历解释器：每次求值都要沿着 `Box` 递归访问每个节点。
+//! 这里把 `Expr` 先编译成扁平的指令序列（`Chunk`），再在一个栈式虚拟机上执行：
+//!
+//! - 操作数压栈，运算指令从栈顶弹出操作数，再把结果压回去
+//! - 变量名在编译时换成槽位编号，运行时只需按下标读取
+//! - 每条指令记录它来自源码的哪个位置，报错的位置与 `eval` 完全一致
+//! - `if(c, a, b)` 编译成条件跳转，只执行选中的分支，和 `eval` 一样惰性
+//!
+//! `disassemble` 把指令序列打印成人类可读的形式（反汇编）。
+
+use std::fmt::Write;
+
+use super::expr::{Env, Expr, ExprError, Span, call_builtin, checked_div, checked_pow, is_conditional};
+
+/// 虚拟机指令
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instr {
+    /// 把常量压栈
+    Push(f64),
+    /// 把变量槽位中的值压栈
+    Load(usize),
+    /// 栈顶取负
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    /// 弹出 `argc` 个参数，调用 `functions[function]`，把结果压栈
+    Call { function: usize, argc: usize },
+    /// 跳到指定偏移的指令
+    Jump(usize),
+    /// 弹出栈顶，为 0 时跳到指定偏移的指令，否则继续执行下一条
+    JumpIfZero(usize),
+}
+
+/// 编译结果：指令序列和它引用的名字表
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    code: Vec<Instr>,
+    /// 每条指令所在的最内层源码区间，与 `code` 一一对应
+    spans: Vec<Option<Span>>,
+    /// 变量槽位 → 变量名
+    variables: Vec<String>,
+    /// 函数编号 → 函数名
+    functions: Vec<String>,
+}
+
+impl Chunk {
+    /// 指令序列
+    pub fn code(&self) -> &[Instr] {
+        &self.code
+    }
+
+    /// 反汇编：每行一条指令，格式为 `偏移  指令  操作数  ; 注释`
+    pub fn disassemble(&self) -> String {
+        let mut out = String::new();
+        for (offset, instr) in self.code.iter().enumerate() {
+            let text = match *instr {
+                Instr::Push(n) => format!("PUSH    {}", n),
+                Instr::Load(slot) => format!("LOAD    {:<10}; {}", slot, self.variables[slot]),
+                Instr::Neg => String::from("NEG"),
+                Instr::Add => String::from("ADD"),
+                Instr::Sub => String::from("SUB"),
+                Instr::Mul => String::from("MUL"),
+                Instr::Div => String::from("DIV"),
+                Instr::Pow => String::from("POW"),
+                Instr::Call { function, argc } => {
+                    format!("CALL    {:<10}; {}/{}", function, self.functions[function], argc)
+                }
+                Instr::Jump(target) => format!("JUMP    {:04}", target),
+                Instr::JumpIfZero(target) => format!("JZ      {:04}", target),
+            };
+            let mut line = format!("{:04}  {:<30}", offset, text);
+            if let Some(span) = self.spans[offset] {
+                let _ = write!(line, "@ {}..{}", span.start, span.end);
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// 把表达式树编译成字节码
+pub fn compile(expr: &Expr) -> Chunk {
+    let mut chunk = Chunk::default();
+    emit(&mut chunk, expr, None);
+    chunk
+}
+
+/// 后序遍历：先生成操作数的指令，再生成运算本身的指令
+fn emit(chunk: &mut Chunk, expr: &Expr, span: Option<Span>) {
+    let instr = match expr {
+        Expr::Number(n) => Instr::Push(*n),
+        Expr::Var(name) => Instr::Load(intern(&mut chunk.variables, name)),
+        Expr::Neg(inner) => {
+            emit(chunk, inner, span);
+            Instr::Neg
+        }
+        Expr::Add(left, right)
+        | Expr::Subtract(left, right)
+        | Expr::Multiply(left, right)
+        | Expr::Divide(left, right)
+        | Expr::Pow(left, right) => {
+            emit(chunk, left, span);
+            emit(chunk, right, span);
+            match expr {
+                Expr::Add(..) => Instr::Add,
+                Expr::Subtract(..) => Instr::Sub,
+                Expr::Multiply(..) => Instr::Mul,
+                Expr::Divide(..) => Instr::Div,
+                _ => Instr::Pow,
+            }
+        }
+        // c; JZ else; a; JUMP end; else: b; end:
+        Expr::Call(name, args) if is_conditional(name, args.len()) => {
+            emit(chunk, &args[0], span);
+            let jump_to_else = push(chunk, Instr::JumpIfZero(0), span);
+            emit(chunk, &args[1], span);
+            let jump_to_end = push(chunk, Instr::Jump(0), span);
+            chunk.code[jump_to_else] = Instr::JumpIfZero(chunk.code.len());
+            emit(chunk, &args[2], span);
+            chunk.code[jump_to_end] = Instr::Jump(chunk.code.len());
+            return;
+        }
+        Expr::Call(name, args) => {
+            for arg in args {
+                emit(chunk, arg, span);
+            }
+            Instr::Call {
+                function: intern(&mut chunk.functions, name),
+                argc: args.len(),
+            }
+        }
+        // Spanned 不生成指令，只改变内部指令记录的位置
+        Expr::Spanned(inner_span, inner) => {
+            emit(chunk, inner, Some(*inner_span));
+            return;
+        }
+    };
+    push(chunk, instr, span);
+}
+
+/// 追加一条指令，返回它的偏移，跳转指令的目标之后再回填
+fn push(chunk: &mut Chunk, instr: Instr, span: Option<Span>) -> usize {
+    chunk.code.push(instr);
+    chunk.spans.push(span);
+    chunk.code.len() - 1
+}
+
+/// 名字表去重：同一个名字只占一个编号
+fn intern(names: &mut Vec<String>, name: &str) -> usize {
+    match names.iter().position(|n| n == name) {
+        Some(index) => index,
+        None => {
+            names.push(name.to_string());
+            names.len() - 1
+        }
+    }
+}
+
+/// 栈式虚拟机
+///
+/// 栈和槽位在多次 `run` 之间复用，反复求值时不会重新分配内存
+#[derive(Debug, Default)]
+pub struct Vm {
+    stack: Vec<f64>,
+    slots: Vec<Option<f64>>,
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在给定的变量环境中执行字节码
+    pub fn run(&mut self, chunk: &Chunk, env: &Env) -> Result<f64, ExprError> {
+        // 按槽位取出变量，未定义的变量等到执行 Load 时才报错，
+        // 这样错误的先后顺序与树遍历求值一致
+        self.slots.clear();
+        self.slots
+            .extend(chunk.variables.iter().map(|name| env.get(name).copied()));
+        self.stack.clear();
+
+        let mut pc = 0;
+        while let Some(&instr) = chunk.code.get(pc) {
+            pc = match instr {
+                Instr::Jump(target) => target,
+                Instr::JumpIfZero(target) if self.pop() == 0.0 => target,
+                Instr::JumpIfZero(_) => pc + 1,
+                _ => {
+                    self.step(chunk, instr).map_err(|e| match chunk.spans[pc] {
+                        Some(span) => e.or_at(span),
+                        None => e,
+                    })?;
+                    pc + 1
+                }
+            };
+        }
+
+        Ok(self.stack.pop().expect("编译器保证结束时栈上恰好剩一个值"))
+    }
+
+    /// 执行一条不是跳转的指令
+    fn step(&mut self, chunk: &Chunk, instr: Instr) -> Result<(), ExprError> {
+        let value = match instr {
+            Instr::Push(n) => n,
+            Instr::Load(slot) => match self.slots[slot] {
+                Some(value) => value,
+                None => {
+                    return Err(ExprError::UndefinedVariable {
+                        name: chunk.variables[slot].clone(),
+                        span: None,
+                    })
+                }
+            },
+            Instr::Neg => -self.pop(),
+            Instr::Call { function, argc } => {
+                // 参数就是栈顶的 argc 个值，直接借用这一段，不需要额外分配
+                let start = self.stack.len() - argc;
+                let result = call_builtin(&chunk.functions[function], &self.stack[start..])?;
+                self.stack.truncate(start);
+                result
+            }
+            Instr::Add | Instr::Sub | Instr::Mul | Instr::Div | Instr::Pow => {
+                // 右操作数后入栈，所以先弹出
+                let right = self.pop();
+                let left = self.pop();
+                match instr {
+                    Instr::Add => left + right,
+                    Instr::Sub => left - right,
+                    Instr::Mul => left * right,
+                    Instr::Div => checked_div(left, right)?,
+                    _ => checked_pow(left, right)?,
+                }
+            }
+            Instr::Jump(_) | Instr::JumpIfZero(_) => unreachable!("跳转指令在 run 中处理"),
+        };
+
+        // 和 eval 一样，任何一步产生 inf 都视为溢出
+        if value.is_infinite() {
+            return Err(ExprError::Overflow { span: None });
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> f64 {
+        self.stack.pop().expect("编译器保证操作数已经入栈")
+    }
+}
@@ -23,6 +23,21 @@ use thiserror::Error;
 // Result 是 anyhow 的结果类型，重命名为 AnyhowResult 避免与标准库的 Result 冲突
 use anyhow::{Context, Result as AnyhowResult};
 
+// 配置文件解析器：INI / TOML 子集，错误带行号和列号（示例 14、17）
+pub mod config;
+
+// 带源码片段的诊断信息（示例 18-19），渲染器在顶层的 diagnostic 模块
+use crate::diagnostic;
+
+// 声明式校验：可组合的规则，收集所有字段错误（示例 20-21）
+pub mod validate;
+
+// 重试、退避和断路器，时间通过可替换的时钟（示例 22-23）
+pub mod retry;
+
+// 错误码、错误链的文本和 JSON Lines 报告（示例 24-25）
+pub mod report;
+
 /// 示例 1: panic! 宏基础
 ///
 /// panic! 用于不可恢复的错误，会立即终止程序
@@ -347,6 +362,25 @@ pub fn thiserror_example() {
         NotFound,
     }
 
+    // 给每个变体一个稳定的错误码，描述改了也不影响日志检索（见示例 24）
+    impl report::ErrorCode for DataError {
+        fn code(&self) -> &'static str {
+            match self {
+                DataError::Io(_) => "DAT001",
+                DataError::Parse(_) => "DAT002",
+                DataError::Validation(_) => "DAT003",
+                DataError::NotFound => "DAT004",
+            }
+        }
+
+        fn severity(&self) -> diagnostic::Severity {
+            match self {
+                DataError::NotFound => diagnostic::Severity::Warning,
+                _ => diagnostic::Severity::Error,
+            }
+        }
+    }
+
     // 使用自定义错误类型的函数
     fn process_data(valid: bool) -> Result<i32, DataError> {
         if !valid {
@@ -365,6 +399,12 @@ pub fn thiserror_example() {
     println!("有效数据: {:?}", process_data(true));   // Ok(42)
     println!("无效数据: {:?}", process_data(false));  // Err(Validation("数据无效"))
 
+    use report::ErrorCode;
+    if let Err(e) = process_data(false) {
+        println!("错误码: {}，文档: {}", e.code(), e.help_url());  // DAT003
+    }
+    println!("NotFound 的严重程度: {:?}", DataError::NotFound.severity());
+
     println!("thiserror 自动实现 Display 和 Error trait");
     println!("#[from] 自动实现错误转换");
 }
@@ -483,6 +523,24 @@ pub fn error_composition() {
         Business(String),
     }
 
+    use report::ErrorCode;
+    impl ErrorCode for AppError {
+        fn code(&self) -> &'static str {
+            match self {
+                AppError::Database(_) => "APP001",
+                AppError::Network(_) => "APP002",
+                AppError::Business(_) => "APP003",
+            }
+        }
+
+        fn severity(&self) -> diagnostic::Severity {
+            match self {
+                AppError::Business(_) => diagnostic::Severity::Warning,
+                _ => diagnostic::Severity::Error,
+            }
+        }
+    }
+
     fn validate_user(age: i32) -> Result<(), AppError> {
         if age < 18 {
             return Err(AppError::Business(String::from("年龄必须大于等于 18")));
@@ -506,7 +564,7 @@ pub fn error_composition() {
     println!("保存用户（年龄 15）:");
     match save_user(15) {
         Ok(_) => println!("  成功"),
-        Err(e) => println!("  失败: {}", e),
+        Err(e) => println!("  失败 [{}]: {}", e.code(), e),
     }
 
     println!("组合不同类型的错误到一个枚举");
@@ -612,69 +670,137 @@ pub fn multiple_errors_strategy() {
 /// 示例 14: 实际应用 - 配置文件解析
 ///
 /// 这个示例展示如何使用 anyhow 解析配置文件
-/// 演示了如何为每个可能失败的步骤添加上下文信息
+/// 解析器本身返回带行号和列号的 ConfigError，调用方再用 .context() 说明是哪一步失败
 pub fn practical_config_parser() -> AnyhowResult<()> {
     println!("\n=== 示例 14: 实际应用 - 配置文件解析 ===");
 
-    // 定义配置结构体
+    use config::{Config, ConfigError, FromConfig, Table};
+
+    // 定义配置结构体，每个段对应一个结构体
+    #[derive(Debug)]
+    struct ServerConfig {
+        host: String,      // 主机地址
+        port: u16,         // 端口号（0-65535）
+        timeout: u64,      // 超时时间（秒），可省略
+        tags: Vec<String>, // 标签，可省略
+    }
+
+    // FromConfig 描述如何从一个段构造结构体
+    // get 缺少配置项时报错，get_or 缺少时使用默认值
+    impl FromConfig for ServerConfig {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            let timeout = table.get_or("timeout", 30)?;
+            if timeout == 0 {
+                // 类型正确但取值不合理，同样指出位置
+                return Err(table.invalid("timeout", "必须大于 0"));
+            }
+            Ok(ServerConfig {
+                host: table.get("host")?,
+                port: table.get("port")?,
+                timeout,
+                tags: table.get_or("tags", Vec::new())?,
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct DatabaseConfig {
+        url: String,
+        pool_size: u64,
+        read_only: bool,
+    }
+
+    impl FromConfig for DatabaseConfig {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            Ok(DatabaseConfig {
+                url: table.get("url")?,
+                pool_size: table.get_or("pool_size", 4)?,
+                read_only: table.get_or("read_only", false)?,
+            })
+        }
+    }
+
+    // 顶层：普通的键加上两个段，段也用 get 读取
     #[derive(Debug)]
-    struct Config {
-        host: String,   // 主机地址
-        port: u16,      // 端口号（0-65535）
-        timeout: u64,   // 超时时间（秒）
-    }
-
-    // 解析配置文件内容
-    // 配置格式：每行一个配置项
-    // 第 1 行：host
-    // 第 2 行：port
-    // 第 3 行：timeout
-    fn parse_config(content: &str) -> AnyhowResult<Config> {
-        // 将内容按行分割
-        let lines: Vec<&str> = content.lines().collect();
-
-        // 解析 host
-        // .get(0) 返回 Option<&&str>
-        // .context() 为 None 的情况添加错误信息
-        // ? 将 None 转换为 Err 并返回
-        let host = lines.get(0)
-            .context("缺少 host 配置")?
-            .trim()        // 去除首尾空白
-            .to_string();  // 转换为 String
-
-        // 解析 port
-        // 需要两次 .context()：
-        // 1. 第一次处理 get(1) 可能返回 None
-        // 2. 第二次处理 parse() 可能失败
-        let port = lines.get(1)
-            .context("缺少 port 配置")?
-            .trim()
-            .parse::<u16>()  // 解析为 u16，可能失败
-            .context("port 必须是有效的端口号")?;
-
-        // 解析 timeout
-        let timeout = lines.get(2)
-            .context("缺少 timeout 配置")?
-            .trim()
-            .parse::<u64>()
-            .context("timeout 必须是有效的数字")?;
-
-        // 构造并返回 Config
-        Ok(Config { host, port, timeout })
+    struct AppConfig {
+        name: String,
+        debug: bool,
+        server: ServerConfig,
+        database: DatabaseConfig,
+    }
+
+    impl FromConfig for AppConfig {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            Ok(AppConfig {
+                name: table.get("name")?,
+                debug: table.get_or("debug", false)?,
+                server: table.get("server")?,
+                database: table.get("database")?,
+            })
+        }
+    }
+
+    // 两步都可能失败，分别添加上下文
+    fn load(content: &str) -> AnyhowResult<AppConfig> {
+        let config = Config::parse(content).context("配置文件格式错误")?;
+        let app = config.deserialize::<AppConfig>().context("配置内容无效")?;
+        Ok(app)
     }
 
     // 测试有效的配置
-    let valid_config = "localhost\n8080\n30";
-    match parse_config(valid_config) {
-        Ok(config) => println!("配置解析成功: {:?}", config),
-        Err(e) => println!("配置解析失败: {}", e),
+    let valid_config = r#"
+# 应用配置
+name = "cargo-learn"
+debug = true
+
+[server]
+host = "localhost"
+port = 8080
+tags = ["web", "api"]   # timeout 省略，使用默认值
+
+; INI 风格的注释也可以
+[database]
+url = "postgres://localhost/learn"
+pool_size = 1_6
+"#;
+    let config = Config::parse(valid_config)?;
+    println!("解析结果（按出现顺序）:");
+    for (key, value) in config.root().iter() {
+        match &value.value {
+            config::Value::Table(table) => {
+                println!("  [{}] （{}）", table.name(), value.pos);
+                for (key, value) in table.iter() {
+                    println!("    {} = {}", key, value.value);
+                }
+            }
+            other => println!("  {} = {}", key, other),
+        }
+    }
+    match load(valid_config) {
+        Ok(app) => {
+            println!("配置解析成功: {}（debug = {}）", app.name, app.debug);
+            let server = &app.server;
+            println!("  服务: {}:{}，超时 {} 秒，标签 {:?}", server.host, server.port, server.timeout, server.tags);
+            let db = &app.database;
+            println!("  数据库: {}，连接池 {}，只读 {}", db.url, db.pool_size, db.read_only);
+        }
+        Err(e) => println!("配置解析失败: {:#}", e),
     }
 
-    // 测试无效的配置（port 不是数字）
-    let invalid_config = "localhost\nabc\n30";
-    match parse_config(invalid_config) {
-        Ok(config) => println!("配置解析成功: {:?}", config),
-        Err(e) => println!("配置解析失败: {}", e),  // 会显示 "port 必须是有效的端口号"
+    // 测试无效的配置：格式错误和内容错误都会指出行号和列号
+    let invalid_configs = [
+        "name = \"demo\"\n[server]\nhost = localhost\n",
+        "name = \"demo\"\n[server]\nhost = \"localhost\"\nport = \"abc\"\n[database]\nurl = \"x\"\n",
+        "name = \"demo\"\n[server]\nhost = \"localhost\"\nport = 70000\n[database]\nurl = \"x\"\n",
+        "name = \"demo\"\n[server]\nhost = \"localhost\"\nport = 80\ntimeout = 0\n[database]\nurl = \"x\"\n",
+        "name = \"demo\"\n[server]\nhost = \"localhost\"\nport = 80\n",
+    ];
+    for content in invalid_configs {
+        match load(content) {
+            Ok(app) => println!("配置解析成功: {:?}", app),
+            // {:#} 打印完整的错误链：上下文: 原始错误
+            Err(e) => println!("配置解析失败: {:#}", e),
+        }
     }
 
     Ok(())
@@ -753,6 +879,8 @@ pub fn practical_user_validation() {
         Ok(_) => println!("  验证通过"),
         Err(e) => println!("  验证失败: {}", e),
     }
+
+    println!("\n注: ? 只能报告第一个错误，一次报告所有字段的做法见示例 20-21");
 }
 
 /// 示例 16: 实际应用 - 链式错误处理
@@ -819,9 +947,973 @@ pub fn practical_error_chain() -> AnyhowResult<()> {
         }
     }
 
+    println!("\n注: 每个步骤只执行一次，失败后按策略重试的做法见示例 22-23");
+
     Ok(())
 }
 
+/// 示例 17: 配置文件解析 - 每种错误
+///
+/// ConfigError 的每个变体都构造一次，检查错误位置
+pub fn config_parser_errors() {
+    println!("\n=== 示例 17: 配置文件解析 - 每种错误 ===");
+
+    use config::{Config, ConfigError, FromConfig, Table};
+    use std::collections::BTreeSet;
+
+    struct Server {
+        host: String,
+        port: u16,
+        workers: u64,
+    }
+
+    impl FromConfig for Server {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            let workers = table.get_or("workers", 1)?;
+            if workers > 64 {
+                return Err(table.invalid("workers", "最多 64 个"));
+            }
+            Ok(Server {
+                host: table.get("host")?,
+                port: table.get("port")?,
+                workers,
+            })
+        }
+    }
+
+    fn load(source: &str) -> Result<Server, ConfigError> {
+        Config::parse(source)?.root().get("server")
+    }
+
+    // (变体名, 输入, 期望的行号和列号)
+    let cases = [
+        ("UnterminatedSection", "[server\nhost = \"a\"", (1, 1)),
+        ("InvalidSectionName", "[my server]", (1, 2)),
+        ("DuplicateSection", "[server]\nport = 1\n[server]", (3, 2)),
+        ("DuplicateSection", "server = 1\n[server]", (2, 2)),
+        ("ExpectedEquals", "[server]\nhost localhost", (2, 6)),
+        ("EmptyKey", "[server]\n  = 1", (2, 3)),
+        ("InvalidKey", "[server]\nhost.name = \"a\"", (2, 1)),
+        ("DuplicateKey", "[server]\nport = 1\n\nport = 2", (4, 1)),
+        ("MissingValue", "[server]\nport =   # 忘了写", (2, 10)),
+        ("UnterminatedString", "[server]\nhost = \"localhost", (2, 8)),
+        ("InvalidEscape", "[server]\nhost = \"服务器\\q\"", (2, 12)),
+        ("InvalidEscape", "[server]\nhost = \"\\u12\"", (2, 9)),
+        ("InvalidInteger", "[server]\nport = 80_80x", (2, 8)),
+        ("InvalidInteger", "[server]\nport = 99999999999999999999", (2, 8)),
+        ("InvalidValue", "[server]\nhost = localhost", (2, 8)),
+        ("InvalidValue", "[server]\ntags = [1, ,]", (2, 12)),
+        ("UnterminatedArray", "[server]\ntags = [\"a\", \"b\"", (2, 8)),
+        ("UnterminatedArray", "[server]\ntags = [1, 2 # 注释", (2, 8)),
+        ("TrailingCharacters", "[server]\nport = 80 81", (2, 11)),
+        ("TrailingCharacters", "[server] extra", (1, 10)),
+        ("TrailingCharacters", "[server]\ntags = [1 2]", (2, 11)),
+        ("MissingKey", "[server]\nhost = \"a\"", (1, 1)),
+        ("MissingKey", "name = \"demo\"", (1, 1)),
+        ("TypeMismatch", "[server]\nhost = \"a\"\nport = \"80\"", (3, 8)),
+        ("TypeMismatch", "server = 1", (1, 10)),
+        ("OutOfRange", "[server]\nhost = \"a\"\nport = -1", (3, 8)),
+        ("Invalid", "[server]\nhost = \"a\"\nport = 80\nworkers = 100", (4, 11)),
+    ];
+
+    let mut covered = BTreeSet::new();
+    for (variant, source, (line, column)) in cases {
+        let error = match load(source) {
+            Ok(_) => panic!("{:?} 应该失败", source),
+            Err(e) => e,
+        };
+        // Debug 输出以变体名开头
+        let debug = format!("{:?}", error);
+        assert!(debug.starts_with(variant), "{:?}: 期望 {}，得到 {}", source, variant, debug);
+        let pos = error.position();
+        assert_eq!((pos.line, pos.column), (line, column), "{:?}: {}", source, error);
+        covered.insert(variant);
+        println!("  {:<20} {}", variant, error);
+    }
+    assert_eq!(covered.len(), 18, "每个变体至少要有一个用例");
+    println!("✓ {} 个用例覆盖了 ConfigError 的全部 {} 个变体，位置都正确", cases.len(), covered.len());
+
+    // 正确的输入：转义、嵌套数组、中文、注释
+    let source = "[server]\nhost = \"\\u4e3b\\u673a\\t\\\"名\\\"\"  # 注释\nport = +8_080\nmatrix = [[1, 2], [3], []]\n";
+    let config = Config::parse(source).unwrap();
+    let server: Server = config.root().get("server").unwrap();
+    assert_eq!(server.host, "主机\t\"名\"");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, 1);
+    let Some(config::Value::Table(table)) = config.root().get_value("server").map(|v| &v.value) else {
+        unreachable!("server 是一个段");
+    };
+    let matrix: Vec<Vec<i64>> = table.get("matrix").unwrap();
+    assert_eq!(matrix, vec![vec![1, 2], vec![3], vec![]]);
+    assert!(!table.is_empty());
+    println!("✓ 转义 / 数字分隔符 / 嵌套数组: host = {:?}, port = {}, matrix = {:?}", server.host, server.port, matrix);
+
+    println!("\n错误处理:");
+    println!("  - 格式错误在 Config::parse 中发现，内容错误在 FromConfig 中发现");
+    println!("  - 两类错误是同一个 thiserror 枚举，都带有行号和列号");
+    println!("  - 列号按字符计算，中文不会让位置错位");
+}
+
+/// 示例 18: 带源码片段的诊断信息
+///
+/// 配置文件的错误画在源码下面，附上说明和帮助
+pub fn diagnostic_rendering() {
+    println!("\n=== 示例 18: 带源码片段的诊断信息 ===");
+
+    use config::{Config, ConfigError, FromConfig, Table};
+    use diagnostic::{Diagnostic, Label};
+
+    // 配置文件：解析器遇到第一个错误就停止，每次修好一处再看下一处
+    let attempts = [
+        "[server]\nhost = localhost\nport = 8080\n",
+        "[server]\nhost = \"localhost\"\nport = 8080\n\n# 后面又写了一次\nport = 9090\n",
+        "[服务器]\n名称 = \"测试\"\t# 中文键名\n端口 = [80, 443\n",
+    ];
+    for source in attempts {
+        if let Err(e) = Config::parse(source) {
+            println!("{}", e.to_diagnostic(source).render("app.ini", source));
+        }
+    }
+
+    // 内容错误同样可以渲染
+    struct Server {
+        port: u16,
+    }
+
+    impl FromConfig for Server {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            Ok(Server { port: table.get("port")? })
+        }
+    }
+
+    let source = "[server]\nhost = \"localhost\"\nport = \"8080\"\n";
+    match Config::parse(source).and_then(|config| config.root().get::<Server>("server")) {
+        Ok(server) => println!("port = {}", server.port),
+        Err(e) => println!("{}", e.to_diagnostic(source).render("app.ini", source)),
+    }
+    let source = "name = \"demo\"\n";
+    if let Err(e) = Config::parse(source).and_then(|config| config.root().get::<Server>("server")) {
+        println!("{}", e.to_diagnostic(source).render("app.ini", source));
+    }
+
+    // 手工构造：同一行的多个标签、警告级别
+    let source = "let total = price * count + price * 0";
+    let warning = Diagnostic::warning("乘以 0 的结果总是 0")
+        .with_label(Label::primary(28..37, "这一项总是 0"))
+        .with_label(Label::secondary(12..17, "price 在这里也用到了"))
+        .with_label(Label::secondary(20..25, "count 没有参与这一项"))
+        .with_help("删掉 + price * 0");
+    println!("{}", warning.render("<输入>", source));
+
+    println!("诊断的组成:");
+    println!("  - 标题：错误的内容，不重复位置");
+    println!("  - --> 文件名:行:列，指向第一个主标签");
+    println!("  - 源码行和下划线：^ 是主标签，- 是次要标签");
+    println!("  - = 帮助 / = 注：怎样修改");
+    println!("  - 表达式语言的错误也用同一个渲染器，见枚举模块的示例 29");
+}
+
+/// 示例 19: 诊断信息 - 快照检查
+///
+/// 渲染结果和事先确认过的文本逐字比较，格式的任何变化都会被发现
+pub fn diagnostic_snapshots() {
+    println!("\n=== 示例 19: 诊断信息 - 快照检查 ===");
+
+    use config::Config;
+    use diagnostic::{Diagnostic, Label};
+
+    let config_error = |source: &str| Config::parse(source).unwrap_err().to_diagnostic(source).render("app.ini", source);
+
+    let cases = [
+        // 制表符展开成 4 列，中文占 2 列
+        ("制表符和中文", config_error("[server]\n\tname = \"服务器\\q\"\n")),
+        // 两个标签之间只隔一行，直接显示那一行
+        ("相隔一行", config_error("[a]\nx = 1\n[a]\n")),
+        // 隔了很多行时用 ... 省略；行号到两位数，左边的空白跟着变宽
+        ("省略和两位数行号", config_error("[a]\nx = 1\n\n\n\n\n\n\n\nx = 2\n")),
+        (
+            "同一行多个标签",
+            Diagnostic::warning("乘以 0 的结果总是 0")
+                .with_label(Label::primary(28..37, "这一项总是 0"))
+                .with_label(Label::secondary(12..17, "price 在这里也用到了"))
+                .with_label(Label::secondary(20..25, ""))
+                .render("<输入>", "let total = price * count + price * 0"),
+        ),
+        // 没有标签时只显示文件名
+        ("没有位置", Diagnostic::error("缺少配置项 server.port").with_note("默认配置里也没有这一项").render("app.ini", "")),
+    ];
+    // 渲染结果的每一行都经过人工确认
+    let expected = [
+        r#"错误: 无效的转义序列 '\q'
+ --> app.ini:2:13
+  |
+2 |     name = "服务器\q"
+  |                   ^^ 无效的转义
+  |
+  = 帮助: 支持的转义有 \" \\ \n \t \r \uXXXX
+"#,
+        r#"错误: 段 [a] 与第 1 行重复
+ --> app.ini:3:2
+  |
+1 | [a]
+  |  - 第一次定义在这里
+2 | x = 1
+3 | [a]
+  |  ^ 又定义了一次
+  |
+  = 帮助: 把两处的配置项合并到同一个段里
+"#,
+        r#"错误: 键 'x' 与第 2 行重复
+  --> app.ini:10:1
+   |
+ 2 | x = 1
+   | - 第一次定义在这里
+...
+10 | x = 2
+   | ^ 又定义了一次
+"#,
+        r#"警告: 乘以 0 的结果总是 0
+ --> <输入>:1:29
+  |
+1 | let total = price * count + price * 0
+  |             -----   -----   ^^^^^^^^^ 这一项总是 0
+  |             |
+  |             price 在这里也用到了
+"#,
+        r#"错误: 缺少配置项 server.port
+ --> app.ini
+  = 注: 默认配置里也没有这一项
+"#,
+    ];
+
+    for ((name, rendered), expected) in cases.iter().zip(expected) {
+        assert_eq!(rendered, expected, "快照 \"{}\" 不一致，实际输出:\n{}", name, rendered);
+    }
+    println!("✓ {} 个快照全部一致", cases.len());
+    print!("{}", cases[0].1);
+
+    println!("\n快照覆盖了:");
+    println!("  - 制表符展开、中文双倍宽度的对齐");
+    println!("  - 两个标签之间隔一行 / 隔多行（...）");
+    println!("  - 行号宽度变化、没有位置的错误");
+    println!("  - 同一行的多个标签，没有说明的标签不占用额外的行");
+}
+
+/// 示例 20: 声明式校验 - 收集所有字段错误
+///
+/// 示例 15 只能报告第一个错误，这里把规则写成数据，一次报告全部
+pub fn validation_framework() {
+    println!("\n=== 示例 20: 声明式校验 - 收集所有字段错误 ===");
+
+    use validate::{Mode, Validate, Validator, email, length, matches, range, required, validate};
+
+    struct Address {
+        city: String,
+        zip: String,
+    }
+
+    impl Validate for Address {
+        fn validate(&self, v: &mut Validator) {
+            v.field("city", self.city.as_str(), &[required()]);
+            v.field(
+                "zip",
+                self.zip.as_str(),
+                &[length(6, 6), matches(|s: &str| s.chars().all(|c| c.is_ascii_digit()), "只能包含数字")],
+            );
+        }
+    }
+
+    struct Contact {
+        name: String,
+        email: String,
+    }
+
+    impl Validate for Contact {
+        fn validate(&self, v: &mut Validator) {
+            v.field("name", self.name.as_str(), &[required(), length(1, 10)]);
+            v.field("email", self.email.as_str(), &[required(), email()]);
+        }
+    }
+
+    struct User {
+        username: String,
+        email: String,
+        age: u8,
+        nickname: Option<String>,
+        password: String,
+        confirm: String,
+        address: Address,
+        tags: Vec<String>,
+        contacts: Vec<Contact>,
+    }
+
+    // 规则集中写在一处，读起来像表单的说明
+    impl Validate for User {
+        fn validate(&self, v: &mut Validator) {
+            v.field("username", self.username.as_str(), &[required(), length(3, 20)])
+                .field("email", self.email.as_str(), &[required(), email()])
+                .field("age", &self.age, &[range(18, 120)])
+                .optional("nickname", self.nickname.as_deref(), &[length(2, 8)])
+                .field("password", self.password.as_str(), &[length(8, 64)])
+                .check("confirm", self.password == self.confirm, "两次输入的密码不一致")
+                .nested("address", &self.address)
+                .field("tags", &self.tags, &[length(0, 3)])
+                .each("tags", &self.tags, &[required(), length(1, 8)])
+                .each_nested("contacts", &self.contacts);
+        }
+    }
+
+    let user = User {
+        username: "ab".to_string(),
+        email: "alice.example.com".to_string(),
+        age: 15,
+        nickname: Some("小".to_string()),
+        password: "secret".to_string(),
+        confirm: "secret!".to_string(),
+        address: Address {
+            city: "  ".to_string(),
+            zip: "10008a".to_string(),
+        },
+        tags: vec!["rust".to_string(), "".to_string()],
+        contacts: vec![
+            Contact {
+                name: "Bob".to_string(),
+                email: "bob@example.com".to_string(),
+            },
+            Contact {
+                name: "".to_string(),
+                email: "carol@".to_string(),
+            },
+        ],
+    };
+
+    println!("Accumulate 模式:");
+    if let Err(report) = validate(&user, Mode::Accumulate) {
+        println!("{}", report);
+    }
+
+    println!("\nFailFast 模式:");
+    if let Err(report) = validate(&user, Mode::FailFast) {
+        println!("{}", report);
+    }
+
+    let fixed = User {
+        username: "alice".to_string(),
+        email: "alice@example.com".to_string(),
+        age: 25,
+        nickname: None,
+        password: "correct horse".to_string(),
+        confirm: "correct horse".to_string(),
+        address: Address {
+            city: "北京".to_string(),
+            zip: "100080".to_string(),
+        },
+        tags: vec!["rust".to_string()],
+        contacts: Vec::new(),
+    };
+    println!("\n修改后: {:?}", validate(&fixed, Mode::Accumulate).map_err(|r| r.len()));
+
+    println!("\n对比示例 15:");
+    println!("  - ? 在第一个错误处返回，用户要反复提交才能看到所有问题");
+    println!("  - 规则是 Rule<T> 值，可以放进数组、复用、组合");
+    println!("  - 错误带有字段路径，前端可以标在对应的输入框上");
+    println!("  - Report 实现了 std::error::Error，可以用 ? 转换成 anyhow::Error");
+}
+
+/// 示例 21: 声明式校验 - 规则检查
+///
+/// 每个组合子的边界情况，以及两种模式的区别
+pub fn validation_checks() {
+    println!("\n=== 示例 21: 声明式校验 - 规则检查 ===");
+
+    use validate::{Mode, Rule, Validate, Validator, Violation, email, length, matches, range, required, validate};
+
+    // required：空白字符串、None、空列表都算没有值
+    assert_eq!(required::<str>().check("  \t"), Err(Violation::Required));
+    assert_eq!(required::<str>().check("x"), Ok(()));
+    assert_eq!(required::<Option<u8>>().check(&None), Err(Violation::Required));
+    assert_eq!(required::<Vec<u8>>().check(&vec![1]), Ok(()));
+
+    // length：按字符计算，两端都包含
+    let rule: Rule<str> = length(2, 4);
+    assert_eq!(rule.check("张三"), Ok(()));
+    assert_eq!(rule.check("张"), Err(Violation::TooShort { min: 2, actual: 1 }));
+    assert_eq!(rule.check("张三李四王"), Err(Violation::TooLong { max: 4, actual: 5 }));
+    assert_eq!(length::<Vec<i32>>(0, 2).check(&vec![1, 2, 3]), Err(Violation::TooLong { max: 2, actual: 3 }));
+
+    // range：两端都包含，错误里带着实际值
+    let rule = range(18u8, 120);
+    assert!(rule.check(&18).is_ok() && rule.check(&120).is_ok());
+    assert_eq!(
+        rule.check(&17).unwrap_err().to_string(),
+        "必须在 18 到 120 之间，实际为 17"
+    );
+    assert!(range(0.0, 1.0).check(&1.5).is_err());
+
+    // matches：任意条件
+    let even = matches(|n: &i32| n % 2 == 0, "必须是偶数");
+    assert_eq!(even.check(&3), Err(Violation::Invalid("必须是偶数".to_string())));
+
+    // email
+    for ok in ["a@b.co", "张三@例子.中国", "first.last+tag@mail.example.com"] {
+        assert_eq!(email().check(ok), Ok(()), "{}", ok);
+    }
+    for bad in ["", "a", "@b.com", "a@", "a@b", "a@b.", "a@.com", "a@@b.com", "a b@c.com"] {
+        assert_eq!(email().check(bad), Err(Violation::InvalidEmail), "{}", bad);
+    }
+    println!("✓ required / length / range / matches / email 的边界情况");
+
+    // 同一个字段遇到第一个失败的规则就停止
+    let mut v = Validator::new(Mode::Accumulate);
+    v.field("name", "", &[required(), length(3, 10)]);
+    let report = v.finish().unwrap_err();
+    assert!(!report.is_empty() && report.len() == 1);
+    assert_eq!(report.get("name"), Some(&Violation::Required));
+    println!("✓ 为空时不再报告长度");
+
+    // 路径和两种模式
+    struct Item {
+        sku: String,
+        qty: u32,
+    }
+
+    impl Validate for Item {
+        fn validate(&self, v: &mut Validator) {
+            v.field("sku", self.sku.as_str(), &[required()]);
+            v.field("qty", &self.qty, &[range(1, 99)]);
+        }
+    }
+
+    struct Order {
+        id: String,
+        items: Vec<Item>,
+        notes: Vec<String>,
+    }
+
+    impl Validate for Order {
+        fn validate(&self, v: &mut Validator) {
+            v.field("id", self.id.as_str(), &[length(4, 4)])
+                .each_nested("items", &self.items)
+                .each("notes", &self.notes, &[length(0, 5)]);
+        }
+    }
+
+    let order = Order {
+        id: "A1".to_string(),
+        items: vec![
+            Item { sku: "X".to_string(), qty: 1 },
+            Item { sku: "".to_string(), qty: 0 },
+        ],
+        notes: vec!["ok".to_string(), "太长太长太长".to_string()],
+    };
+    let all = validate(&order, Mode::Accumulate).unwrap_err();
+    let paths: Vec<&str> = all.errors.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["id", "items[1].sku", "items[1].qty", "notes[1]"]);
+    let first = validate(&order, Mode::FailFast).unwrap_err();
+    assert_eq!(first.errors, all.errors[..1]);
+    println!("✓ Accumulate 按检查顺序收集 {} 个错误，FailFast 只保留第一个", all.len());
+    println!("  路径: {:?}", paths);
+
+    // Report 是一个普通的错误类型
+    fn submit(order: &Order) -> anyhow::Result<()> {
+        validate(order, Mode::Accumulate)?;
+        Ok(())
+    }
+    let error = submit(&order).unwrap_err();
+    assert!(error.downcast_ref::<validate::Report>().is_some());
+    println!("✓ Report 可以通过 ? 转换成 anyhow::Error，再 downcast 回来");
+
+    println!("\n要点:");
+    println!("  - 每个组合子都是返回 Rule<T> 的函数，T 由字段类型推断");
+    println!("  - 字符串的长度按字符计算，和示例 17 的列号一致");
+}
+
+/// 示例 22: 重试、退避和断路器
+///
+/// 示例 16 的步骤失败一次就放弃；对偶尔失败的操作，可以按策略重试
+pub fn retry_and_circuit_breaker() {
+    println!("\n=== 示例 22: 重试、退避和断路器 ===");
+
+    use crate::util::{FakeClock, SystemClock};
+    use retry::{Backoff, CircuitBreaker, RetryPolicy, Retryable, retry};
+    use std::time::{Duration, Instant};
+
+    #[derive(Error, Debug, PartialEq)]
+    enum ServiceError {
+        #[error("请求超时（{0} 毫秒）")]
+        Timeout(u64),
+        #[error("服务暂时不可用")]
+        Unavailable,
+        #[error("找不到资源 '{0}'")]
+        NotFound(String),
+    }
+
+    // 按变体分类：超时和不可用值得重试，找不到资源重试也没用
+    impl Retryable for ServiceError {
+        fn is_retryable(&self) -> bool {
+            matches!(self, ServiceError::Timeout(_) | ServiceError::Unavailable)
+        }
+    }
+
+    let ms = Duration::from_millis;
+    // 前两次超时，第三次成功
+    let flaky = |attempt: u32| {
+        println!("    第 {} 次尝试", attempt);
+        if attempt < 3 { Err(ServiceError::Timeout(500)) } else { Ok("用户数据") }
+    };
+
+    println!("1. 三种退避策略（FakeClock，不真的等待）:");
+    let backoffs = [
+        ("固定", Backoff::Fixed(ms(100))),
+        ("指数", Backoff::Exponential { initial: ms(100), max: ms(1000) }),
+        ("抖动", Backoff::Jittered { initial: ms(100), max: ms(1000) }),
+    ];
+    for (name, backoff) in backoffs {
+        let first: Vec<u128> = backoff.delays(7).take(6).map(|d| d.as_millis()).collect();
+        println!("  {} 退避的前 6 个间隔（毫秒）: {:?}", name, first);
+    }
+
+    let clock = FakeClock::new();
+    let policy = RetryPolicy::new(5, Backoff::Exponential { initial: ms(100), max: ms(1000) });
+    let result = retry(&policy, &clock, flaky);
+    println!("  结果: {:?}，等待了 {:?}", result, clock.sleeps());
+
+    println!("\n2. 不可重试的错误立即返回:");
+    let clock = FakeClock::new();
+    let result: Result<(), _> = retry(&policy, &clock, |attempt| {
+        println!("    第 {} 次尝试", attempt);
+        Err(ServiceError::NotFound("user/42".to_string()))
+    });
+    if let Err(e) = result {
+        println!("  {}: {}", e, std::error::Error::source(&e).map(|s| s.to_string()).unwrap_or_default());
+    }
+
+    println!("\n3. 次数用完:");
+    let clock = FakeClock::new();
+    let policy = RetryPolicy::new(3, Backoff::Fixed(ms(200)));
+    let result: Result<(), _> = retry(&policy, &clock, |_| Err(ServiceError::Unavailable));
+    if let Err(e) = result {
+        println!("  {}（attempts = {}）", e, e.attempts());
+        println!("  最后一次的错误: {}", e.into_inner());
+    }
+
+    println!("\n4. 断路器:");
+    let clock = FakeClock::new();
+    let mut breaker = CircuitBreaker::new(&clock, 3, ms(5000));
+    let call = |breaker: &mut CircuitBreaker<&FakeClock>, service_up: bool| {
+        let result = breaker.call(|| if service_up { Ok("ok") } else { Err(ServiceError::Unavailable) });
+        println!("  [{:>5} ms] {:<36} -> {:?}", clock.elapsed().as_millis(), format!("{:?}", result), breaker.state());
+    };
+    // 连续失败 3 次后打开，第 4 次调用被直接拒绝
+    for _ in 0..4 {
+        call(&mut breaker, false);
+    }
+    // 5 秒后半开，试探失败，重新打开
+    clock.advance(ms(5000));
+    call(&mut breaker, false);
+    // 再过 5 秒，服务恢复，试探成功后关闭
+    clock.advance(ms(5000));
+    call(&mut breaker, true);
+    call(&mut breaker, true);
+
+    println!("\n5. 真实时钟（SystemClock，每次等待 1 毫秒）:");
+    let clock = SystemClock;
+    let started = Instant::now();
+    let policy = RetryPolicy::new(5, Backoff::Fixed(ms(1)));
+    let result = retry(&policy, &clock, |attempt| if attempt < 3 { Err(ServiceError::Unavailable) } else { Ok(attempt) });
+    println!("  结果: {:?}，用时 {:?}", result, started.elapsed());
+
+    println!("\n要点:");
+    println!("  - 错误类型实现 Retryable，按变体决定是否重试");
+    println!("  - 抖动避免大量客户端同时重试，种子固定时结果可以重现");
+    println!("  - 断路器在服务不可用时直接拒绝，给服务恢复的时间");
+    println!("  - 时间都经过 Clock，换成 FakeClock 就不需要真的等待");
+}
+
+/// 示例 23: 重试和断路器 - 用手动时钟检查
+///
+/// 退避间隔、时间上限和断路器的状态变化都可以精确断言，整个示例不会 sleep
+pub fn retry_checks() {
+    println!("\n=== 示例 23: 重试和断路器 - 用手动时钟检查 ===");
+
+    use crate::util::FakeClock;
+    use retry::{Backoff, CircuitBreaker, CircuitError, CircuitState, RetryError, RetryPolicy, Retryable, retry};
+    use std::time::{Duration, Instant};
+
+    #[derive(Error, Debug, PartialEq)]
+    enum NetError {
+        #[error("连接被重置")]
+        Reset,
+        #[error("请求无效")]
+        BadRequest,
+    }
+
+    impl Retryable for NetError {
+        fn is_retryable(&self) -> bool {
+            *self == NetError::Reset
+        }
+    }
+
+    let ms = Duration::from_millis;
+    let real_start = Instant::now();
+
+    // 退避序列
+    let millis = |backoff: &Backoff, seed: u64, n: usize| -> Vec<u64> {
+        backoff.delays(seed).take(n).map(|d| d.as_millis() as u64).collect()
+    };
+    assert_eq!(millis(&Backoff::Fixed(ms(50)), 0, 3), [50, 50, 50]);
+    let exponential = Backoff::Exponential { initial: ms(100), max: ms(1000) };
+    assert_eq!(millis(&exponential, 0, 6), [100, 200, 400, 800, 1000, 1000]);
+    // 第 40 次重试时 2^n 已经溢出 u32，仍然取上限
+    assert_eq!(exponential.delays(0).nth(40), Some(ms(1000)));
+
+    let jittered = Backoff::Jittered { initial: ms(100), max: ms(1000) };
+    let a: Vec<Duration> = jittered.delays(1).take(50).collect();
+    let b: Vec<Duration> = jittered.delays(1).take(50).collect();
+    let c: Vec<Duration> = jittered.delays(2).take(50).collect();
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    for (n, (delay, cap)) in a.iter().zip(exponential.delays(0)).enumerate() {
+        assert!(*delay < cap, "第 {} 个间隔 {:?} 超过了 {:?}", n, delay, cap);
+    }
+    // retry 使用策略里的种子
+    let clock = FakeClock::new();
+    let seeded = RetryPolicy::new(4, jittered.clone()).seed(1);
+    let _: Result<(), _> = retry(&seeded, &clock, |_| Err(NetError::Reset));
+    assert_eq!(clock.sleeps(), a[..3]);
+    println!("✓ 固定 / 指数 / 抖动的间隔；抖动不超过指数退避，同一个种子结果相同");
+
+    // 成功前的每次等待都经过时钟
+    let clock = FakeClock::new();
+    let policy = RetryPolicy::new(5, exponential.clone());
+    let mut calls = 0;
+    let result = retry(&policy, &clock, |attempt| {
+        calls += 1;
+        if attempt < 4 { Err(NetError::Reset) } else { Ok(attempt) }
+    });
+    assert_eq!(result, Ok(4));
+    assert_eq!(calls, 4);
+    assert_eq!(clock.sleeps(), [ms(100), ms(200), ms(400)]);
+    assert_eq!(clock.elapsed(), ms(700));
+    println!("✓ 第 4 次成功，等待了 100 + 200 + 400 毫秒");
+
+    // 不可重试：只调用一次，不等待
+    let clock = FakeClock::new();
+    let result: Result<(), _> = retry(&policy, &clock, |_| Err(NetError::BadRequest));
+    assert_eq!(result, Err(RetryError::Fatal { attempts: 1, source: NetError::BadRequest }));
+    assert!(clock.sleeps().is_empty());
+
+    // 次数用完：最后一次失败后不再等待
+    let clock = FakeClock::new();
+    let result: Result<(), _> = retry(&policy, &clock, |_| Err(NetError::Reset));
+    assert_eq!(result, Err(RetryError::Exhausted { attempts: 5, source: NetError::Reset }));
+    assert_eq!(clock.sleeps().len(), 4);
+    println!("✓ 不可重试的错误立即返回，次数用完后不再多等一次");
+
+    // 时间上限：已用 300 毫秒，下一次要等 400，超过 500 的上限
+    let clock = FakeClock::new();
+    let limited = RetryPolicy::new(10, exponential.clone()).max_elapsed(ms(500));
+    let result: Result<(), _> = retry(&limited, &clock, |_| Err(NetError::Reset));
+    assert_eq!(result.unwrap_err().attempts(), 3);
+    assert_eq!(clock.elapsed(), ms(300));
+
+    // 操作本身耗时也算在内
+    let clock = FakeClock::new();
+    let result: Result<(), _> = retry(&limited, &clock, |_| {
+        clock.advance(ms(150));
+        Err(NetError::Reset)
+    });
+    assert_eq!(result.unwrap_err().attempts(), 2);
+    println!("✓ max_elapsed 把等待和操作本身的耗时都算在内");
+
+    // 断路器的状态变化
+    let clock = FakeClock::new();
+    let mut breaker = CircuitBreaker::new(&clock, 2, ms(1000));
+    let fail = || Err::<(), _>(NetError::Reset);
+    assert_eq!(breaker.call(fail), Err(CircuitError::Inner(NetError::Reset)));
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    // 成功会清零连续失败的次数
+    assert_eq!(breaker.call(|| Ok::<_, NetError>(1)), Ok(1));
+    breaker.call(fail).unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    breaker.call(fail).unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // 打开时不调用操作
+    clock.advance(ms(400));
+    let mut invoked = false;
+    let rejected = breaker.call(|| {
+        invoked = true;
+        Ok::<_, NetError>(())
+    });
+    assert!(!invoked);
+    assert_eq!(rejected, Err(CircuitError::Open { retry_after: ms(600) }));
+    assert_eq!(rejected.unwrap_err().to_string(), "断路器已打开，600 毫秒后再试");
+
+    // 半开时一次失败就重新打开，重新计时
+    clock.advance(ms(600));
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    breaker.call(fail).unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+    clock.advance(ms(999));
+    assert_eq!(breaker.state(), CircuitState::Open);
+    clock.advance(ms(1));
+    assert_eq!(breaker.call(|| Ok::<_, NetError>("恢复")), Ok("恢复"));
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    println!("✓ 关闭 -> 打开 -> 半开 -> 打开 -> 半开 -> 关闭");
+
+    // 组合：重试经过断路器，断路器打开后停止重试
+    let clock = FakeClock::new();
+    let mut breaker = CircuitBreaker::new(&clock, 3, ms(10_000));
+    let mut calls = 0;
+    let result: Result<(), _> = retry(&policy, &clock, |_| {
+        breaker.call(|| {
+            calls += 1;
+            Err(NetError::Reset)
+        })
+    });
+    let error = result.unwrap_err();
+    assert_eq!(error.attempts(), 4);
+    assert!(matches!(error.into_inner(), CircuitError::Open { .. }));
+    assert_eq!(calls, 3);
+    println!("✓ 断路器打开后 CircuitError::Open 不可重试，第 4 次尝试就停止");
+
+    assert!(real_start.elapsed() < ms(100));
+    println!("✓ 所有等待都是拨动手动时钟，实际用时 {:?}", real_start.elapsed());
+
+    println!("\n要点:");
+    println!("  - util::Clock 是一个 trait，&FakeClock 也实现了它，断路器和测试可以共用同一个时钟");
+    println!("  - RetryError 和 CircuitError 都是带 source 的 thiserror 枚举");
+}
+
+/// 示例 24: 错误码和结构化的错误报告
+///
+/// 沿着错误链找到带错误码的那一层，输出给人看的文本和给日志系统的 JSON Lines
+pub fn error_code_reports() {
+    println!("\n=== 示例 24: 错误码和结构化的错误报告 ===");
+
+    use config::{Config, ConfigError, FromConfig, Table};
+    use report::Registry;
+    use validate::{Mode, Validate, Validator, email, length, validate};
+
+    struct Server {
+        port: u16,
+    }
+
+    impl FromConfig for Server {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            Ok(Server { port: table.get("port")? })
+        }
+    }
+
+    fn load_port(source: &str) -> AnyhowResult<u16> {
+        let config = Config::parse(source).context("app.ini 的格式有误")?;
+        let server: Server = config.root().get("server").context("读取 [server] 段失败")?;
+        Ok(server.port)
+    }
+
+    fn start(source: &str) -> AnyhowResult<()> {
+        let port = load_port(source).context("加载配置失败")?;
+        println!("  监听端口 {}", port);
+        Ok(())
+    }
+
+    struct Signup {
+        name: String,
+        email: String,
+    }
+
+    impl Validate for Signup {
+        fn validate(&self, v: &mut Validator) {
+            v.field("name", self.name.as_str(), &[length(2, 10)])
+                .field("email", self.email.as_str(), &[email()]);
+        }
+    }
+
+    fn sign_up(form: &Signup) -> AnyhowResult<()> {
+        validate(form, Mode::Accumulate).context("注册失败")?;
+        Ok(())
+    }
+
+    // 只有登记过的类型才能从 &dyn Error 中认出来
+    let registry = Registry::new().register::<ConfigError>().register::<validate::Report>();
+
+    let failures = [
+        start("[server]\nport = \"8080\"\n").unwrap_err(),
+        sign_up(&Signup {
+            name: "张".to_string(),
+            email: "zhang".to_string(),
+        })
+        .unwrap_err(),
+        anyhow::anyhow!("磁盘已满").context("写入日志失败"),
+    ];
+
+    println!("给人看的文本:");
+    for error in &failures {
+        print!("{}", registry.report(error).to_text());
+    }
+
+    println!("\nJSON Lines（每个错误一行）:");
+    for error in &failures {
+        println!("{}", registry.report(error).to_json_line());
+    }
+
+    // 没有转换成 anyhow 的错误，沿着 source() 走
+    #[derive(Error, Debug)]
+    #[error("读取 {path} 失败")]
+    struct LoadError {
+        path: String,
+        #[source]
+        source: ConfigError,
+    }
+
+    let error = LoadError {
+        path: "app.ini".to_string(),
+        source: Config::parse("[server\n").unwrap_err(),
+    };
+    println!("\n沿着 source() 收集:");
+    print!("{}", registry.report_error(&error).to_text());
+
+    println!("\n要点:");
+    println!("  - 错误码跟着错误类型的变体走，.context() 加的外层没有错误码");
+    println!("  - 报告的错误码取最外层的带错误码的原因，完整的链保留在 chain 中");
+    println!("  - 帮助链接指向 docs/errors.md 中对应的小节");
+    println!("  - 校验失败是用户输入的问题，记为警告（level = warning）");
+}
+
+/// 示例 25: 错误报告 - 格式检查
+///
+/// 错误码、文档、文本和 JSON 的格式都是对外的约定，逐字检查
+pub fn error_report_checks() {
+    println!("\n=== 示例 25: 错误报告 - 格式检查 ===");
+
+    use config::{Config, ConfigError, FromConfig, Table};
+    use diagnostic::Severity;
+    use report::{ErrorCode, Registry, help_url};
+
+    // 每一类配置错误的错误码
+    struct Limits {
+        port: u16,
+        workers: u64,
+    }
+
+    impl FromConfig for Limits {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            let workers = table.get("workers")?;
+            if workers == 0 {
+                return Err(table.invalid("workers", "至少 1 个"));
+            }
+            Ok(Limits {
+                port: table.get("port")?,
+                workers,
+            })
+        }
+    }
+
+    let code = |source: &str| match Config::parse(source).and_then(|c| c.deserialize::<Limits>()) {
+        Ok(limits) => panic!("应该失败: port = {}, workers = {}", limits.port, limits.workers),
+        Err(e) => e.code(),
+    };
+    let cases = [
+        ("port = [1, 2", "CFG001"),
+        ("port = 1\nport = 2", "CFG002"),
+        ("workers = 4", "CFG003"),
+        ("workers = 4\nport = \"80\"", "CFG004"),
+        ("workers = 4\nport = 70000", "CFG005"),
+        ("workers = 0\nport = 80", "CFG006"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(code(source), expected, "{:?}", source);
+    }
+    println!("✓ ConfigError 的错误码: CFG001 到 CFG006");
+
+    // docs/errors.md 中每个错误码一节，标题不重复，格式统一
+    const DOCS: &str = include_str!("../docs/errors.md");
+    let headings: Vec<&str> = DOCS.lines().filter_map(|line| line.strip_prefix("## ")).collect();
+    let known = [
+        "CFG001", "CFG002", "CFG003", "CFG004", "CFG005", "CFG006", "VAL001", "DAT001", "DAT002", "DAT003",
+        "DAT004", "APP001", "APP002", "APP003",
+    ];
+    assert_eq!(headings, known);
+    for heading in &headings {
+        let (prefix, number) = heading.split_at(3);
+        assert!(prefix.chars().all(|c| c.is_ascii_uppercase()) && number.len() == 3);
+        assert!(number.chars().all(|c| c.is_ascii_digit()));
+    }
+    assert_eq!(help_url("CFG004"), "docs/errors.md#cfg004");
+    println!("✓ docs/errors.md 有 {} 个错误码，每个都有对应的锚点", headings.len());
+
+    // 错误链：两层 context 包着一个 ConfigError
+    let registry = Registry::new().register::<ConfigError>();
+    struct Server {
+        port: u16,
+    }
+
+    impl FromConfig for Server {
+        fn from_config(table: &Table) -> Result<Self, ConfigError> {
+            Ok(Server { port: table.get("port")? })
+        }
+    }
+
+    let inner = || match Config::parse("[server]\nport = \"8080\"").unwrap().root().get::<Server>("server") {
+        Ok(server) => panic!("应该失败: port = {}", server.port),
+        Err(e) => e,
+    };
+    let error = anyhow::Error::new(inner())
+        .context("读取 [server] 段失败")
+        .context("加载配置失败");
+    let report = registry.report(&error);
+    assert_eq!(report.causes.len(), 3);
+    assert_eq!(report.code(), Some("CFG004"));
+    assert_eq!(report.severity(), Severity::Error);
+    assert_eq!(
+        report.to_text(),
+        "错误[CFG004]: 加载配置失败\n  原因 1: 读取 [server] 段失败\n  原因 2 [CFG004]: 第 2 行第 8 列: 期望整数，但这里是字符串\n  帮助: docs/errors.md#cfg004\n"
+    );
+    assert_eq!(
+        report.to_json_line(),
+        r#"{"level":"error","code":"CFG004","message":"加载配置失败","help":"docs/errors.md#cfg004","chain":[{"message":"加载配置失败","code":null},{"message":"读取 [server] 段失败","code":null},{"message":"第 2 行第 8 列: 期望整数，但这里是字符串","code":"CFG004"}]}"#
+    );
+    println!("✓ 文本和 JSON 的快照一致");
+
+    // 没有登记的类型认不出来
+    let error = anyhow::Error::new(inner());
+    assert_eq!(Registry::new().report(&error).code(), None);
+    assert_eq!(registry.report(&error).code(), Some("CFG004"));
+
+    // 没有错误码：level 按 error，code 和 help 为 null
+    let plain = anyhow::anyhow!("磁盘已满");
+    assert_eq!(
+        registry.report(&plain).to_json_line(),
+        r#"{"level":"error","code":null,"message":"磁盘已满","help":null,"chain":[{"message":"磁盘已满","code":null}]}"#
+    );
+
+    // 转义：引号、反斜杠、换行、制表符、其他控制字符；中文原样保留
+    let tricky = anyhow::anyhow!("路径 \"C:\\tmp\"\n\t第二行\u{1}");
+    let line = registry.report(&tricky).to_json_line();
+    assert!(line.contains(r#""message":"路径 \"C:\\tmp\"\n\t第二行\u0001""#), "{}", line);
+    assert!(!line.contains('\n'));
+    println!("✓ 没有错误码的错误、JSON 转义、每个报告只占一行");
+
+    // 警告：VAL001 的 severity 覆盖了默认值
+    let registry = registry.register::<validate::Report>();
+    let report = validate::Report { errors: Vec::new() };
+    assert_eq!(report.severity(), Severity::Warning);
+    let wrapped = anyhow::Error::new(report).context("注册失败");
+    assert!(registry.report(&wrapped).to_json_line().starts_with(r#"{"level":"warning","code":"VAL001""#));
+
+    // source() 和 chain() 得到同样的报告
+    #[derive(Error, Debug)]
+    #[error("读取 {0} 失败")]
+    struct LoadError(&'static str, #[source] ConfigError);
+
+    let by_source = registry.report_error(&LoadError("app.ini", inner()));
+    let by_chain = registry.report(&anyhow::Error::new(LoadError("app.ini", inner())));
+    assert_eq!(by_source, by_chain);
+    assert_eq!(by_source.primary().map(|c| c.message.as_str()), Some("第 2 行第 8 列: 期望整数，但这里是字符串"));
+    println!("✓ 警告级别、source() 与 chain() 的结果一致");
+}
+
 /// 运行所有示例
 pub fn run_all_examples() {
     println!("╔════════════════════════════════════════╗");
@@ -844,6 +1936,15 @@ pub fn run_all_examples() {
     let _ = practical_config_parser();
     practical_user_validation();
     let _ = practical_error_chain();
+    config_parser_errors();
+    diagnostic_rendering();
+    diagnostic_snapshots();
+    validation_framework();
+    validation_checks();
+    retry_and_circuit_breaker();
+    retry_checks();
+    error_code_reports();
+    error_report_checks();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  错误处理是 Rust 程序的重要组成！   ║");
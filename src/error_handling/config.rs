@@ -0,0 +1,819 @@
+//! # 配置文件解析：INI / TOML 的一个子集
+//!
+//! 示例 14 原来只能解析固定的三行 `host\nport\ntimeout`。这里支持一种常见的配置格式：
+//!
+//! ```text
+//! # 注释以 # 或 ; 开头，# 也可以跟在值后面
+//! name = "demo"
+//!
+//! [server]
+//! host = "localhost"
+//! port = 8080
+//! tls = false
+//! tags = ["web", "api"]        # 数组写在一行内，可以嵌套
+//! motd = "欢迎\n你好"   # 字符串支持 \" \\ \n \t \r \uXXXX
+//! ```
+//!
+//! 解析分两步：
+//!
+//! 1. `Config::parse`：文本 → `Table`，每个值都记录自己的行号和列号
+//! 2. `FromConfig` / `FromValue`：`Table` → 带类型的结构体，类型不符时同样指出行列
+//!
+//! 所有错误都是 `ConfigError` 的变体（thiserror 派生）。
+
+use crate::diagnostic::{offset_of, Diagnostic, Label};
+use super::report::ErrorCode;
+use std::fmt;
+use thiserror::Error;
+
+/// 源码中的位置，行号和列号都从 1 开始；列号按字符而不是字节计算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Self {
+        Position { line, column }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "第 {} 行第 {} 列", self.line, self.column)
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("{pos}: 段名缺少 ']'")]
+    UnterminatedSection { pos: Position },
+
+    #[error("{pos}: 无效的段名 '{name}'（只能包含字母、数字、'_' 和 '-'）")]
+    InvalidSectionName { name: String, pos: Position },
+
+    #[error("{pos}: 段 [{name}] 与第 {} 行重复", .first.line)]
+    DuplicateSection { name: String, first: Position, pos: Position },
+
+    #[error("{pos}: 期望 '='")]
+    ExpectedEquals { pos: Position },
+
+    #[error("{pos}: '=' 前面缺少键名")]
+    EmptyKey { pos: Position },
+
+    #[error("{pos}: 无效的键名 '{key}'（只能包含字母、数字、'_' 和 '-'）")]
+    InvalidKey { key: String, pos: Position },
+
+    #[error("{pos}: 键 '{key}' 与第 {} 行重复", .first.line)]
+    DuplicateKey { key: String, first: Position, pos: Position },
+
+    #[error("{pos}: '=' 后面缺少值")]
+    MissingValue { pos: Position },
+
+    #[error("{pos}: 字符串缺少结尾的引号")]
+    UnterminatedString { pos: Position },
+
+    #[error("{pos}: 无效的转义序列 '{escape}'")]
+    InvalidEscape { escape: String, pos: Position },
+
+    #[error("{pos}: 无效的整数 '{text}'")]
+    InvalidInteger { text: String, pos: Position },
+
+    #[error("{pos}: 无法识别的值 '{text}'（字符串需要加引号）")]
+    InvalidValue { text: String, pos: Position },
+
+    #[error("{pos}: 数组缺少 ']'（数组必须写在一行内）")]
+    UnterminatedArray { pos: Position },
+
+    #[error("{pos}: 值后面有多余的内容 '{text}'")]
+    TrailingCharacters { text: String, pos: Position },
+
+    #[error("{pos}: {}缺少配置项 '{key}'", table_label(.table))]
+    MissingKey { key: String, table: String, pos: Position },
+
+    #[error("{pos}: 期望{expected}，但这里是{found}")]
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+        pos: Position,
+    },
+
+    #[error("{pos}: {value} 超出了{target}的范围")]
+    OutOfRange {
+        value: i64,
+        target: &'static str,
+        pos: Position,
+    },
+
+    #[error("{pos}: '{key}' 无效：{message}")]
+    Invalid { key: String, message: String, pos: Position },
+}
+
+impl ConfigError {
+    /// 出错的位置；每个变体都带有位置
+    pub fn position(&self) -> Position {
+        match self {
+            ConfigError::UnterminatedSection { pos }
+            | ConfigError::InvalidSectionName { pos, .. }
+            | ConfigError::DuplicateSection { pos, .. }
+            | ConfigError::ExpectedEquals { pos }
+            | ConfigError::EmptyKey { pos }
+            | ConfigError::InvalidKey { pos, .. }
+            | ConfigError::DuplicateKey { pos, .. }
+            | ConfigError::MissingValue { pos }
+            | ConfigError::UnterminatedString { pos }
+            | ConfigError::InvalidEscape { pos, .. }
+            | ConfigError::InvalidInteger { pos, .. }
+            | ConfigError::InvalidValue { pos, .. }
+            | ConfigError::UnterminatedArray { pos }
+            | ConfigError::TrailingCharacters { pos, .. }
+            | ConfigError::MissingKey { pos, .. }
+            | ConfigError::TypeMismatch { pos, .. }
+            | ConfigError::OutOfRange { pos, .. }
+            | ConfigError::Invalid { pos, .. } => *pos,
+        }
+    }
+}
+
+impl ConfigError {
+    /// 转换成带源码片段的诊断，`source` 必须是解析时的原文
+    pub fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        let line_text = |pos: Position| source.lines().nth(pos.line - 1).unwrap_or("");
+        // 从 pos 开始的 len 个字符
+        let span = |pos: Position, len: usize| {
+            let start = offset_of(source, pos.line, pos.column);
+            start..start + len.max(1)
+        };
+        // 从 pos 到行尾
+        let rest_of_line = |pos: Position| span(pos, line_text(pos).chars().count().saturating_sub(pos.column - 1));
+        // pos 处的一个值（字符串、数组或单词）
+        let value = |pos: Position| {
+            let text: String = line_text(pos).chars().skip(pos.column - 1).collect();
+            span(pos, value_len(&text))
+        };
+        let len = |s: &str| s.chars().count();
+
+        // 标题不重复位置，位置由 --> 那一行显示
+        let message = self.to_string();
+        let title = message.split_once(": ").map_or(message.as_str(), |(_, rest)| rest);
+        let diagnostic = Diagnostic::error(title);
+        match self {
+            ConfigError::UnterminatedSection { pos } => diagnostic
+                .with_label(Label::primary(rest_of_line(*pos), "这个段名没有结束"))
+                .with_help("段名写成 [name]"),
+            ConfigError::InvalidSectionName { name, pos } => {
+                diagnostic.with_label(Label::primary(span(*pos, len(name)), "无效的段名"))
+            }
+            ConfigError::DuplicateSection { name, first, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(name)), "又定义了一次"))
+                .with_label(Label::secondary(span(*first, len(name)), "第一次定义在这里"))
+                .with_help("把两处的配置项合并到同一个段里"),
+            ConfigError::ExpectedEquals { pos } => diagnostic
+                .with_label(Label::primary(span(*pos, 1), "这里应该是 '='"))
+                .with_help("配置项写成 key = value"),
+            ConfigError::EmptyKey { pos } => diagnostic.with_label(Label::primary(span(*pos, 1), "前面缺少键名")),
+            ConfigError::InvalidKey { key, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(key)), "无效的键名"))
+                .with_note("不支持 TOML 的点号键，可以改用 [段]"),
+            ConfigError::DuplicateKey { key, first, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(key)), "又定义了一次"))
+                .with_label(Label::secondary(span(*first, len(key)), "第一次定义在这里")),
+            ConfigError::MissingValue { pos } => {
+                diagnostic.with_label(Label::primary(span(*pos, 1), "这里需要一个值"))
+            }
+            ConfigError::UnterminatedString { pos } => diagnostic
+                .with_label(Label::primary(rest_of_line(*pos), "字符串从这里开始"))
+                .with_help("字符串不能跨行，换行写成 \\n"),
+            ConfigError::InvalidEscape { escape, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(escape)), "无效的转义"))
+                .with_help("支持的转义有 \\\" \\\\ \\n \\t \\r \\uXXXX"),
+            ConfigError::InvalidInteger { text, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(text)), "不是 i64 范围内的整数"))
+                .with_note("不支持小数；可以用 _ 分隔数字，比如 1_000"),
+            ConfigError::InvalidValue { text, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(text)), "字符串需要加引号"))
+                .with_help(format!("写成 \"{}\"", text)),
+            ConfigError::UnterminatedArray { pos } => diagnostic
+                .with_label(Label::primary(rest_of_line(*pos), "数组从这里开始"))
+                .with_help("数组必须写在一行内，以 ] 结束"),
+            ConfigError::TrailingCharacters { text, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(text)), "多余的内容"))
+                .with_help("一行只能写一个值，注释以 # 开头"),
+            // 顶层表没有段头可以指，只给出帮助
+            ConfigError::MissingKey { key, table, .. } if table.is_empty() => {
+                diagnostic.with_help(format!("在第一个段之前加上 {} = ...", key))
+            }
+            ConfigError::MissingKey { key, table, pos } => diagnostic
+                .with_label(Label::primary(span(*pos, len(table) + 2), format!("这个段里没有 '{}'", key)))
+                .with_help(format!("在 [{}] 段里加上 {} = ...", table, key)),
+            ConfigError::TypeMismatch { expected, found, pos } => diagnostic
+                .with_label(Label::primary(value(*pos), format!("这是{}", found)))
+                .with_help(format!("这里需要{}", expected)),
+            ConfigError::OutOfRange { pos, .. } => diagnostic.with_label(Label::primary(value(*pos), "超出范围")),
+            ConfigError::Invalid { message, pos, .. } => {
+                diagnostic.with_label(Label::primary(value(*pos), message.clone()))
+            }
+        }
+    }
+}
+
+/// 错误码见 docs/errors.md；格式错误共用 CFG001，内容错误各有一个错误码
+impl ErrorCode for ConfigError {
+    fn code(&self) -> &'static str {
+        match self {
+            ConfigError::DuplicateSection { .. } | ConfigError::DuplicateKey { .. } => "CFG002",
+            ConfigError::MissingKey { .. } => "CFG003",
+            ConfigError::TypeMismatch { .. } => "CFG004",
+            ConfigError::OutOfRange { .. } => "CFG005",
+            ConfigError::Invalid { .. } => "CFG006",
+            _ => "CFG001",
+        }
+    }
+}
+
+/// 一行开头的值占几个字符：字符串到结尾的引号，数组到配对的 ]，其他到空白或分隔符
+fn value_len(text: &str) -> usize {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.chars().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' => depth += 1,
+            ']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ if depth == 0 && (c.is_whitespace() || matches!(c, ',' | ']' | '#')) => return i,
+            _ => {}
+        }
+    }
+    text.chars().count()
+}
+
+fn table_label(table: &str) -> String {
+    if table.is_empty() {
+        "顶层".to_string()
+    } else {
+        format!("[{}] 段", table)
+    }
+}
+
+/// 值和它在源码中的位置
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Spanned<Value>>),
+    /// `[section]` 段，作为顶层表中的一项
+    Table(Table),
+}
+
+impl Value {
+    /// 用于错误信息的类型名
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "字符串",
+            Value::Integer(_) => "整数",
+            Value::Boolean(_) => "布尔值",
+            Value::Array(_) => "数组",
+            Value::Table(_) => "段",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item.value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Table(table) => write!(f, "<段，{} 项>", table.len()),
+        }
+    }
+}
+
+/// 有序的键值表：顶层和每个 `[section]` 各是一个
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    name: String,
+    /// 段头的位置；顶层表为第 1 行第 1 列
+    pos: Position,
+    entries: Vec<(Spanned<String>, Spanned<Value>)>,
+}
+
+impl Table {
+    fn new(name: &str, pos: Position) -> Self {
+        Table {
+            name: name.to_string(),
+            pos,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 按出现顺序的键和值
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Spanned<Value>)> {
+        self.entries.iter().map(|(key, value)| (key.value.as_str(), value))
+    }
+
+    pub fn get_value(&self, key: &str) -> Option<&Spanned<Value>> {
+        self.entries.iter().find(|(k, _)| k.value == key).map(|(_, v)| v)
+    }
+
+    /// 读取并转换一项，缺少时返回 `MissingKey`
+    pub fn get<T: FromValue>(&self, key: &str) -> Result<T, ConfigError> {
+        match self.get_value(key) {
+            Some(value) => T::from_value(value),
+            None => Err(ConfigError::MissingKey {
+                key: key.to_string(),
+                table: self.name.clone(),
+                pos: self.pos,
+            }),
+        }
+    }
+
+    /// 可选的一项：缺少时返回 `default`，存在但类型不对仍然报错
+    pub fn get_or<T: FromValue>(&self, key: &str, default: T) -> Result<T, ConfigError> {
+        match self.get_value(key) {
+            Some(value) => T::from_value(value),
+            None => Ok(default),
+        }
+    }
+
+    /// 构造 `Invalid` 错误，位置指向 `key` 的值（不存在时指向段头）
+    pub fn invalid(&self, key: &str, message: impl Into<String>) -> ConfigError {
+        ConfigError::Invalid {
+            key: key.to_string(),
+            message: message.into(),
+            pos: self.get_value(key).map_or(self.pos, |v| v.pos),
+        }
+    }
+
+    fn insert(&mut self, key: Spanned<String>, value: Spanned<Value>) -> Result<(), ConfigError> {
+        if let Some((first, _)) = self.entries.iter().find(|(k, _)| k.value == key.value) {
+            return Err(ConfigError::DuplicateKey {
+                key: key.value,
+                first: first.pos,
+                pos: key.pos,
+            });
+        }
+        self.entries.push((key, value));
+        Ok(())
+    }
+}
+
+/// 从一个值转换成具体类型
+pub trait FromValue: Sized {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError>;
+}
+
+/// 从一个段转换成结构体；实现了它的类型自动实现 `FromValue`，可以用 `get` 读取整个段
+pub trait FromConfig: Sized {
+    fn from_config(table: &Table) -> Result<Self, ConfigError>;
+}
+
+fn mismatch(expected: &'static str, value: &Spanned<Value>) -> ConfigError {
+    ConfigError::TypeMismatch {
+        expected,
+        found: value.value.type_name(),
+        pos: value.pos,
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError> {
+        match &value.value {
+            Value::String(s) => Ok(s.clone()),
+            _ => Err(mismatch("字符串", value)),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError> {
+        match value.value {
+            Value::Boolean(b) => Ok(b),
+            _ => Err(mismatch("布尔值", value)),
+        }
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError> {
+        match value.value {
+            Value::Integer(n) => Ok(n),
+            _ => Err(mismatch("整数", value)),
+        }
+    }
+}
+
+impl FromValue for u16 {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError> {
+        let n = i64::from_value(value)?;
+        u16::try_from(n).map_err(|_| ConfigError::OutOfRange {
+            value: n,
+            target: " u16（0 到 65535）",
+            pos: value.pos,
+        })
+    }
+}
+
+impl FromValue for u64 {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError> {
+        let n = i64::from_value(value)?;
+        u64::try_from(n).map_err(|_| ConfigError::OutOfRange {
+            value: n,
+            target: "非负整数",
+            pos: value.pos,
+        })
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError> {
+        match &value.value {
+            Value::Array(items) => items.iter().map(T::from_value).collect(),
+            _ => Err(mismatch("数组", value)),
+        }
+    }
+}
+
+impl<T: FromConfig> FromValue for T {
+    fn from_value(value: &Spanned<Value>) -> Result<Self, ConfigError> {
+        match &value.value {
+            Value::Table(table) => T::from_config(table),
+            _ => Err(mismatch("段", value)),
+        }
+    }
+}
+
+/// 解析后的配置文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    root: Table,
+}
+
+impl Config {
+    pub fn parse(source: &str) -> Result<Config, ConfigError> {
+        let mut root = Table::new("", Position::new(1, 1));
+        // 当前段：(名字, 段)；遇到下一个段头或文件结束时放进 root
+        let mut current: Option<(Spanned<String>, Table)> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let mut cursor = Cursor::new(line, index + 1);
+            cursor.skip_whitespace();
+            if cursor.at_comment_or_end() || cursor.peek() == Some(';') {
+                continue;
+            }
+
+            if cursor.peek() == Some('[') {
+                let (name, table) = parse_section_header(&mut cursor)?;
+                if let Some((prev_name, prev)) = current.take() {
+                    close_section(&mut root, prev_name, prev);
+                }
+                if let Some((first, _)) = root.entries.iter().find(|(k, _)| k.value == name.value) {
+                    return Err(ConfigError::DuplicateSection {
+                        name: name.value,
+                        first: first.pos,
+                        pos: name.pos,
+                    });
+                }
+                current = Some((name, table));
+                continue;
+            }
+
+            let (key, value) = parse_entry(&mut cursor)?;
+            match &mut current {
+                Some((_, table)) => table.insert(key, value)?,
+                None => root.insert(key, value)?,
+            }
+        }
+
+        if let Some((name, table)) = current {
+            close_section(&mut root, name, table);
+        }
+        Ok(Config { root })
+    }
+
+    /// 顶层表，各个段作为其中的 `Value::Table`
+    pub fn root(&self) -> &Table {
+        &self.root
+    }
+
+    /// 把整个文件转换成结构体
+    pub fn deserialize<T: FromConfig>(&self) -> Result<T, ConfigError> {
+        T::from_config(&self.root)
+    }
+}
+
+/// 段结束后作为 `Value::Table` 放进顶层表；重名已经在读到段头时检查过
+fn close_section(root: &mut Table, name: Spanned<String>, table: Table) {
+    let value = Spanned {
+        pos: table.pos,
+        value: Value::Table(table),
+    };
+    root.entries.push((name, value));
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+fn parse_section_header(cursor: &mut Cursor) -> Result<(Spanned<String>, Table), ConfigError> {
+    let open = cursor.position();
+    cursor.bump();
+    let rest = cursor.rest();
+    let Some(close) = rest.find(']') else {
+        return Err(ConfigError::UnterminatedSection { pos: open });
+    };
+    let raw = &rest[..close];
+    let name = raw.trim();
+    let name_pos = Position::new(open.line, open.column + 1 + (raw.chars().count() - raw.trim_start().chars().count()));
+    if name.is_empty() || !name.chars().all(is_key_char) {
+        return Err(ConfigError::InvalidSectionName {
+            name: name.to_string(),
+            pos: name_pos,
+        });
+    }
+    cursor.advance(close + 1);
+    cursor.expect_line_end()?;
+
+    let name = Spanned {
+        value: name.to_string(),
+        pos: name_pos,
+    };
+    let table = Table::new(&name.value, open);
+    Ok((name, table))
+}
+
+fn parse_entry(cursor: &mut Cursor) -> Result<(Spanned<String>, Spanned<Value>), ConfigError> {
+    let key_pos = cursor.position();
+    let rest = cursor.rest();
+    // 键名里不会有 '#'，先截掉注释，避免 "a # = 1" 被当成键 "a #"
+    let searchable = rest.split('#').next().unwrap_or("");
+    let Some(eq) = searchable.find('=') else {
+        // 指向第一个词后面，"host localhost" 指向 localhost
+        cursor.take_while(is_key_char);
+        cursor.skip_whitespace();
+        return Err(ConfigError::ExpectedEquals { pos: cursor.position() });
+    };
+    let key = rest[..eq].trim_end();
+    if key.is_empty() {
+        cursor.advance(eq);
+        return Err(ConfigError::EmptyKey { pos: cursor.position() });
+    }
+    if !key.chars().all(is_key_char) {
+        return Err(ConfigError::InvalidKey {
+            key: key.to_string(),
+            pos: key_pos,
+        });
+    }
+    let key = Spanned {
+        value: key.to_string(),
+        pos: key_pos,
+    };
+
+    cursor.advance(eq + 1);
+    cursor.skip_whitespace();
+    if cursor.at_comment_or_end() {
+        return Err(ConfigError::MissingValue { pos: cursor.position() });
+    }
+    let value = parse_value(cursor)?;
+    cursor.expect_line_end()?;
+    Ok((key, value))
+}
+
+fn parse_value(cursor: &mut Cursor) -> Result<Spanned<Value>, ConfigError> {
+    let pos = cursor.position();
+    let value = match cursor.peek() {
+        Some('"') => Value::String(parse_string(cursor)?),
+        Some('[') => Value::Array(parse_array(cursor)?),
+        _ => {
+            let mut text = cursor.take_while(|c| !c.is_whitespace() && !matches!(c, ',' | ']' | '#'));
+            if text.is_empty() {
+                // 例如 "a = ]"，把这个字符本身报告出来
+                let rest = cursor.rest();
+                cursor.bump();
+                text = &rest[..rest.len() - cursor.rest().len()];
+            }
+            match text {
+                "true" => Value::Boolean(true),
+                "false" => Value::Boolean(false),
+                _ if text.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') => {
+                    // 允许 1_000_000 这样的分隔符
+                    let digits: String = text.chars().filter(|&c| c != '_').collect();
+                    let n = digits.parse::<i64>().map_err(|_| ConfigError::InvalidInteger {
+                        text: text.to_string(),
+                        pos,
+                    })?;
+                    Value::Integer(n)
+                }
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        text: text.to_string(),
+                        pos,
+                    })
+                }
+            }
+        }
+    };
+    Ok(Spanned { value, pos })
+}
+
+fn parse_string(cursor: &mut Cursor) -> Result<String, ConfigError> {
+    let open = cursor.position();
+    cursor.bump();
+    let mut out = String::new();
+    loop {
+        let escape_pos = cursor.position();
+        match cursor.bump() {
+            None => return Err(ConfigError::UnterminatedString { pos: open }),
+            Some('"') => return Ok(out),
+            Some('\\') => {
+                let c = match cursor.bump() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('u') => {
+                        let hex: String = cursor.rest().chars().take(4).take_while(char::is_ascii_hexdigit).collect();
+                        let c = (hex.len() == 4)
+                            .then(|| u32::from_str_radix(&hex, 16).ok())
+                            .flatten()
+                            .and_then(char::from_u32);
+                        match c {
+                            Some(c) => {
+                                cursor.advance(4);
+                                c
+                            }
+                            None => {
+                                return Err(ConfigError::InvalidEscape {
+                                    escape: format!("\\u{}", hex),
+                                    pos: escape_pos,
+                                })
+                            }
+                        }
+                    }
+                    Some(other) => {
+                        return Err(ConfigError::InvalidEscape {
+                            escape: format!("\\{}", other),
+                            pos: escape_pos,
+                        })
+                    }
+                    None => return Err(ConfigError::UnterminatedString { pos: open }),
+                };
+                out.push(c);
+            }
+            Some(c) => out.push(c),
+        }
+    }
+}
+
+fn parse_array(cursor: &mut Cursor) -> Result<Vec<Spanned<Value>>, ConfigError> {
+    let open = cursor.position();
+    cursor.bump();
+    let mut items = Vec::new();
+    loop {
+        cursor.skip_whitespace();
+        if cursor.at_comment_or_end() {
+            return Err(ConfigError::UnterminatedArray { pos: open });
+        }
+        if cursor.peek() == Some(']') {
+            cursor.bump();
+            return Ok(items);
+        }
+        items.push(parse_value(cursor)?);
+        cursor.skip_whitespace();
+        match cursor.peek() {
+            // 允许末尾多一个逗号
+            Some(',') => {
+                cursor.bump();
+            }
+            Some(']') => {}
+            None | Some('#') => return Err(ConfigError::UnterminatedArray { pos: open }),
+            Some(_) => {
+                let pos = cursor.position();
+                let text = cursor.take_while(|c| !c.is_whitespace() && !matches!(c, ',' | ']'));
+                return Err(ConfigError::TrailingCharacters {
+                    text: text.to_string(),
+                    pos,
+                });
+            }
+        }
+    }
+}
+
+/// 一行文本上的游标，记录字节偏移，需要时换算成列号
+struct Cursor<'a> {
+    line: &'a str,
+    line_number: usize,
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a str, line_number: usize) -> Self {
+        Cursor {
+            line,
+            line_number,
+            offset: 0,
+        }
+    }
+
+    fn position(&self) -> Position {
+        Position::new(self.line_number, self.line[..self.offset].chars().count() + 1)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.line[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        self.offset += bytes;
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn at_comment_or_end(&self) -> bool {
+        matches!(self.peek(), None | Some('#'))
+    }
+
+    /// 值或段头后面只能有空白和注释
+    fn expect_line_end(&mut self) -> Result<(), ConfigError> {
+        self.skip_whitespace();
+        if self.at_comment_or_end() {
+            return Ok(());
+        }
+        let pos = self.position();
+        let text = self.rest().split('#').next().unwrap_or("").trim_end();
+        Err(ConfigError::TrailingCharacters {
+            text: text.to_string(),
+            pos,
+        })
+    }
+}
@@ -0,0 +1,204 @@
+//! # 错误码和结构化的错误报告
+//!
+//! 示例 8 的 `DataError`、示例 11 的 `AppError` 只有一句描述。描述会改、会翻译，
+//! 日志检索和文档需要一个稳定的标识：
+//!
+//! - `ErrorCode`：错误类型给每个变体分配一个错误码（如 `CFG004`）、严重程度和帮助链接
+//! - 帮助链接指向仓库里的 `docs/errors.md`，每个错误码一节
+//! - `Registry`：登记实现了 `ErrorCode` 的类型。错误链里的每一层都是 `&dyn Error`，
+//!   只能逐个类型尝试 `downcast_ref`，所以需要事先登记
+//! - `ErrorReport`：沿着 `anyhow::Error::chain()` 或 `source()` 收集每一层，
+//!   输出给人看的文本，或者一行一个 JSON 对象（JSON Lines）给日志系统
+//!
+//! ```text
+//! 错误[CFG004]: 加载配置失败
+//!   原因 1: 读取 [server] 段失败
+//!   原因 2 [CFG004]: 第 3 行第 8 列: 期望整数，但这里是字符串
+//!   帮助: docs/errors.md#cfg004
+//! ```
+
+use crate::diagnostic::Severity;
+use std::error::Error;
+use std::fmt::Write;
+
+/// 错误码文档的路径，相对于仓库根目录
+pub const DOCS_PATH: &str = "docs/errors.md";
+
+/// 错误码对应的文档链接，锚点是小写的错误码
+pub fn help_url(code: &str) -> String {
+    format!("{}#{}", DOCS_PATH, code.to_lowercase())
+}
+
+/// 带错误码的错误
+pub trait ErrorCode: Error {
+    /// 三个大写字母加三位数字，发布后不再修改
+    fn code(&self) -> &'static str;
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn help_url(&self) -> String {
+        help_url(self.code())
+    }
+}
+
+type Lookup = for<'a> fn(&'a (dyn Error + 'static)) -> Option<&'a dyn ErrorCode>;
+
+fn lookup<'a, T: ErrorCode + 'static>(error: &'a (dyn Error + 'static)) -> Option<&'a dyn ErrorCode> {
+    error.downcast_ref::<T>().map(|e| e as &dyn ErrorCode)
+}
+
+/// 登记了哪些错误类型带有错误码
+#[derive(Default)]
+pub struct Registry {
+    lookups: Vec<Lookup>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry { lookups: Vec::new() }
+    }
+
+    pub fn register<T: ErrorCode + 'static>(mut self) -> Self {
+        self.lookups.push(lookup::<T>);
+        self
+    }
+
+    /// 这一层错误是不是登记过的类型
+    pub fn code_of<'a>(&self, error: &'a (dyn Error + 'static)) -> Option<&'a dyn ErrorCode> {
+        self.lookups.iter().find_map(|lookup| lookup(error))
+    }
+
+    /// 沿着 `anyhow::Error::chain()` 收集，包括 `.context()` 加上的每一层
+    pub fn report(&self, error: &anyhow::Error) -> ErrorReport {
+        self.collect(error.chain())
+    }
+
+    /// 沿着 `source()` 收集，用于没有转换成 anyhow 的错误
+    pub fn report_error(&self, error: &(dyn Error + 'static)) -> ErrorReport {
+        self.collect(std::iter::successors(Some(error), |&e| e.source()))
+    }
+
+    fn collect<'a>(&self, chain: impl Iterator<Item = &'a (dyn Error + 'static)>) -> ErrorReport {
+        let causes = chain
+            .map(|error| {
+                let coded = self.code_of(error);
+                Cause {
+                    message: error.to_string(),
+                    code: coded.map(|c| c.code()),
+                    severity: coded.map(|c| c.severity()),
+                    help: coded.map(|c| c.help_url()),
+                }
+            })
+            .collect();
+        ErrorReport { causes }
+    }
+}
+
+/// 错误链中的一层
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cause {
+    pub message: String,
+    pub code: Option<&'static str>,
+    pub severity: Option<Severity>,
+    pub help: Option<String>,
+}
+
+/// 一个错误的完整原因链，第一个是最外层
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorReport {
+    pub causes: Vec<Cause>,
+}
+
+impl ErrorReport {
+    /// 最外层的带错误码的原因；外面的 `.context()` 通常没有错误码
+    pub fn primary(&self) -> Option<&Cause> {
+        self.causes.iter().find(|c| c.code.is_some())
+    }
+
+    pub fn code(&self) -> Option<&'static str> {
+        self.primary().and_then(|c| c.code)
+    }
+
+    /// 没有错误码时按错误处理
+    pub fn severity(&self) -> Severity {
+        self.primary().and_then(|c| c.severity).unwrap_or(Severity::Error)
+    }
+
+    /// 给人看的多行文本
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let level = match self.severity() {
+            Severity::Error => "错误",
+            Severity::Warning => "警告",
+        };
+        let message = self.causes.first().map_or("", |c| c.message.as_str());
+        match self.code() {
+            Some(code) => writeln!(out, "{}[{}]: {}", level, code, message),
+            None => writeln!(out, "{}: {}", level, message),
+        }
+        .unwrap();
+        for (i, cause) in self.causes.iter().enumerate().skip(1) {
+            // 多行的描述（比如校验报告）缩进到原因的下面
+            let message = cause.message.replace('\n', "\n    ");
+            match cause.code {
+                Some(code) => writeln!(out, "  原因 {} [{}]: {}", i, code, message),
+                None => writeln!(out, "  原因 {}: {}", i, message),
+            }
+            .unwrap();
+        }
+        if let Some(help) = self.primary().and_then(|c| c.help.as_deref()) {
+            writeln!(out, "  帮助: {}", help).unwrap();
+        }
+        out
+    }
+
+    /// 一行 JSON，不含换行，字段顺序固定
+    ///
+    /// `{"level":"error","code":"CFG004","message":"...","help":"...","chain":[{"message":"...","code":null}]}`
+    pub fn to_json_line(&self) -> String {
+        let level = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let message = self.causes.first().map_or("", |c| c.message.as_str());
+        let help = self.primary().and_then(|c| c.help.as_deref());
+        let chain: Vec<String> = self
+            .causes
+            .iter()
+            .map(|c| format!(r#"{{"message":{},"code":{}}}"#, json_string(&c.message), json_option(c.code)))
+            .collect();
+        format!(
+            r#"{{"level":"{}","code":{},"message":{},"help":{},"chain":[{}]}}"#,
+            level,
+            json_option(self.code()),
+            json_string(message),
+            json_option(help),
+            chain.join(",")
+        )
+    }
+}
+
+fn json_option(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".to_string(), json_string)
+}
+
+/// JSON 字符串字面量；非 ASCII 字符原样保留（JSON 本身就是 UTF-8）
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
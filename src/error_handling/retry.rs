@@ -0,0 +1,234 @@
+//! # 重试、退避和断路器
+//!
+//! 示例 16 的三个步骤只执行一次，失败就放弃。对网络请求这类偶尔失败的操作，常见的做法是：
+//!
+//! - 重试：隔一段时间再试，间隔固定、指数增长，或者在指数增长的基础上加随机抖动
+//! - 区分错误：超时、限流值得重试；参数错误、资源不存在重试多少次都一样，立即返回
+//! - 断路器：连续失败太多次就暂时不再调用（打开），过一段时间放一个请求试探（半开），
+//!   成功了恢复正常（关闭），失败了继续打开
+//!
+//! 所有和时间有关的操作都通过 `util::Clock` 完成（与限流器、缓存共用）。示例里用 `FakeClock`，
+//! `sleep` 只是把时间往前拨，检查退避间隔和断路器的超时都不需要真的等待。
+
+use crate::util::{Clock, XorShift};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// 两次尝试之间等待多久
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backoff {
+    /// 每次都等待相同的时间
+    Fixed(Duration),
+    /// 从 `initial` 开始每次翻倍，不超过 `max`
+    Exponential { initial: Duration, max: Duration },
+    /// 在 0 和指数退避的间隔之间随机取值（full jitter），
+    /// 避免很多客户端在同一时刻一起重试
+    Jittered { initial: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// 第 1、2、3... 次重试之前的等待时间，是一个无限序列；`seed` 只影响 `Jittered`
+    pub fn delays(&self, seed: u64) -> impl Iterator<Item = Duration> + '_ {
+        let mut rng = XorShift::new(seed);
+        (0u32..).map(move |n| match *self {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => exponential(initial, max, n),
+            Backoff::Jittered { initial, max } => exponential(initial, max, n).mul_f64(rng.next_f64()),
+        })
+    }
+}
+
+/// `initial * 2^n`，溢出时取 `max`
+fn exponential(initial: Duration, max: Duration, n: u32) -> Duration {
+    2u32.checked_pow(n)
+        .and_then(|factor| initial.checked_mul(factor))
+        .map_or(max, |delay| delay.min(max))
+}
+
+/// 错误是否值得重试
+///
+/// 通常由错误类型自己实现，按 thiserror 枚举的变体分类
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 包括第一次在内最多尝试几次
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+    /// 从第一次尝试开始最多花多长时间（包括等待）；下一次等待会超过时就不再重试
+    pub max_elapsed: Option<Duration>,
+    pub seed: u64,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, backoff: Backoff) -> Self {
+        RetryPolicy {
+            max_attempts,
+            backoff,
+            max_elapsed: None,
+            seed: 0x853C_49E6_748F_EA9B,
+        }
+    }
+
+    pub fn max_elapsed(mut self, limit: Duration) -> Self {
+        self.max_elapsed = Some(limit);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// 重试结束时仍然失败，保留最后一次的错误
+#[derive(Error, Debug, PartialEq)]
+pub enum RetryError<E> {
+    #[error("第 {attempts} 次尝试遇到不可重试的错误")]
+    Fatal {
+        attempts: u32,
+        #[source]
+        source: E,
+    },
+
+    #[error("尝试了 {attempts} 次仍然失败")]
+    Exhausted {
+        attempts: u32,
+        #[source]
+        source: E,
+    },
+}
+
+impl<E> RetryError<E> {
+    pub fn attempts(&self) -> u32 {
+        match self {
+            RetryError::Fatal { attempts, .. } | RetryError::Exhausted { attempts, .. } => *attempts,
+        }
+    }
+
+    pub fn into_inner(self) -> E {
+        match self {
+            RetryError::Fatal { source, .. } | RetryError::Exhausted { source, .. } => source,
+        }
+    }
+}
+
+/// 按策略执行 `op`，参数是第几次尝试（从 1 开始）
+///
+/// 成功、遇到不可重试的错误、次数用完、或者超过 `max_elapsed` 时返回
+pub fn retry<T, E, C>(policy: &RetryPolicy, clock: &C, mut op: impl FnMut(u32) -> Result<T, E>) -> Result<T, RetryError<E>>
+where
+    E: Retryable,
+    C: Clock,
+{
+    let start = clock.now();
+    let mut delays = policy.backoff.delays(policy.seed);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let error = match op(attempts) {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        if !error.is_retryable() {
+            return Err(RetryError::Fatal { attempts, source: error });
+        }
+        let delay = delays.next().unwrap_or_default();
+        let over_time = policy
+            .max_elapsed
+            .is_some_and(|limit| clock.now() - start + delay > limit);
+        if attempts >= policy.max_attempts || over_time {
+            return Err(RetryError::Exhausted { attempts, source: error });
+        }
+        clock.sleep(delay);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// 正常调用，记录连续失败的次数
+    Closed,
+    /// 直接拒绝，不调用操作
+    Open,
+    /// 打开的时间已到，允许一次试探
+    HalfOpen,
+}
+
+/// 经过断路器的调用失败
+#[derive(Error, Debug, PartialEq)]
+pub enum CircuitError<E> {
+    #[error("断路器已打开，{} 毫秒后再试", .retry_after.as_millis())]
+    Open { retry_after: Duration },
+
+    #[error(transparent)]
+    Inner(E),
+}
+
+/// 断路器打开时重试没有意义，交给调用方决定什么时候再来
+impl<E: Retryable> Retryable for CircuitError<E> {
+    fn is_retryable(&self) -> bool {
+        match self {
+            CircuitError::Open { .. } => false,
+            CircuitError::Inner(error) => error.is_retryable(),
+        }
+    }
+}
+
+pub struct CircuitBreaker<C: Clock> {
+    clock: C,
+    /// 连续失败多少次后打开
+    failure_threshold: u32,
+    /// 打开多久后进入半开
+    open_for: Duration,
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+}
+
+impl<C: Clock> CircuitBreaker<C> {
+    pub fn new(clock: C, failure_threshold: u32, open_for: Duration) -> Self {
+        CircuitBreaker {
+            opened_at: clock.now(),
+            clock,
+            failure_threshold,
+            open_for,
+            state: CircuitState::Closed,
+            failures: 0,
+        }
+    }
+
+    /// 当前状态；打开的时间到了就显示为半开
+    pub fn state(&self) -> CircuitState {
+        match self.state {
+            CircuitState::Open if self.clock.now() - self.opened_at >= self.open_for => CircuitState::HalfOpen,
+            state => state,
+        }
+    }
+
+    /// 每一次失败都计数，不区分是否可重试
+    pub fn call<T, E>(&mut self, op: impl FnOnce() -> Result<T, E>) -> Result<T, CircuitError<E>> {
+        let state = self.state();
+        if state == CircuitState::Open {
+            let retry_after = self.open_for - (self.clock.now() - self.opened_at);
+            return Err(CircuitError::Open { retry_after });
+        }
+        match op() {
+            Ok(value) => {
+                self.state = CircuitState::Closed;
+                self.failures = 0;
+                Ok(value)
+            }
+            Err(error) => {
+                self.failures += 1;
+                // 半开时的试探失败，立即重新打开
+                if state == CircuitState::HalfOpen || self.failures >= self.failure_threshold {
+                    self.state = CircuitState::Open;
+                    self.opened_at = self.clock.now();
+                }
+                Err(CircuitError::Inner(error))
+            }
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! # 声明式校验
+//!
+//! 示例 15 用 `?` 串起三个校验函数，第一个错误出现后就返回，用户改完一处才能看到下一处。
+//! 这里把每条规则做成可以组合的值，一次收集所有字段的错误：
+//!
+//! ```text
+//! let mut v = Validator::new(Mode::Accumulate);
+//! v.field("username", user.username.as_str(), &[required(), length(3, 20)]);
+//! v.field("age", &user.age, &[range(18, 120)]);
+//! v.nested("address", &user.address);        // Address 实现了 Validate
+//! v.finish()?;                               // Err(Report)，包含所有错误和字段路径
+//! ```
+//!
+//! - 同一个字段的规则按顺序检查，遇到第一个失败就停止（为空时不必再报长度不够）
+//! - `Mode::Accumulate` 继续检查其他字段；`Mode::FailFast` 在第一个错误后跳过剩下的检查
+//! - 嵌套结构和列表的错误带有路径，比如 `address.zip`、`contacts[1].email`
+
+use crate::diagnostic::Severity;
+use super::report::ErrorCode;
+use std::fmt;
+use thiserror::Error;
+
+/// 一条规则失败的原因
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    #[error("不能为空")]
+    Required,
+
+    #[error("长度至少为 {min}，实际为 {actual}")]
+    TooShort { min: usize, actual: usize },
+
+    #[error("长度最多为 {max}，实际为 {actual}")]
+    TooLong { max: usize, actual: usize },
+
+    #[error("必须在 {min} 到 {max} 之间，实际为 {actual}")]
+    OutOfRange { min: String, max: String, actual: String },
+
+    #[error("邮箱格式无效")]
+    InvalidEmail,
+
+    #[error("{0}")]
+    Invalid(String),
+}
+
+type Check<T> = dyn Fn(&T) -> Result<(), Violation>;
+
+/// 一条规则：检查 `&T`，失败时返回原因
+pub struct Rule<T: ?Sized> {
+    check: Box<Check<T>>,
+}
+
+impl<T: ?Sized> Rule<T> {
+    pub fn new(check: impl Fn(&T) -> Result<(), Violation> + 'static) -> Self {
+        Rule { check: Box::new(check) }
+    }
+
+    pub fn check(&self, value: &T) -> Result<(), Violation> {
+        (self.check)(value)
+    }
+}
+
+/// `required` 判断"有没有值"的方式
+pub trait Presence {
+    fn is_present(&self) -> bool;
+}
+
+impl Presence for str {
+    /// 只有空白也算没有填
+    fn is_present(&self) -> bool {
+        !self.trim().is_empty()
+    }
+}
+
+impl Presence for String {
+    fn is_present(&self) -> bool {
+        self.as_str().is_present()
+    }
+}
+
+impl<T> Presence for Option<T> {
+    fn is_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T> Presence for Vec<T> {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+/// `length` 计算长度的方式
+pub trait Length {
+    fn length(&self) -> usize;
+}
+
+impl Length for str {
+    /// 按字符计算，"张三" 的长度是 2 而不是 6
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl Length for String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+pub fn required<T: Presence + ?Sized>() -> Rule<T> {
+    Rule::new(|value: &T| if value.is_present() { Ok(()) } else { Err(Violation::Required) })
+}
+
+/// 长度在 `min..=max` 之间
+pub fn length<T: Length + ?Sized>(min: usize, max: usize) -> Rule<T> {
+    Rule::new(move |value: &T| {
+        let actual = value.length();
+        if actual < min {
+            Err(Violation::TooShort { min, actual })
+        } else if actual > max {
+            Err(Violation::TooLong { max, actual })
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// 取值在 `min..=max` 之间
+pub fn range<T: PartialOrd + fmt::Display + 'static>(min: T, max: T) -> Rule<T> {
+    Rule::new(move |value: &T| {
+        if *value < min || *value > max {
+            Err(Violation::OutOfRange {
+                min: min.to_string(),
+                max: max.to_string(),
+                actual: value.to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// 自定义条件，不满足时报告 `message`
+pub fn matches<T: ?Sized>(pred: impl Fn(&T) -> bool + 'static, message: &str) -> Rule<T> {
+    let message = message.to_string();
+    Rule::new(move |value: &T| if pred(value) { Ok(()) } else { Err(Violation::Invalid(message.clone())) })
+}
+
+/// 简化的邮箱格式：`本地部分@域名`，域名至少有两段，不能有空白
+pub fn email() -> Rule<str> {
+    Rule::new(|value: &str| {
+        let valid = match value.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty()
+                    && !domain.contains('@')
+                    && domain.split('.').count() >= 2
+                    && domain.split('.').all(|part| !part.is_empty())
+                    && !value.chars().any(char::is_whitespace)
+            }
+            None => false,
+        };
+        if valid { Ok(()) } else { Err(Violation::InvalidEmail) }
+    })
+}
+
+/// 某个字段的错误，`path` 是从根开始的字段路径
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub path: String,
+    pub violation: Violation,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.violation)
+    }
+}
+
+/// 所有字段错误，按检查顺序排列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub errors: Vec<FieldError>,
+}
+
+impl Report {
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 某个路径上的错误
+    pub fn get(&self, path: &str) -> Option<&Violation> {
+        self.errors.iter().find(|e| e.path == path).map(|e| &e.violation)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} 个字段校验失败", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Report {}
+
+/// 用户输入的问题，不是程序的错误，记为警告
+impl ErrorCode for Report {
+    fn code(&self) -> &'static str {
+        "VAL001"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+}
+
+/// 遇到错误后是否继续检查
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// 第一个错误之后跳过剩下的检查
+    FailFast,
+    /// 检查所有字段，收集全部错误
+    Accumulate,
+}
+
+/// 可以被嵌套校验的结构体
+pub trait Validate {
+    fn validate(&self, v: &mut Validator);
+}
+
+/// 逐个字段执行规则，记录错误和路径
+pub struct Validator {
+    mode: Mode,
+    prefix: String,
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new(mode: Mode) -> Self {
+        Validator {
+            mode,
+            prefix: String::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.mode == Mode::FailFast && !self.errors.is_empty()
+    }
+
+    fn path(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    /// 按顺序检查规则，第一个失败的规则产生一条错误
+    pub fn field<T: ?Sized>(&mut self, name: &str, value: &T, rules: &[Rule<T>]) -> &mut Self {
+        if self.stopped() {
+            return self;
+        }
+        if let Some(violation) = rules.iter().find_map(|rule| rule.check(value).err()) {
+            self.errors.push(FieldError {
+                path: self.path(name),
+                violation,
+            });
+        }
+        self
+    }
+
+    /// 可选字段：`None` 时跳过，有值时检查
+    pub fn optional<T: ?Sized>(&mut self, name: &str, value: Option<&T>, rules: &[Rule<T>]) -> &mut Self {
+        match value {
+            Some(value) => self.field(name, value, rules),
+            None => self,
+        }
+    }
+
+    /// 嵌套结构，错误路径加上 `name.` 前缀
+    pub fn nested<T: Validate>(&mut self, name: &str, value: &T) -> &mut Self {
+        if self.stopped() {
+            return self;
+        }
+        let prefix = format!("{}{}.", self.prefix, name);
+        let saved = std::mem::replace(&mut self.prefix, prefix);
+        value.validate(self);
+        self.prefix = saved;
+        self
+    }
+
+    /// 列表中的每个元素都检查同样的规则，路径为 `name[i]`
+    pub fn each<T>(&mut self, name: &str, items: &[T], rules: &[Rule<T>]) -> &mut Self {
+        for (i, item) in items.iter().enumerate() {
+            self.field(&format!("{}[{}]", name, i), item, rules);
+        }
+        self
+    }
+
+    /// 列表中的每个结构体，路径为 `name[i].field`
+    pub fn each_nested<T: Validate>(&mut self, name: &str, items: &[T]) -> &mut Self {
+        for (i, item) in items.iter().enumerate() {
+            self.nested(&format!("{}[{}]", name, i), item);
+        }
+        self
+    }
+
+    /// 跨字段的检查（比如两次输入的密码一致），失败时记在 `name` 上
+    pub fn check(&mut self, name: &str, ok: bool, message: &str) -> &mut Self {
+        if !self.stopped() && !ok {
+            self.errors.push(FieldError {
+                path: self.path(name),
+                violation: Violation::Invalid(message.to_string()),
+            });
+        }
+        self
+    }
+
+    pub fn finish(&mut self) -> Result<(), Report> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Report {
+                errors: std::mem::take(&mut self.errors),
+            })
+        }
+    }
+}
+
+/// 校验整个结构体
+pub fn validate<T: Validate>(value: &T, mode: Mode) -> Result<(), Report> {
+    let mut v = Validator::new(mode);
+    value.validate(&mut v);
+    v.finish()
+}
@@ -5,6 +5,15 @@
 
 use std::fmt::Display;
 
+// 零拷贝的词法分析器：记号借用输入，带字节区间（示例 15-16）
+pub mod lexer;
+
+// 分层配置：默认值 < 配置文件 < 环境变量 < 命令行，值借用加载的文本（示例 17-18）
+pub mod layered_config;
+
+// 零拷贝的 CSV 读取：字段借用输入，需要去掉转义引号时才分配（示例 19-20），集合和闭包模块的示例 16 也用它读取输入
+pub mod csv;
+
 /// 示例 1: 生命周期问题演示
 ///
 /// 展示为什么需要生命周期注解
@@ -478,6 +487,7 @@ pub fn practical_parser() {
     println!("  - 避免复制数据");
     println!("  - 返回原始字符串的切片");
     println!("  - 确保引用的有效性");
+    println!("\n注: 只能按单个空格切分；通用的词法分析器见示例 15-16");
 }
 
 /// 示例 13: 实际应用 - 配置管理器
@@ -542,6 +552,7 @@ pub fn practical_config() {
     println!("  - 不复制配置数据");
     println!("  - 生命周期确保配置数据的有效性");
     println!("  - 高效的内存使用");
+    println!("  - 多个来源按优先级合并的版本见示例 17-18 的分层配置");
 }
 
 /// 示例 14: 实际应用 - 迭代器包装器
@@ -594,6 +605,563 @@ pub fn practical_iterator() {
     println!("  - 类型安全");
 }
 
+/// 示例 15: 零拷贝的词法分析器
+///
+/// 示例 12 的解析器推广成通用的词法分析器，所有记号都借用输入
+pub fn zero_copy_lexer() {
+    println!("\n=== 示例 15: 零拷贝的词法分析器 ===");
+
+    use lexer::{Lexer, TokenKind};
+    use std::borrow::Cow;
+
+    let source = String::from("let 名字 = \"张三\\t\\u{1F980}\";　年龄 >= 18 && 分数 != 3.5e2，完成！");
+    println!("输入: {}", source);
+    for item in Lexer::new(&source) {
+        let token = item.expect("输入中没有错误");
+        let content = match token.unescape() {
+            Cow::Borrowed(_) if token.kind != TokenKind::Str => String::new(),
+            Cow::Borrowed(s) => format!(" -> {:?}（借用）", s),
+            Cow::Owned(s) => format!(" -> {:?}（分配）", s),
+        };
+        let kind = format!("{:?}", token.kind);
+        let span = format!("{:?}", token.span);
+        println!("  {:<7} {:<8} {}{}", kind, span, token.text, content);
+    }
+
+    // 记号的文本就是输入的切片，地址落在输入的范围内
+    let range = source.as_bytes().as_ptr_range();
+    let borrowed = Lexer::new(&source).flatten().all(|t| range.contains(&t.text.as_ptr()));
+    println!("\n所有记号都指向输入内部: {}", borrowed);
+
+    // 示例 12 的解析器只认单个空格
+    let words = "hello\tworld,  你好　世界";
+    let tokens: Vec<&str> = Lexer::new(words).flatten().map(|t| t.text).collect();
+    println!("\n{:?}", words);
+    println!("  按空格切分: {:?}", words.split(' ').filter(|w| !w.is_empty()).collect::<Vec<_>>());
+    println!("  词法分析:   {:?}", tokens);
+
+    println!("\n错误和恢复:");
+    let bad = "a = \"坏的\\q转义\" + \"没有结尾\nb = 1\u{7}";
+    let mut lexer = Lexer::new(bad);
+    for item in lexer.by_ref() {
+        match item {
+            Ok(token) => println!("  {:?} {:?}", token.kind, token.text),
+            Err(e) => println!("  错误: {}（区间 {:?}）", e, e.span()),
+        }
+    }
+    assert!(lexer.remaining().is_empty());
+
+    println!("\n要点:");
+    println!("  - Token<'a> 和 LexError<'a> 都借用输入，生命周期参数说明了这一点");
+    println!("  - 按字符前进、记录字节下标：&input[span] 不会切到汉字中间");
+    println!("  - 字符串的转义按需处理：unescape 返回 Cow，没有转义时不分配");
+    println!("  - 出错后跳过出错的部分，调用方可以一次收集所有错误");
+}
+
+/// 示例 16: 词法分析器 - 随机输入检查
+///
+/// 用固定种子生成大量随机输入（模糊测试的思路），检查不依赖具体输入的性质
+pub fn lexer_fuzz_checks() {
+    println!("\n=== 示例 16: 词法分析器 - 随机输入检查 ===");
+
+    use crate::util::XorShift;
+    use lexer::{LexError, Lexer, TokenKind};
+
+    // 故意包含容易出错的片段：引号、反斜杠、多字节字符、换行、控制字符
+    const PIECES: [&str; 32] = [
+        "a", "Z", "_", "变量", "名", "0", "9", "3.14", ".", "e", "E", "+", "-", "=", "==", "!", "<", "&", "|",
+        "\"", "\\", "\\u{", "}", "n", " ", "\t", "\n", "　", "，", "🦀", "\u{1}", "é",
+    ];
+
+    // 可重现的伪随机数
+    let mut rng = XorShift::new(0x9E37_79B9_7F4A_7C15);
+    let mut inputs = 0;
+    let mut tokens = 0;
+    let mut errors = 0;
+    for _ in 0..3000 {
+        let len = rng.below(24);
+        let input: String = (0..len).map(|_| *rng.pick(&PIECES)).collect();
+        let items: Vec<Result<_, LexError>> = Lexer::new(&input).collect();
+        inputs += 1;
+
+        // 每一项至少消耗一个字节，迭代一定会结束
+        assert!(items.len() <= input.len());
+
+        let mut last_end = 0;
+        let mut after_token = true;
+        for item in &items {
+            let span = match item {
+                Ok(token) => token.span.clone(),
+                Err(e) => e.span(),
+            };
+            // 区间递增、不重叠、落在字符边界上
+            assert!(span.start >= last_end && span.start <= span.end, "{:?}: {:?}", input, span);
+            assert!(input.is_char_boundary(span.start) && input.is_char_boundary(span.end));
+            // 记号之间只有空白；无效的转义位于字符串中间，前面是字符串的开头；
+            // 错误之后可能跳过了字符串剩下的部分，不检查
+            if after_token {
+                let gap = input[last_end..span.start].trim_start_matches(char::is_whitespace);
+                match item {
+                    Err(LexError::InvalidEscape { .. }) => assert!(gap.starts_with('"'), "{:?}", input),
+                    _ => assert!(gap.is_empty(), "{:?}: 间隔 {:?}", input, gap),
+                }
+            }
+            match item {
+                Ok(token) => {
+                    tokens += 1;
+                    assert_eq!(&input[span.clone()], token.text);
+                    assert!(!token.text.is_empty());
+                    // 单独分析一个记号的文本，得到同样的一个记号
+                    let alone: Vec<_> = Lexer::new(token.text).collect();
+                    assert_eq!(alone.len(), 1, "{:?}: {:?}", input, token.text);
+                    let again = alone[0].as_ref().expect("单独分析不应该出错");
+                    assert_eq!((again.kind, again.text), (token.kind, token.text));
+                    after_token = true;
+                }
+                Err(_) => {
+                    errors += 1;
+                    after_token = false;
+                }
+            }
+            last_end = span.end;
+        }
+        if after_token {
+            assert!(input[last_end..].chars().all(char::is_whitespace));
+        }
+    }
+    println!("✓ {} 个随机输入，{} 个记号，{} 个错误：区间有序、文本与区间一致、单独分析结果不变", inputs, tokens, errors);
+
+    // 字符串往返：任意内容转义后再分析，unescape 得到原来的内容
+    fn escape(text: &str) -> String {
+        let mut out = String::from("\"");
+        for c in text.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\t' => out.push_str("\\t"),
+                '\r' => out.push_str("\\r"),
+                c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+        out
+    }
+    const CONTENT: [&str; 12] = ["a", "汉", "字", " ", "\"", "\\", "\n", "\t", "\r", "\u{0}", "🦀", "{}"];
+    for _ in 0..1000 {
+        let len = rng.below(12);
+        let text: String = (0..len).map(|_| *rng.pick(&CONTENT)).collect();
+        let literal = escape(&text);
+        let items: Vec<_> = Lexer::new(&literal).collect();
+        assert_eq!(items.len(), 1, "{:?}", literal);
+        let token = items[0].as_ref().expect("转义后的字符串应该合法");
+        assert_eq!(token.kind, TokenKind::Str);
+        assert_eq!(token.unescape(), text);
+        // 不需要转义的内容直接借用
+        let needs_escape = literal.len() != text.len() + 2;
+        assert_eq!(matches!(token.unescape(), std::borrow::Cow::Owned(_)), needs_escape);
+    }
+    println!("✓ 1000 个随机字符串转义后再分析，unescape 还原了原来的内容");
+
+    // 几个具体的例子
+    let kinds = |input: &str| -> Vec<(TokenKind, String)> {
+        Lexer::new(input).flatten().map(|t| (t.kind, t.text.to_string())).collect()
+    };
+    use TokenKind::{Ident, Number, Punct};
+    let owned = |items: &[(TokenKind, &str)]| items.iter().map(|&(k, s)| (k, s.to_string())).collect::<Vec<_>>();
+    assert_eq!(kinds("1.max"), owned(&[(Number, "1"), (Punct, "."), (Ident, "max")]));
+    assert_eq!(kinds("2e 2e+ 2e+5"), owned(&[(Number, "2"), (Ident, "e"), (Number, "2"), (Ident, "e"), (Punct, "+"), (Number, "2e+5")]));
+    assert_eq!(kinds("a->b::c"), owned(&[(Ident, "a"), (Punct, "->"), (Ident, "b"), (Punct, "::"), (Ident, "c")]));
+    assert_eq!(kinds("计数器1，_x"), owned(&[(Ident, "计数器1"), (Punct, "，"), (Ident, "_x")]));
+    let errors: Vec<String> = Lexer::new("\"\\u{D800}\" \"\\u{110000}\" \"\\u{}\" \"x\\\n\"y\\").filter_map(Result::err).map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        [
+            "字节 1: 无效的转义序列 '\\u{D800}'",
+            "字节 12: 无效的转义序列 '\\u{110000}'",
+            "字节 25: 无效的转义序列 '\\u{}'",
+            "字节 31: 字符串缺少结尾的引号",
+            "字节 35: 字符串缺少结尾的引号",
+        ]
+    );
+    println!("✓ 数字的边界、双字符运算符、中文标识符和标点、无效的 \\u 转义、停在行尾的反斜杠");
+
+    println!("\n性质检查的思路:");
+    println!("  - 不关心具体的输出，只检查对所有输入都成立的性质");
+    println!("  - 随机片段里故意放了引号、反斜杠、多字节字符，最容易出边界错误");
+    println!("  - 种子固定，失败时可以重现同一个输入");
+}
+
+/// 示例 17: 分层配置
+///
+/// 示例 13 的配置管理器推广成四层：默认值 < 配置文件 < 环境变量 < 命令行
+pub fn layered_config() {
+    println!("\n=== 示例 17: 分层配置 ===");
+
+    use layered_config::{Layer, LayeredConfig, Source};
+    use std::borrow::Cow;
+
+    // 这些字符串在实际程序中来自 fs::read_to_string、std::env::vars 和 std::env::args
+    let file_text = String::from(
+        "# 应用配置\n\
+         name = \"演示服务\"\n\
+         port = 8080  # 开发环境\n\
+         \n\
+         [database]\n\
+         url = \"postgres://localhost/dev\"\n\
+         pool = 4\n",
+    );
+    let env_vars = [("APP_PORT", "9090"), ("APP_DATABASE__POOL", "16"), ("HOME", "/root")];
+    let args: Vec<String> = ["--debug", "--port", "7000", "--log.level=trace"].map(String::from).to_vec();
+
+    let defaults = Source::defaults(&[("name", "未命名"), ("port", "80"), ("debug", "false"), ("log.level", "info")]);
+    let config = LayeredConfig::new()
+        .with(Source::from_args(&args).expect("合法的参数"))
+        .with(Source::from_env("APP_", env_vars))
+        .with(Source::from_file("app.conf", &file_text).expect("合法的配置文件"))
+        .with(defaults);
+
+    println!("生效的配置（加入顺序是反的，结果只由层决定）:");
+    for (key, _) in config.snapshot(Layer::Cli) {
+        let layers = config.explain(key);
+        let (layer, entry) = layers.last().expect("snapshot 中的键");
+        let shadowed: Vec<String> = layers[..layers.len() - 1].iter().map(|(l, e)| format!("{}={}", l, e.value)).collect();
+        println!(
+            "  {:<14} = {:<28} {}（{}）{}",
+            key,
+            entry.value,
+            layer,
+            entry.origin,
+            if shadowed.is_empty() { String::new() } else { format!("  覆盖了 {}", shadowed.join(", ")) }
+        );
+    }
+
+    // 类型化的查询
+    let port = config.get::<u16>("port").unwrap();
+    let debug = config.get::<bool>("debug").unwrap();
+    let pool = config.get::<usize>("database.pool").unwrap();
+    println!("\nget::<u16>(\"port\")            = {}（{}，{}）", port.value, port.layer, port.origin);
+    println!("get::<bool>(\"debug\")          = {}（{}，{}）", debug.value, debug.layer, debug.origin);
+    println!("get::<usize>(\"database.pool\") = {}（{}，{}）", pool.value, pool.layer, pool.origin);
+
+    // 值借用加载时的文本：指针落在 file_text 内部，没有复制
+    let (_, url) = config.lookup("database.url").unwrap();
+    let inside = file_text.as_bytes().as_ptr_range().contains(&url.value.as_ptr());
+    println!("\ndatabase.url 借用配置文件的内容: {}，键是 {}", inside, match url.key {
+        Cow::Borrowed(_) => "借用的",
+        Cow::Owned(_) => "拼出来的（加上了段名）",
+    });
+
+    println!("\n环境变量带来的变化（配置文件 -> 环境变量）:");
+    for change in config.diff(Layer::File, Layer::Env) {
+        println!("  {}", change);
+    }
+    println!("所有层带来的变化（默认值 -> 命令行）:");
+    for change in config.diff(Layer::Default, Layer::Cli) {
+        println!("  {}", change);
+    }
+
+    println!("\n错误:");
+    let bad_args: Vec<String> = ["--port=eighty"].map(String::from).to_vec();
+    let bad = LayeredConfig::new().with(Source::from_args(&bad_args).unwrap());
+    println!("  {}", bad.get::<u16>("port").unwrap_err());
+    println!("  {}", config.get::<u16>("timeout").unwrap_err());
+    println!("  {}", Source::from_file("app.conf", "port 8080").unwrap_err());
+    let stray: Vec<String> = ["extra"].map(String::from).to_vec();
+    println!("  {}", Source::from_args(&stray).unwrap_err());
+
+    println!("\n要点:");
+    println!("  - Source<'a> 和 LayeredConfig<'a> 借用文件内容和参数，'a 保证它们先被释放");
+    println!("  - Cow<'a, str>：大部分值直接借用，只有改写过的键才分配");
+    println!("  - 层之间的顺序由 Layer 的 Ord 决定，与加入的顺序无关");
+    println!("  - 每个值都知道自己的出处，类型错误能指出是哪一行、哪个变量、哪个参数");
+}
+
+/// 示例 18: 分层配置 - 逐项检查
+///
+/// 层的优先级、各种来源的格式、借用、diff 和错误信息
+pub fn layered_config_checks() {
+    println!("\n=== 示例 18: 分层配置 - 逐项检查 ===");
+
+    use layered_config::{Change, Layer, LayerError, LayeredConfig, Source};
+    use std::borrow::Cow;
+
+    let file_text = "a = file\nb = file\n[s]\nk = \"带 = 号的值\"\n";
+    let env_vars = [("APP_B", "env"), ("APP_C", "env"), ("APP_S__K", "env"), ("APP_", "忽略"), ("OTHER", "忽略")];
+    let args: Vec<String> = ["--c", "cli", "--flag", "--d=", "--e=x=y"].map(String::from).to_vec();
+    let sources = || {
+        [
+            Source::defaults(&[("a", "default"), ("z", "default")]),
+            Source::from_file("t.conf", file_text).unwrap(),
+            Source::from_env("APP_", env_vars),
+            Source::from_args(&args).unwrap(),
+        ]
+    };
+
+    // 1. 各个来源的格式
+    let [defaults, file, env, cli] = sources();
+    let keys = |s: &Source| s.entries().iter().map(|e| format!("{}={}", e.key, e.value)).collect::<Vec<_>>();
+    assert_eq!(keys(&defaults), ["a=default", "z=default"]);
+    assert_eq!(keys(&file), ["a=file", "b=file", "s.k=带 = 号的值"]);
+    assert_eq!(keys(&env), ["b=env", "c=env", "s.k=env"]);
+    assert_eq!(keys(&cli), ["c=cli", "flag=true", "d=", "e=x=y"]);
+    assert_eq!(file.entries()[2].origin, "t.conf:4");
+    assert_eq!(env.entries()[0].origin, "APP_B");
+    assert_eq!(cli.entries()[0].origin, "--c");
+    println!("✓ 配置文件、环境变量、命令行参数的格式和出处");
+
+    // 行尾注释去掉，引号里的 # 保留
+    let commented = "port = 8080  # dev\ncolor = \"#ff0000\"  # 红色\n[db] # 数据库\nurl=x#y\n";
+    let commented = Source::from_file("t.conf", commented).unwrap();
+    assert_eq!(keys(&commented), ["port=8080", "color=#ff0000", "db.url=x"]);
+    println!("✓ 行尾的 # 注释不算在值里，引号中的 # 不是注释");
+
+    // 2. 借用：只有改写过的键是 Owned
+    assert!(file.entries().iter().all(|e| matches!(e.value, Cow::Borrowed(_))));
+    assert!(matches!(file.entries()[0].key, Cow::Borrowed(_)));
+    assert!(matches!(file.entries()[2].key, Cow::Owned(_)));
+    assert!(env.entries().iter().all(|e| matches!(e.key, Cow::Owned(_)) && matches!(e.origin, Cow::Borrowed(_))));
+    assert!(cli.entries().iter().all(|e| matches!(e.key, Cow::Borrowed(_)) && matches!(e.value, Cow::Borrowed(_))));
+    println!("✓ 值借用来源的文本，只有加上段名或转换过的键才分配");
+
+    // 3. 加入顺序的所有排列，结果都一样
+    let orders = [[0, 1, 2, 3], [3, 2, 1, 0], [2, 0, 3, 1], [1, 3, 0, 2]];
+    for order in orders {
+        let mut pending = sources().map(Some);
+        let config = order
+            .iter()
+            .fold(LayeredConfig::new(), |config, &i| config.with(pending[i].take().unwrap()));
+        let got: Vec<(&str, Layer)> = ["a", "b", "c", "s.k", "z"]
+            .iter()
+            .map(|key| {
+                let r = config.get_str(key).unwrap();
+                (r.value, r.layer)
+            })
+            .collect();
+        assert_eq!(
+            got,
+            [("file", Layer::File), ("env", Layer::Env), ("cli", Layer::Cli), ("env", Layer::Env), ("default", Layer::Default)]
+        );
+    }
+    println!("✓ 高层覆盖低层，与加入的顺序无关");
+
+    // 4. 同一层的多个来源，后加入的优先；同一个来源中重复的键，后出现的优先
+    let overlay = "a = local\n";
+    let config = LayeredConfig::new()
+        .with(Source::from_file("t.conf", "a = 1\na = 2\n").unwrap())
+        .with(Source::from_file("local.conf", overlay).unwrap());
+    assert_eq!(config.get_str("a").unwrap().origin, "local.conf:1");
+    let config = LayeredConfig::new().with(Source::from_file("t.conf", "a = 1\na = 2\n").unwrap());
+    assert_eq!(config.get::<i32>("a").unwrap().value, 2);
+    println!("✓ 同一层中后加入的来源优先，同一个来源中后出现的键优先");
+
+    // 5. 类型化查询和错误
+    let [defaults, file, env, cli] = sources();
+    let config = LayeredConfig::new().with(defaults).with(file).with(env).with(cli);
+    assert!(config.get::<bool>("flag").unwrap().value);
+    assert_eq!(config.get::<String>("d").unwrap().value, "");
+    assert_eq!(config.get::<u8>("zz"), Err(LayerError::Missing("zz".to_string())));
+    assert_eq!(
+        config.get::<u8>("a").unwrap_err().to_string(),
+        "配置项 a 的值 \"file\" 无效（配置文件，t.conf:1）: invalid digit found in string"
+    );
+    assert_eq!(
+        Source::from_file("t.conf", "# 注释\n\n= 1\n").unwrap_err().to_string(),
+        "t.conf:3: 应为 key = value，实际是 \"= 1\""
+    );
+    for bad in ["-x", "--", "--=1", "值"] {
+        let args = vec![bad.to_string()];
+        assert_eq!(Source::from_args(&args).unwrap_err(), LayerError::Argument(bad.to_string()));
+    }
+    println!("✓ FromStr 转换、缺少的键、错误中的出处");
+
+    // 6. explain 和 diff
+    let layers: Vec<Layer> = config.explain("a").iter().map(|&(layer, _)| layer).collect();
+    assert_eq!(layers, [Layer::Default, Layer::File]);
+    assert_eq!(
+        config.diff(Layer::File, Layer::Env),
+        [
+            Change { key: "b", old: Some("file"), new: "env", layer: Layer::Env },
+            Change { key: "c", old: None, new: "env", layer: Layer::Env },
+            Change { key: "s.k", old: Some("带 = 号的值"), new: "env", layer: Layer::Env },
+        ]
+    );
+    assert_eq!(config.diff(Layer::Cli, Layer::Cli), []);
+    // 值相同的覆盖不算变化
+    let same = LayeredConfig::new()
+        .with(Source::defaults(&[("port", "80")]))
+        .with(Source::from_env("APP_", [("APP_PORT", "80")]));
+    assert!(same.diff(Layer::Default, Layer::Env).is_empty());
+    assert_eq!(same.get_str("port").unwrap().layer, Layer::Env);
+    println!("✓ explain 列出被覆盖的层，diff 只列出生效值的变化");
+
+    println!("\n检查的性质:");
+    println!("  - 优先级只由层决定，同层看加入顺序");
+    println!("  - Cow 的 Borrowed / Owned 与预期一致：能借用的地方都没有复制");
+    println!("  - 错误信息带有出处，可以直接给用户看");
+}
+
+/// 示例 19: 零拷贝的 CSV 读取
+///
+/// 字段借用输入，只有需要去掉转义引号的字段才分配
+pub fn zero_copy_csv() {
+    println!("\n=== 示例 19: 零拷贝的 CSV 读取 ===");
+
+    use csv::{Headers, Reader};
+    use std::borrow::Cow;
+
+    let input = "姓名,城市,备注\r\n\
+                 张三,北京,普通字段\r\n\
+                 李四,\"上海, 浦东\",\"带逗号\"\r\n\
+                 王五,广州,\"他说 \"\"你好\"\"\"\r\n\
+                 \r\n\
+                 赵六,深圳,\"第一行\n第二行\"\n\
+                 钱七,杭州,最后一行没有换行";
+
+    for record in Reader::new(input) {
+        let record = record.unwrap();
+        let kinds: Vec<&str> = record
+            .fields
+            .iter()
+            .map(|f| match f {
+                Cow::Borrowed(_) => "借用",
+                Cow::Owned(_) => "分配",
+            })
+            .collect();
+        println!("  第 {} 行 {:?} {:?}", record.line, record.fields, kinds);
+    }
+
+    // 借用的字段指向输入内部
+    let inside = |s: &str| input.as_bytes().as_ptr_range().contains(&s.as_ptr());
+    let all: Vec<_> = Reader::new(input).map(Result::unwrap).collect();
+    let borrowed = all.iter().flat_map(|r| &r.fields).filter(|f| matches!(f, Cow::Borrowed(s) if inside(s))).count();
+    let total: usize = all.iter().map(|r| r.fields.len()).sum();
+    println!("\n{} 个字段中 {} 个直接借用输入", total, borrowed);
+
+    // 按列名取值
+    let mut reader = Reader::new(input);
+    let headers = Headers::read(&mut reader).unwrap();
+    println!("\n列名: {:?}", headers.names());
+    for record in reader {
+        let row = headers.row(record.unwrap()).unwrap();
+        println!("  {} 住在 {}", row.get("姓名").unwrap(), row.get("城市").unwrap());
+    }
+
+    println!("\n错误和恢复:");
+    let bad = "a,b\n1,2\n3,\"4\"x\n5,6\n7,8\"\n9,\"未闭合\n10,11\n";
+    for record in Reader::new(bad) {
+        match record {
+            Ok(record) => println!("  第 {} 行 {:?}", record.line, record.fields),
+            Err(e) => println!("  错误: {}", e),
+        }
+    }
+
+    println!("\n要点:");
+    println!("  - Record<'a> 的字段是 Cow<'a, str>，输入必须比所有记录活得更久");
+    println!("  - \"\" 转义需要把两个引号变成一个，只有这种字段才分配");
+    println!("  - 带引号的字段可以包含逗号和换行，行号按实际的行计算");
+    println!("  - 一行出错不影响后面的行，引号没有闭合时后面的内容无法确定，只能停下");
+}
+
+/// 示例 20: CSV - 随机数据检查
+///
+/// 随机字段写成 CSV 再读回来，内容、行号和借用情况都与预期一致
+pub fn csv_checks() {
+    println!("\n=== 示例 20: CSV - 随机数据检查 ===");
+
+    use crate::util::XorShift;
+    use csv::{CsvError, Headers, Reader, escape_field};
+    use std::borrow::Cow;
+
+    // 故意放进逗号、引号、换行、\r 和多字节字符
+    const PIECES: [&str; 10] = ["a", "中文", " ", ",", "\"", "\n", "\r\n", "\r", "x y", "🦀"];
+    let mut rng = XorShift::new(0x1234_5678_9ABC_DEF1);
+    let (mut records, mut allocated) = (0, 0);
+    for _ in 0..500 {
+        let rows: Vec<Vec<String>> = (0..1 + rng.below(5))
+            .map(|_| {
+                (0..1 + rng.below(4))
+                    .map(|_| (0..rng.below(4)).map(|_| *rng.pick(&PIECES)).collect())
+                    .collect()
+            })
+            .collect();
+        let newline = if rng.below(2) == 0 { "\n" } else { "\r\n" };
+        let mut text = String::new();
+        for row in &rows {
+            let fields: Vec<Cow<str>> = row.iter().map(|f| escape_field(f)).collect();
+            text.push_str(&fields.join(","));
+            text.push_str(newline);
+        }
+        // 最后一行的换行可有可无
+        if rng.below(2) == 0 {
+            text.truncate(text.len() - newline.len());
+        }
+
+        let parsed: Vec<_> = Reader::new(&text).collect::<Result<_, _>>().unwrap_or_else(|e| panic!("{}: {:?}", e, text));
+        assert_eq!(parsed.len(), rows.len(), "{:?}", text);
+        let mut line = 1;
+        for (record, row) in parsed.iter().zip(&rows) {
+            assert_eq!(&record.fields, row, "{:?}", text);
+            assert_eq!(record.line, line, "{:?}", text);
+            for (field, original) in record.fields.iter().zip(row) {
+                // 只有含引号的字段需要分配
+                assert_eq!(matches!(field, Cow::Owned(_)), original.contains('"'), "{:?}", original);
+                allocated += usize::from(matches!(field, Cow::Owned(_)));
+            }
+            line += 1 + row.iter().map(|f| f.matches('\n').count()).sum::<usize>();
+            records += 1;
+        }
+    }
+    println!("✓ 500 个随机文件，{} 条记录写出后读回，内容和行号不变，{} 个含引号的字段分配", records, allocated);
+
+    // 固定的边界情况
+    let fields = |text: &str| -> Vec<Vec<String>> {
+        Reader::new(text).map(|r| r.unwrap().fields.iter().map(|f| f.to_string()).collect()).collect()
+    };
+    assert_eq!(fields("a,,b\n,\n"), [vec!["a", "", "b"], vec!["", ""]]);
+    assert_eq!(fields("\"\"\n\"\"\"\"\n"), [vec![""], vec!["\""]]);
+    assert_eq!(fields("\n\r\n\na\n\n"), [vec!["a"]]);
+    assert_eq!(fields("a\rb,c\r\n"), [vec!["a\rb", "c"]]);
+    assert_eq!(fields(""), Vec::<Vec<String>>::new());
+    // 输入最后单独的 \r 也是行尾
+    assert_eq!(fields("a,b\r"), [vec!["a", "b"]]);
+    assert_eq!(fields("a,\"b\"\r"), [vec!["a", "b"]]);
+    assert_eq!(fields("a\r\nb\r\n\r"), [vec!["a"], vec!["b"]]);
+    println!("✓ 空字段、只有引号的字段、空行、单独的 \\r、输入最后的 \\r");
+
+    let errors = |text: &str| -> Vec<String> {
+        Reader::new(text).map(|r| r.map_or_else(|e| e.to_string(), |r| r.fields.join("|"))).collect()
+    };
+    assert_eq!(errors("a\"b,c\nd\n"), ["第 1 行第 1 个字段: 引号只能括住整个字段，字段中的引号要写成 \"\"", "d"]);
+    assert_eq!(errors("\"a\nb\" x\ny\n"), ["第 2 行第 1 个字段: 引号只能括住整个字段，字段中的引号要写成 \"\"", "y"]);
+    assert_eq!(errors("1\n2,\"3\n4\n"), ["1", "第 2 行: 引号没有闭合"]);
+    println!("✓ 错误的行号，出错后从下一行继续");
+
+    // 表头和类型化的取值
+    let text = "name,age,name\n张三,30,别名\n李四,abc,\n王五\n";
+    let mut reader = Reader::new(text);
+    let headers = Headers::read(&mut reader).unwrap();
+    assert_eq!(headers.index("name"), Ok(0));
+    assert_eq!(headers.index("email"), Err(CsvError::MissingColumn("email".to_string())));
+    let rows: Vec<_> = reader.map(|r| headers.row(r.unwrap())).collect();
+    let first = rows[0].as_ref().unwrap();
+    assert_eq!(first.parse::<u8>("age"), Ok(30));
+    assert!(matches!(first.field("name"), Ok(Cow::Borrowed("张三"))));
+    assert_eq!(
+        rows[1].as_ref().unwrap().parse::<u8>("age").unwrap_err().to_string(),
+        "第 3 行 age 列: 无法转换 \"abc\": invalid digit found in string"
+    );
+    assert_eq!(rows[2].as_ref().unwrap_err(), &CsvError::FieldCount { line: 4, expected: 3, found: 1 });
+    assert_eq!(Headers::read(&mut Reader::new("\n\n")).unwrap_err(), CsvError::Empty);
+    println!("✓ 按列名查找、重名的列取第一个、FromStr 转换、字段数不符");
+
+    println!("\n检查的思路:");
+    println!("  - 写出再读回：escape_field 和 Reader 互为逆运算");
+    println!("  - Cow::Owned 出现的条件是确定的：当且仅当字段含有引号");
+    println!("  - 跨行的字段让行号容易算错，单独检查");
+}
+
 /// 运行所有示例
 pub fn run_all_examples() {
     println!("\n╔════════════════════════════════════════╗");
@@ -614,6 +1182,12 @@ pub fn run_all_examples() {
     practical_parser();
     practical_config();
     practical_iterator();
+    zero_copy_lexer();
+    lexer_fuzz_checks();
+    layered_config();
+    layered_config_checks();
+    zero_copy_csv();
+    csv_checks();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  生命周期是 Rust 内存安全的关键！    ║");
@@ -0,0 +1,292 @@
+//! # 零拷贝的 CSV 读取
+//!
+//! 按 RFC 4180 读取逗号分隔的数据：
+//!
+//! - 字段可以用引号括起来，里面可以有逗号和换行，引号写成 `""`
+//! - 行尾可以是 `\n` 也可以是 `\r\n`，最后一行可以没有换行；完全空白的行跳过
+//! - 不带引号的字段中不能出现引号，结尾的引号后面只能是逗号或行尾
+//!
+//! 字段是 `Cow<'a, str>`：大部分字段直接借用输入，只有含 `""` 的字段需要去掉一个引号，
+//! 这时才分配。出错时返回带行号的错误，跳过出错的那一行后继续。
+//!
+//! `Headers` 把第一行当作列名，`Row<'h, 'a>` 同时借用表头（`'h`）和输入（`'a`），
+//! 按列名取值或用 `FromStr` 转换；实现 `FromRow` 的类型可以用 `read` 一行一行地读出来。
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    #[error("第 {line} 行: 引号没有闭合")]
+    UnterminatedQuote { line: usize },
+
+    #[error("第 {line} 行第 {field} 个字段: 引号只能括住整个字段，字段中的引号要写成 \"\"")]
+    StrayQuote { line: usize, field: usize },
+
+    #[error("第 {line} 行: 应有 {expected} 个字段，实际有 {found} 个")]
+    FieldCount { line: usize, expected: usize, found: usize },
+
+    #[error("输入为空，没有表头")]
+    Empty,
+
+    #[error("没有名为 {0:?} 的列")]
+    MissingColumn(String),
+
+    #[error("第 {line} 行 {column} 列: 无法转换 {value:?}: {reason}")]
+    Parse {
+        line: usize,
+        column: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// 一条记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<'a> {
+    pub fields: Vec<Cow<'a, str>>,
+    /// 记录开始的行号，从 1 开始；带引号的字段可以跨行
+    pub line: usize,
+}
+
+impl Record<'_> {
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.fields.get(index).map(|f| &**f)
+    }
+}
+
+/// 逐条产生记录的迭代器
+pub struct Reader<'a> {
+    input: &'a str,
+    /// 下一个字符的字节下标
+    pos: usize,
+    /// `pos` 所在的行号
+    line: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Reader { input, pos: 0, line: 1 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    /// 字段后面的分隔符：逗号返回 `false`，行尾或输入结束返回 `true`
+    fn separator(&mut self, field: usize) -> Result<bool, CsvError> {
+        let rest = self.rest();
+        let (len, end) = if rest.starts_with(',') {
+            (1, false)
+        } else if rest.starts_with("\r\n") {
+            (2, true)
+        } else if rest.starts_with('\n') || rest == "\r" {
+            // 最后一行以单独的 \r 结尾也算行尾
+            (1, true)
+        } else if rest.is_empty() {
+            (0, true)
+        } else {
+            return Err(CsvError::StrayQuote { line: self.line, field });
+        };
+        self.pos += len;
+        if end && len > 0 {
+            self.line += 1;
+        }
+        Ok(end)
+    }
+
+    /// 一个字段和它后面的分隔符；返回字段，以及这条记录是否结束
+    fn field(&mut self, line: usize, field: usize) -> Result<(Cow<'a, str>, bool), CsvError> {
+        let rest = self.rest();
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            // 找结尾的引号，跳过 "" 转义
+            let mut end = 0;
+            let mut escaped = false;
+            loop {
+                match quoted[end..].find('"') {
+                    None => return Err(CsvError::UnterminatedQuote { line }),
+                    Some(i) if quoted[end + i + 1..].starts_with('"') => {
+                        escaped = true;
+                        end += i + 2;
+                    }
+                    Some(i) => {
+                        end += i;
+                        break;
+                    }
+                }
+            }
+            let raw = &quoted[..end];
+            self.pos += end + 2;
+            self.line += raw.matches('\n').count();
+            match escaped {
+                true => Cow::Owned(raw.replace("\"\"", "\"")),
+                false => Cow::Borrowed(raw),
+            }
+        } else {
+            let len = rest.find([',', '\n']).unwrap_or(rest.len());
+            let mut raw = &rest[..len];
+            if len == rest.len() || rest[len..].starts_with('\n') {
+                raw = raw.strip_suffix('\r').unwrap_or(raw);
+            }
+            if raw.contains('"') {
+                return Err(CsvError::StrayQuote { line: self.line, field });
+            }
+            self.pos += raw.len();
+            Cow::Borrowed(raw)
+        };
+        let end = self.separator(field)?;
+        Ok((value, end))
+    }
+
+    /// 出错后跳到下一行；引号没有闭合时后面的内容都无法确定，直接结束
+    fn recover(&mut self, error: &CsvError) {
+        if let CsvError::UnterminatedQuote { .. } = error {
+            self.pos = self.input.len();
+            return;
+        }
+        match self.rest().find('\n') {
+            Some(i) => {
+                self.pos += i + 1;
+                self.line += 1;
+            }
+            None => self.pos = self.input.len(),
+        }
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Record<'a>, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // 跳过空行
+        loop {
+            let rest = self.rest();
+            let blank = if rest.starts_with("\r\n") {
+                2
+            } else if rest.starts_with('\n') || rest == "\r" {
+                1
+            } else if rest.is_empty() {
+                return None;
+            } else {
+                break;
+            };
+            self.pos += blank;
+            self.line += 1;
+        }
+        let line = self.line;
+        let mut fields = Vec::new();
+        loop {
+            match self.field(line, fields.len() + 1) {
+                Ok((field, end)) => {
+                    fields.push(field);
+                    if end {
+                        return Some(Ok(Record { fields, line }));
+                    }
+                }
+                Err(error) => {
+                    self.recover(&error);
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+/// 写出时需要引号的字段加上引号，其余原样借用
+pub fn escape_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) || field.is_empty() {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// 第一行的列名
+#[derive(Debug, Clone)]
+pub struct Headers<'a> {
+    names: Vec<Cow<'a, str>>,
+}
+
+impl<'a> Headers<'a> {
+    /// 从读取器中读出第一条记录作为表头
+    pub fn read(reader: &mut Reader<'a>) -> Result<Self, CsvError> {
+        let record = reader.next().ok_or(CsvError::Empty)??;
+        Ok(Headers { names: record.fields })
+    }
+
+    pub fn names(&self) -> &[Cow<'a, str>] {
+        &self.names
+    }
+
+    /// 列名对应的下标；重名时取第一个
+    pub fn index(&self, name: &str) -> Result<usize, CsvError> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| CsvError::MissingColumn(name.to_string()))
+    }
+
+    /// 把一条记录和表头配对，字段数必须和列数相同
+    pub fn row<'h>(&'h self, record: Record<'a>) -> Result<Row<'h, 'a>, CsvError> {
+        if record.fields.len() != self.names.len() {
+            return Err(CsvError::FieldCount {
+                line: record.line,
+                expected: self.names.len(),
+                found: record.fields.len(),
+            });
+        }
+        Ok(Row { headers: self, record })
+    }
+}
+
+/// 带表头的一行：借用表头 `'h` 和输入 `'a`，两者的生命周期互不相关
+#[derive(Debug, Clone)]
+pub struct Row<'h, 'a> {
+    headers: &'h Headers<'a>,
+    record: Record<'a>,
+}
+
+impl<'a> Row<'_, 'a> {
+    pub fn line(&self) -> usize {
+        self.record.line
+    }
+
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.headers.index(column).ok().and_then(|i| self.record.get(i))
+    }
+
+    /// 字段本身：借用输入的字段复制出来仍然借用输入，可以比这一行活得更久
+    pub fn field(&self, column: &str) -> Result<Cow<'a, str>, CsvError> {
+        let index = self.headers.index(column)?;
+        Ok(self.record.fields[index].clone())
+    }
+
+    /// 用 `FromStr` 转换，失败时报告行号、列名和原来的值
+    pub fn parse<T>(&self, column: &str) -> Result<T, CsvError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = &self.record.fields[self.headers.index(column)?];
+        value.parse().map_err(|e: T::Err| CsvError::Parse {
+            line: self.line(),
+            column: column.to_string(),
+            value: value.to_string(),
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// 可以从一行数据构造的类型，`'a` 让字段可以借用输入
+pub trait FromRow<'a>: Sized {
+    fn from_row(row: &Row<'_, 'a>) -> Result<Self, CsvError>;
+}
+
+/// 读出表头，然后逐行转换成 `T`；每一行的错误单独返回，不影响后面的行
+pub fn read<'a, T: FromRow<'a>>(input: &'a str) -> Result<impl Iterator<Item = Result<T, CsvError>>, CsvError> {
+    let mut reader = Reader::new(input);
+    let headers = Headers::read(&mut reader)?;
+    Ok(reader.map(move |record| T::from_row(&headers.row(record?)?)))
+}
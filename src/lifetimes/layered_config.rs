@@ -0,0 +1,312 @@
+//! # 分层配置
+//!
+//! 示例 13 的 `ConfigManager<'a>` 只有一层，`get` 在 `Vec` 里逐个比较。
+//! 实际的程序从多个地方读取配置，后面的层覆盖前面的层：
+//!
+//! ```text
+//! 内置默认值 < 配置文件 < 环境变量 < 命令行参数
+//! ```
+//!
+//! - 每个来源借用加载时的文本：配置文件中的值是文件内容的切片，命令行的值是参数的切片。
+//!   只有需要改写的部分才分配（带段名的键、由环境变量名转换来的键），所以用 `Cow<'a, str>`
+//! - `get::<u16>("port")` 用 `FromStr` 转换，结果带上来自哪一层、哪一行或哪个参数
+//! - `diff` 列出从一层到另一层，最终生效的配置有哪些变化
+//!
+//! `LayeredConfig<'a>` 不能比它借用的文件内容和参数活得更久，这由 `'a` 保证。
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// 配置的层，按优先级从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Layer::Default => "默认值",
+            Layer::File => "配置文件",
+            Layer::Env => "环境变量",
+            Layer::Cli => "命令行",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 一个配置项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub key: Cow<'a, str>,
+    pub value: Cow<'a, str>,
+    /// 出处：`app.conf:3`、`APP_PORT`、`--port=8080`
+    pub origin: Cow<'a, str>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LayerError {
+    #[error("{origin}: 应为 key = value，实际是 {line:?}")]
+    Syntax { origin: String, line: String },
+
+    #[error("命令行参数 {0:?} 应为 --key=value、--key value 或 --flag")]
+    Argument(String),
+
+    #[error("缺少配置项 {0}")]
+    Missing(String),
+
+    #[error("配置项 {key} 的值 {value:?} 无效（{layer}，{origin}）: {reason}")]
+    Invalid {
+        key: String,
+        value: String,
+        layer: Layer,
+        origin: String,
+        reason: String,
+    },
+}
+
+/// 一层中的一个来源，同一个来源中重复的键以最后一个为准
+#[derive(Debug, Clone)]
+pub struct Source<'a> {
+    pub layer: Layer,
+    entries: Vec<Entry<'a>>,
+}
+
+impl<'a> Source<'a> {
+    /// 内置默认值
+    pub fn defaults(pairs: &[(&'a str, &'a str)]) -> Self {
+        let entries = pairs
+            .iter()
+            .map(|&(key, value)| Entry {
+                key: Cow::Borrowed(key),
+                value: Cow::Borrowed(value),
+                origin: Cow::Borrowed("内置默认值"),
+            })
+            .collect();
+        Source { layer: Layer::Default, entries }
+    }
+
+    /// `key = value` 格式的配置文件
+    ///
+    /// `#` 之后到行尾是注释（引号里的 `#` 除外），`[server]` 之后的键加上 `server.` 前缀，
+    /// 值两边的引号去掉（不处理转义）
+    pub fn from_file(name: &str, text: &'a str) -> Result<Self, LayerError> {
+        let mut entries = Vec::new();
+        let mut section: Option<&'a str> = None;
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let origin = format!("{}:{}", name, i + 1);
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = Some(name.trim());
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => (key.trim(), value.trim()),
+                _ => {
+                    return Err(LayerError::Syntax {
+                        origin,
+                        line: line.to_string(),
+                    });
+                }
+            };
+            let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+            let key = match section {
+                Some(section) => Cow::Owned(format!("{}.{}", section, key)),
+                None => Cow::Borrowed(key),
+            };
+            entries.push(Entry {
+                key,
+                value: Cow::Borrowed(value),
+                origin: Cow::Owned(origin),
+            });
+        }
+        Ok(Source { layer: Layer::File, entries })
+    }
+
+    /// 以 `prefix` 开头的环境变量：`APP_PORT` → `port`，`APP_SERVER__HOST` → `server.host`
+    ///
+    /// 传入变量列表而不是直接读 `std::env`，调用方决定借用谁的字符串，也方便检查
+    pub fn from_env(prefix: &str, vars: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let entries = vars
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let rest = name.strip_prefix(prefix).filter(|rest| !rest.is_empty())?;
+                Some(Entry {
+                    key: Cow::Owned(rest.to_lowercase().replace("__", ".")),
+                    value: Cow::Borrowed(value),
+                    origin: Cow::Borrowed(name),
+                })
+            })
+            .collect();
+        Source { layer: Layer::Env, entries }
+    }
+
+    /// 命令行参数：`--key=value`、`--key value`，单独的 `--flag` 的值是 `true`
+    pub fn from_args(args: &'a [String]) -> Result<Self, LayerError> {
+        let mut entries = Vec::new();
+        let mut args = args.iter().map(String::as_str).peekable();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--").filter(|o| !o.is_empty() && !o.starts_with('=')) else {
+                return Err(LayerError::Argument(arg.to_string()));
+            };
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key, value),
+                None => match args.next_if(|next| !next.starts_with("--")) {
+                    Some(value) => (option, value),
+                    None => (option, "true"),
+                },
+            };
+            entries.push(Entry {
+                key: Cow::Borrowed(key),
+                value: Cow::Borrowed(value),
+                origin: Cow::Borrowed(arg),
+            });
+        }
+        Ok(Source { layer: Layer::Cli, entries })
+    }
+
+    pub fn entries(&self) -> &[Entry<'a>] {
+        &self.entries
+    }
+
+    fn lookup(&self, key: &str) -> Option<&Entry<'a>> {
+        self.entries.iter().rev().find(|e| e.key == key)
+    }
+}
+
+/// 去掉行尾注释：`port = 8080  # dev` → `port = 8080  `；`"a # b"` 里的 `#` 不算注释
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// 查询结果：值和它的出处，出处借用配置本身
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved<'s, T> {
+    pub value: T,
+    pub layer: Layer,
+    pub origin: &'s str,
+}
+
+/// 从一层到另一层，最终生效的值的一处变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<'s> {
+    pub key: &'s str,
+    /// 之前没有这个键时为 `None`
+    pub old: Option<&'s str>,
+    pub new: &'s str,
+    /// 新值来自哪一层
+    pub layer: Layer,
+}
+
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.old {
+            Some(old) => write!(f, "~ {}: {:?} -> {:?}（{}）", self.key, old, self.new, self.layer),
+            None => write!(f, "+ {} = {:?}（{}）", self.key, self.new, self.layer),
+        }
+    }
+}
+
+/// 按层合并的配置
+#[derive(Debug, Default)]
+pub struct LayeredConfig<'a> {
+    /// 按层排序；同一层中后加入的排在后面
+    sources: Vec<Source<'a>>,
+}
+
+impl<'a> LayeredConfig<'a> {
+    pub fn new() -> Self {
+        LayeredConfig { sources: Vec::new() }
+    }
+
+    /// 加入一个来源，加入的顺序不影响层之间的优先级；同一层的多个来源，后加入的优先
+    pub fn with(mut self, source: Source<'a>) -> Self {
+        let at = self.sources.partition_point(|s| s.layer <= source.layer);
+        self.sources.insert(at, source);
+        self
+    }
+
+    /// 最终生效的配置项：优先级最高的层中最后出现的那一个
+    pub fn lookup(&self, key: &str) -> Option<(Layer, &Entry<'a>)> {
+        self.sources.iter().rev().find_map(|s| s.lookup(key).map(|e| (s.layer, e)))
+    }
+
+    /// 原始字符串，不做转换，借用配置本身
+    pub fn get_str(&self, key: &str) -> Option<Resolved<'_, &str>> {
+        self.lookup(key).map(|(layer, entry)| Resolved {
+            value: &*entry.value,
+            layer,
+            origin: &entry.origin,
+        })
+    }
+
+    /// 按 `FromStr` 转换；转换失败时报告值来自哪一层、哪个出处
+    pub fn get<T>(&self, key: &str) -> Result<Resolved<'_, T>, LayerError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let raw = self.get_str(key).ok_or_else(|| LayerError::Missing(key.to_string()))?;
+        match raw.value.parse() {
+            Ok(value) => Ok(Resolved {
+                value,
+                layer: raw.layer,
+                origin: raw.origin,
+            }),
+            Err(e) => Err(LayerError::Invalid {
+                key: key.to_string(),
+                value: raw.value.to_string(),
+                layer: raw.layer,
+                origin: raw.origin.to_string(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    /// 定义了这个键的所有层，从低到高；最后一个是生效的值，前面的都被覆盖了
+    pub fn explain(&self, key: &str) -> Vec<(Layer, &Entry<'a>)> {
+        self.sources.iter().filter_map(|s| s.lookup(key).map(|e| (s.layer, e))).collect()
+    }
+
+    /// 只用不高于 `up_to` 的层时，每个键生效的值和它所在的层
+    pub fn snapshot(&self, up_to: Layer) -> BTreeMap<&str, (Layer, &str)> {
+        let mut view = BTreeMap::new();
+        for source in self.sources.iter().take_while(|s| s.layer <= up_to) {
+            for entry in &source.entries {
+                view.insert(&*entry.key, (source.layer, &*entry.value));
+            }
+        }
+        view
+    }
+
+    /// 从只用不高于 `from` 的层，到只用不高于 `to` 的层，生效的值有哪些变化，按键排序
+    ///
+    /// 高层只会覆盖或者增加键，不会删除；值相同的覆盖不算变化
+    pub fn diff(&self, from: Layer, to: Layer) -> Vec<Change<'_>> {
+        let before = self.snapshot(from);
+        self.snapshot(to)
+            .into_iter()
+            .filter_map(|(key, (layer, new))| {
+                let old = before.get(key).map(|&(_, old)| old);
+                (old != Some(new)).then_some(Change { key, old, new, layer })
+            })
+            .collect()
+    }
+}
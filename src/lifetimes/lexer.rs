@@ -0,0 +1,261 @@
+//! # 零拷贝的词法分析器
+//!
+//! 示例 12 的 `Parser<'a>` 只按单个空格切分，用 `starts_with(' ')` 逐字节前进。
+//! 这里是一个通用的词法分析器，`Token<'a>` 中的文本都是输入的切片，不复制任何数据：
+//!
+//! - 标识符：字母（包括汉字）或 `_` 开头，后面是字母、数字或 `_`
+//! - 数字：`42`、`3.14`、`6.02e23`、`1E-9`
+//! - 字符串：`"..."`，支持 `\" \\ \n \t \r \0 \u{XXXX}`，不能跨行
+//! - 标点和其他符号：`== != <= >= && || -> => ::` 作为一个记号，其余每个字符一个记号（包括 `，` `。`）
+//! - 空白：`char::is_whitespace`，包括全角空格 U+3000，直接跳过
+//!
+//! 每个记号都带有字节区间，`&input[span]` 就是记号的文本。按字符前进，不会切到多字节字符的中间。
+//! 遇到错误时返回 `Err` 并跳过出错的部分，之后的记号照常产生。
+
+use std::borrow::Cow;
+use std::ops::Range;
+use thiserror::Error;
+
+/// 由两个字符组成的运算符，优先于单个字符匹配
+const OPERATORS: [&str; 9] = ["==", "!=", "<=", ">=", "&&", "||", "->", "=>", "::"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Ident,
+    Number,
+    /// 文本包括两边的引号，转义没有处理，用 `Token::unescape` 取得内容
+    Str,
+    Punct,
+}
+
+/// 一个记号，`text` 借用自输入
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// 字节区间，`&input[span] == text`
+    pub span: Range<usize>,
+}
+
+impl<'a> Token<'a> {
+    /// 字符串的内容：没有转义时借用输入，有转义时才分配；其他记号返回原文
+    pub fn unescape(&self) -> Cow<'a, str> {
+        if self.kind != TokenKind::Str {
+            return Cow::Borrowed(self.text);
+        }
+        let inner = &self.text[1..self.text.len() - 1];
+        if !inner.contains('\\') {
+            return Cow::Borrowed(inner);
+        }
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            // 转义在词法分析时已经检查过
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some('0') => out.push('\0'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                    let code = u32::from_str_radix(&hex, 16).expect("已检查的 \\u 转义");
+                    out.push(char::from_u32(code).expect("已检查的 \\u 转义"));
+                }
+                Some(c) => out.push(c),
+                None => unreachable!("字符串不会以单独的 \\ 结尾"),
+            }
+        }
+        Cow::Owned(out)
+    }
+}
+
+/// 词法错误，区间同样是字节下标
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LexError<'a> {
+    #[error("字节 {}: 无法识别的字符 {ch:?}", .span.start)]
+    UnexpectedChar { ch: char, span: Range<usize> },
+
+    #[error("字节 {}: 字符串缺少结尾的引号", .span.start)]
+    UnterminatedString { span: Range<usize> },
+
+    #[error("字节 {}: 无效的转义序列 '{escape}'", .span.start)]
+    InvalidEscape { escape: &'a str, span: Range<usize> },
+}
+
+impl LexError<'_> {
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            LexError::UnexpectedChar { span, .. }
+            | LexError::UnterminatedString { span }
+            | LexError::InvalidEscape { span, .. } => span.clone(),
+        }
+    }
+}
+
+/// 逐个产生记号的迭代器
+pub struct Lexer<'a> {
+    input: &'a str,
+    /// 下一个字符的字节下标，总是落在字符边界上
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Lexer { input, pos: 0 }
+    }
+
+    /// 还没有处理的部分
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.remaining().chars().nth(1)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat_while(&mut self, pred: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&pred) {
+            self.bump();
+        }
+    }
+
+    fn token(&self, kind: TokenKind, start: usize) -> Token<'a> {
+        Token {
+            kind,
+            text: &self.input[start..self.pos],
+            span: start..self.pos,
+        }
+    }
+
+    /// 小数点和指数只有后面跟着数字时才算数字的一部分，`1.max` 是 `1` `.` `max`
+    fn number(&mut self, start: usize) -> Token<'a> {
+        self.eat_while(|c| c.is_ascii_digit());
+        if self.peek() == Some('.') && self.peek_second().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+            self.eat_while(|c| c.is_ascii_digit());
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let rest = &self.remaining()[1..];
+            let digits = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+            if digits.starts_with(|c: char| c.is_ascii_digit()) {
+                self.pos = self.input.len() - digits.len();
+                self.eat_while(|c| c.is_ascii_digit());
+            }
+        }
+        self.token(TokenKind::Number, start)
+    }
+
+    fn string(&mut self, start: usize) -> Result<Token<'a>, LexError<'a>> {
+        self.bump();
+        loop {
+            match self.peek() {
+                None | Some('\n') => {
+                    return Err(LexError::UnterminatedString { span: start..self.pos });
+                }
+                Some('"') => {
+                    self.bump();
+                    return Ok(self.token(TokenKind::Str, start));
+                }
+                Some('\\') => {
+                    let escape_start = self.pos;
+                    self.bump();
+                    if !self.escape() {
+                        // 转义停在换行或输入结尾：字符串没有结束，报告缺少引号
+                        if matches!(self.peek(), None | Some('\n')) {
+                            return Err(LexError::UnterminatedString { span: start..self.pos });
+                        }
+                        let span = escape_start..self.pos;
+                        // 跳过字符串剩下的部分，从结尾的引号之后继续
+                        self.eat_while(|c| c != '"' && c != '\n');
+                        if self.peek() == Some('"') {
+                            self.bump();
+                        }
+                        return Err(LexError::InvalidEscape {
+                            escape: &self.input[span.clone()],
+                            span,
+                        });
+                    }
+                }
+                Some(_) => {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    /// `\` 之后的部分；无效时停在已经读过的位置
+    fn escape(&mut self) -> bool {
+        match self.peek() {
+            Some('"' | '\\' | 'n' | 't' | 'r' | '0') => {
+                self.bump();
+                true
+            }
+            Some('u') => {
+                self.bump();
+                if self.peek() != Some('{') {
+                    return false;
+                }
+                self.bump();
+                let hex_start = self.pos;
+                self.eat_while(|c| c.is_ascii_hexdigit());
+                let hex = &self.input[hex_start..self.pos];
+                // 有结尾的 } 时把它算进转义，错误信息显示完整的 \u{...}
+                if self.peek() != Some('}') {
+                    return false;
+                }
+                self.bump();
+                (1..=6).contains(&hex.len()) && u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).is_some()
+            }
+            // 换行不属于转义，留给 string 报告缺少引号
+            Some('\n') | None => false,
+            Some(_) => {
+                self.bump();
+                false
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, LexError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.eat_while(char::is_whitespace);
+        let start = self.pos;
+        let c = self.peek()?;
+        let item = if c == '_' || c.is_alphabetic() {
+            self.eat_while(|c| c == '_' || c.is_alphanumeric());
+            Ok(self.token(TokenKind::Ident, start))
+        } else if c.is_ascii_digit() {
+            Ok(self.number(start))
+        } else if c == '"' {
+            self.string(start)
+        } else if c.is_control() {
+            self.bump();
+            Err(LexError::UnexpectedChar { ch: c, span: start..self.pos })
+        } else {
+            match OPERATORS.iter().find(|op| self.remaining().starts_with(*op)) {
+                Some(op) => self.pos += op.len(),
+                None => {
+                    self.bump();
+                }
+            }
+            Ok(self.token(TokenKind::Punct, start))
+        };
+        Some(item)
+    }
+}
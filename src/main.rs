@@ -16,6 +16,12 @@
 // mod modules_and_packages;
 mod concurrency;
 
+// 各模块共用的小工具（随机数、时钟），始终启用
+mod util;
+
+// 带源码片段的诊断渲染器，错误处理和枚举模块共用，始终启用
+mod diagnostic;
+
 fn main() {
     // 运行所有变量与可变性的教学示例
     // variables_and_mutability::run_all_examples();
@@ -38,13 +44,16 @@ fn main() {
     // 运行所有引用与借用的教学示例
     // references_and_borrowing::run_all_examples();
 
-    // 运行所有结构体的教学示例
+    // 运行所有结构体的教学示例（银行账户 Actor 的示例需要同时启用 mod concurrency）
     // structs::run_all_examples();
 
     // 运行所有枚举的教学示例
     // enums::run_all_examples();
 
-    // 运行所有集合类型的教学示例
+    // 运行表达式计算器 REPL（交互式，输入 :quit 退出）
+    // enums::calc::repl(std::io::stdin().lock(), std::io::stdout()).unwrap();
+
+    // 运行所有集合类型的教学示例（成绩管理的示例需要同时启用 mod lifetimes）
     // collections::run_all_examples();
 
     // 运行所有错误处理的教学示例
@@ -56,10 +65,10 @@ fn main() {
     // 运行所有生命周期的教学示例
     // lifetimes::run_all_examples();
 
-    // 运行所有智能指针与包装类型的教学示例
+    // 运行所有智能指针与包装类型的教学示例（arena 表达式树的示例需要同时启用 mod enums）
     // smart_pointers::run_all_examples();
 
-    // 运行所有闭包与迭代器的教学示例
+    // 运行所有闭包与迭代器的教学示例（数据处理管道的示例需要同时启用 mod lifetimes）
     // closures_and_iterators::run_all_examples();
 
     // 运行所有模块系统与包管理的教学示例
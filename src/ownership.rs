@@ -1,6 +1,8 @@
 // Rust 所有权规则教学代码
 // 主题：所有权、移动、克隆、复制
 
+pub mod alloc_counter;
+
 /// 示例 1: 所有权基本规则
 /// Rust 的三条所有权规则
 pub fn ownership_rules() {
@@ -311,27 +313,130 @@ pub fn practical_builder_pattern() {
     println!("配置: name = {}, value = {}", config.name, config.value);
 }
 
+/// 示例 16: 测量分配 - 移动不分配，克隆才分配
+///
+/// 需要 `cargo run --features alloc-stats`
+pub fn measuring_allocations() {
+    println!("\n=== 示例 16: 测量分配 - 移动不分配，克隆才分配 ===");
+
+    use alloc_counter::measure;
+
+    if !alloc_counter::is_enabled() {
+        println!("没有启用计数分配器，请用 cargo run --features alloc-stats 运行");
+        return;
+    }
+
+    // 移动只复制栈上的 (指针, 长度, 容量)，堆上的数据原地不动
+    let s1 = String::from("hello");
+    let (s2, moved) = measure(move || s1);
+    println!("移动 String:        {}", moved);
+    assert!(moved.is_zero());
+
+    let (s3, cloned) = measure(|| s2.clone());
+    println!("克隆 String:        {}", cloned);
+    assert_eq!((cloned.allocations, cloned.bytes_allocated), (1, s2.len()));
+
+    let x = 5;
+    let (_, copied) = measure(|| x);
+    println!("复制 i32:           {}", copied);
+    assert!(copied.is_zero());
+
+    let v1 = vec![1, 2, 3];
+    let (v2, moved) = measure(move || v1);
+    let (_v3, cloned) = measure(|| v2.clone());
+    println!("移动 Vec:           {}", moved);
+    println!("克隆 Vec:           {}", cloned);
+    assert!(moved.is_zero());
+    assert_eq!((cloned.allocations, cloned.bytes_allocated), (1, 3 * std::mem::size_of::<i32>()));
+
+    let (b1, boxed) = measure(|| Box::new(5));
+    let (b2, moved) = measure(move || b1);
+    let ((), dropped) = measure(move || drop(b2));
+    println!("Box::new(5):        {}", boxed);
+    println!("移动 Box:           {}", moved);
+    println!("丢弃 Box:           {}", dropped);
+    assert_eq!(boxed.allocations, 1);
+    assert!(moved.is_zero());
+    assert_eq!((dropped.deallocations, dropped.bytes_freed), (1, 4));
+
+    // 把所有权交给函数：函数结束时释放
+    fn takes_ownership(s: String) -> usize {
+        s.len()
+    }
+    let (_, passed) = measure(move || takes_ownership(s3));
+    println!("传给函数并在其中丢弃: {}", passed);
+    assert_eq!((passed.allocations, passed.deallocations), (0, 1));
+
+    // + 复用左边的缓冲区，format! 总是新建一个 String
+    let left = String::from("Hello, ");
+    let right = String::from("world!");
+    let (joined, plus) = measure(move || left + &right);
+    let (formatted, formatting) = measure(|| format!("{}{}", joined, "!"));
+    println!("String + &str:      {}", plus);
+    println!("format!:            {}", formatting);
+    assert_eq!(plus.allocations, 0);
+    assert!(formatting.allocations >= 1);
+    assert_eq!(formatted, "Hello, world!!");
+
+    // 预留容量可以避免反复扩容
+    let (_, growing) = measure(|| {
+        let mut v = Vec::new();
+        for i in 0..1000 {
+            v.push(i);
+        }
+        v
+    });
+    let (_, reserved) = measure(|| {
+        let mut v = Vec::with_capacity(1000);
+        v.extend(0..1000);
+        v
+    });
+    println!("逐个 push 1000 个:  {}", growing);
+    println!("with_capacity:      {}", reserved);
+    assert!(growing.reallocations > 0);
+    // 扩容不算释放：只 push 的代码没有一次释放，也不会显示释放了多少字节
+    assert_eq!((growing.deallocations, growing.bytes_freed), (0, 0));
+    assert!(growing.realloc_bytes_to > growing.realloc_bytes_from);
+    assert_eq!((reserved.allocations, reserved.reallocations), (1, 0));
+
+    println!("\n测量结果:");
+    println!("  - 移动、复制、把 Box 换个变量名都是 0 次分配");
+    println!("  - clone 按数据大小分配一次，丢弃时释放同样多的字节");
+    println!("  - String + &str 复用左边的缓冲区，format! 总是分配新的");
+    println!("  - 扩容是重新分配，单独统计前后的大小，不算作释放");
+    println!("  - 每个示例后面的 [分配统计] 统计整个示例，包括打印时可能发生的分配");
+}
+
+/// 运行一个示例；启用了 alloc-stats 时打印它的分配统计
+fn run_measured(example: fn()) {
+    let ((), stats) = alloc_counter::measure(example);
+    if alloc_counter::is_enabled() {
+        println!("  [分配统计] {}", stats);
+    }
+}
+
 /// 运行所有示例
 pub fn run_all_examples() {
     println!("╔════════════════════════════════════════╗");
     println!("║  Rust 所有权规则教学代码             ║");
     println!("╚════════════════════════════════════════╝");
     
-    ownership_rules();
-    move_semantics();
-    clone_semantics();
-    copy_semantics();
-    ownership_and_functions();
-    return_values_and_ownership();
-    return_multiple_values();
-    ownership_transfer_timing();
-    partial_move();
-    ownership_and_scope();
-    ownership_with_vec();
-    ownership_with_box();
-    practical_string_concatenation();
-    practical_swap_values();
-    practical_builder_pattern();
+    run_measured(ownership_rules);
+    run_measured(move_semantics);
+    run_measured(clone_semantics);
+    run_measured(copy_semantics);
+    run_measured(ownership_and_functions);
+    run_measured(return_values_and_ownership);
+    run_measured(return_multiple_values);
+    run_measured(ownership_transfer_timing);
+    run_measured(partial_move);
+    run_measured(ownership_and_scope);
+    run_measured(ownership_with_vec);
+    run_measured(ownership_with_box);
+    run_measured(practical_string_concatenation);
+    run_measured(practical_swap_values);
+    run_measured(practical_builder_pattern);
+    measuring_allocations();
     
     println!("\n╔════════════════════════════════════════╗");
     println!("║  所有权是 Rust 最独特的特性！       ║");
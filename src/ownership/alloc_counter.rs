@@ -0,0 +1,146 @@
+//! # 计数的全局分配器
+//!
+//! 所有权的示例里常说"移动不分配内存，克隆会分配"，这里把它测出来。
+//! `CountingAllocator` 把每次请求转交给系统分配器，同时累计次数和字节数。
+//!
+//! 默认不启用，需要打开 `alloc-stats` feature：
+//!
+//! ```text
+//! cargo run --features alloc-stats
+//! ```
+//!
+//! 计数是全局的，其他线程的分配也会被计入；所有权的示例都是单线程的，不受影响。
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static DEALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static REALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static BYTES_FREED: AtomicUsize = AtomicUsize::new(0);
+static REALLOC_BYTES_FROM: AtomicUsize = AtomicUsize::new(0);
+static REALLOC_BYTES_TO: AtomicUsize = AtomicUsize::new(0);
+
+/// 只在启用 feature 时编译，否则分配器既不安装也不存在
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+
+    /// 包装系统分配器，只增加计数
+    pub struct CountingAllocator;
+
+    // 分配器里不能再分配内存（会递归），只做原子加法
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES_ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES_ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+            unsafe { System.alloc_zeroed(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES_FREED.fetch_add(layout.size(), Ordering::Relaxed);
+            unsafe { System.dealloc(ptr, layout) }
+        }
+
+        /// 扩容或缩小：单独计数，旧的和新的大小也单独累计，
+        /// 不混进分配和释放，否则只扩容的代码会显示"释放了 N 字节"却没有一次释放
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            REALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            REALLOC_BYTES_FROM.fetch_add(layout.size(), Ordering::Relaxed);
+            REALLOC_BYTES_TO.fetch_add(new_size, Ordering::Relaxed);
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+}
+
+/// 是否启用了计数（`alloc-stats` feature）
+pub fn is_enabled() -> bool {
+    cfg!(feature = "alloc-stats")
+}
+
+/// 某一时刻的累计值，或两个时刻之间的差
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub reallocations: usize,
+    pub bytes_allocated: usize,
+    pub bytes_freed: usize,
+    /// 所有重新分配之前的大小之和
+    pub realloc_bytes_from: usize,
+    /// 所有重新分配之后的大小之和
+    pub realloc_bytes_to: usize,
+}
+
+impl AllocStats {
+    /// 从程序开始到现在的累计值
+    pub fn now() -> Self {
+        AllocStats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+            reallocations: REALLOCATIONS.load(Ordering::Relaxed),
+            bytes_allocated: BYTES_ALLOCATED.load(Ordering::Relaxed),
+            bytes_freed: BYTES_FREED.load(Ordering::Relaxed),
+            realloc_bytes_from: REALLOC_BYTES_FROM.load(Ordering::Relaxed),
+            realloc_bytes_to: REALLOC_BYTES_TO.load(Ordering::Relaxed),
+        }
+    }
+
+    /// `earlier` 之后发生的部分
+    pub fn since(&self, earlier: &AllocStats) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations - earlier.allocations,
+            deallocations: self.deallocations - earlier.deallocations,
+            reallocations: self.reallocations - earlier.reallocations,
+            bytes_allocated: self.bytes_allocated - earlier.bytes_allocated,
+            bytes_freed: self.bytes_freed - earlier.bytes_freed,
+            realloc_bytes_from: self.realloc_bytes_from - earlier.realloc_bytes_from,
+            realloc_bytes_to: self.realloc_bytes_to - earlier.realloc_bytes_to,
+        }
+    }
+
+    /// 没有任何分配、释放或重新分配
+    pub fn is_zero(&self) -> bool {
+        *self == AllocStats::default()
+    }
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "分配 {} 次 / {} 字节，释放 {} 次 / {} 字节",
+            self.allocations, self.bytes_allocated, self.deallocations, self.bytes_freed
+        )?;
+        if self.reallocations > 0 {
+            write!(
+                f,
+                "，重新分配 {} 次 / {} → {} 字节",
+                self.reallocations, self.realloc_bytes_from, self.realloc_bytes_to
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 执行 `f`，返回它的结果和期间的分配统计
+///
+/// 返回值会被移出闭包，它持有的内存算作"分配了但还没释放"
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, AllocStats) {
+    let before = AllocStats::now();
+    let result = f();
+    let stats = AllocStats::now().since(&before);
+    (result, stats)
+}
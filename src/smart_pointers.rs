@@ -8,6 +8,17 @@ use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::borrow::Cow;
 use std::ops::Deref;
+use crate::util::XorShift;
+
+pub mod arena;
+pub mod arena_expr;
+pub mod cache;
+pub mod graph;
+pub mod linked_list;
+pub mod my_arc;
+pub mod my_rc;
+pub mod normalize;
+pub mod raw_list;
 
 /// 示例 1: Box<T> - 堆分配
 ///
@@ -81,6 +92,7 @@ pub fn box_recursive_types() {
     println!("  - 编译器需要知道类型的大小");
     println!("  - Box 的大小是固定的（指针大小）");
     println!("  - 可以创建无限嵌套的数据结构");
+    println!("  - 每个节点都是一次单独的堆分配，另一种做法（arena）见示例 25-27");
 }
 
 /// 示例 3: Rc<T> - 引用计数
@@ -217,6 +229,7 @@ pub fn rc_refcell_combination() {
     println!("  - 多个所有者需要修改数据");
     println!("  - 图、树等复杂数据结构");
     println!("  - 只能用于单线程");
+    println!("  - 有前后指针的双向链表见示例 28-30");
 }
 
 /// 示例 7: Cell<T> - 简单的内部可变性
@@ -390,6 +403,7 @@ pub fn cow_practical() {
     println!("  - 配置文件处理");
     println!("  - 文本替换和过滤");
     println!("  - 路径规范化");
+    println!("  - 由多个 Cow 步骤组成的文本规范化流水线见示例 31-32");
 }
 
 /// 示例 12: 自定义智能指针 - 基础
@@ -511,6 +525,7 @@ pub fn custom_smart_pointer_rc() {
     println!("  - 共享的引用计数器");
     println!("  - Clone trait 增加计数");
     println!("  - Drop trait 减少计数并清理");
+    println!("  - 注意: 计数单独分配、没有 Weak，完整实现见示例 22-24");
 }
 
 /// 示例 14: 实际应用 - 图数据结构
@@ -557,6 +572,7 @@ pub fn practical_graph() {
     println!("\n图数据结构需要:");
     println!("  - Rc: 多个节点可以指向同一个节点");
     println!("  - RefCell: 可以修改邻居列表");
+    println!("  - 注意: 邻居是强引用，图中有环就会内存泄漏，改进版见示例 16");
 }
 
 /// 示例 15: 实际应用 - 缓存系统
@@ -608,6 +624,1473 @@ pub fn practical_cache() {
     println!("  - Arc<Mutex<T>>: 线程安全的共享可变数据");
     println!("  - 可以在多个地方访问同一缓存");
     println!("  - 适合多线程环境");
+    println!("  - 注意: 没有容量上限，也不会过期，有界缓存见示例 19-21");
+}
+
+/// 示例 16: 图 - 用 Weak 打破引用环
+///
+/// 对比示例 14 的写法（边是 Rc）和 graph::RcGraph（边是 Weak）在有环时的释放情况
+pub fn graph_weak_edges() {
+    println!("\n=== 示例 16: 图 - 用 Weak 打破引用环 ===");
+
+    use graph::RcGraph;
+    use std::rc::Weak;
+
+    // 示例 14 的写法：邻居是强引用
+    struct StrongNode {
+        neighbors: RefCell<Vec<Rc<StrongNode>>>,
+    }
+
+    let a = Rc::new(StrongNode { neighbors: RefCell::new(Vec::new()) });
+    let b = Rc::new(StrongNode { neighbors: RefCell::new(Vec::new()) });
+    a.neighbors.borrow_mut().push(Rc::clone(&b));
+    b.neighbors.borrow_mut().push(Rc::clone(&a));
+    let watch_a = Rc::downgrade(&a);
+    drop(a);
+    drop(b);
+    let leaked = watch_a.upgrade();
+    println!("强引用边 a ⇄ b，丢弃所有句柄后 a 还活着: {}", leaked.is_some());
+    assert!(leaked.is_some());
+    // 手动打破环，这个例子本身才不会泄漏
+    if let Some(a) = leaked {
+        a.neighbors.borrow_mut().clear();
+    }
+    assert!(watch_a.upgrade().is_none());
+    println!("手动清空邻居列表打破环之后才被释放");
+
+    // RcGraph：图持有节点，边是 Weak
+    let mut graph = RcGraph::new();
+    let x = graph.add_node("x");
+    let y = graph.add_node("y");
+    let z = graph.add_node("z");
+    graph.connect(x.id(), y.id(), 1);
+    graph.connect(y.id(), z.id(), 1);
+    graph.connect(z.id(), x.id(), 1);
+    graph.connect(z.id(), z.id(), 1); // 自环
+
+    println!("\nRcGraph: x → y → z → x，z → z");
+    for node in [&x, &y, &z] {
+        let neighbors: Vec<String> = node.neighbors().iter().map(|n| n.name().to_string()).collect();
+        println!(
+            "  {} (id {:?}) 的邻居: {:?}，强引用计数 {}",
+            node.name(),
+            node.id(),
+            neighbors,
+            Rc::strong_count(node)
+        );
+    }
+    // 强引用只来自图和局部变量，与边的数量无关
+    assert_eq!(Rc::strong_count(&z), 2);
+    assert_eq!(graph.node(z.id()).name(), "z");
+
+    let watchers: Vec<Weak<graph::RcNode>> = [&x, &y, &z].iter().map(|n| Rc::downgrade(n)).collect();
+    drop((x, y, z));
+    drop(graph);
+    let alive = watchers.iter().filter(|w| w.upgrade().is_some()).count();
+    println!("丢弃图之后仍然存活的节点: {}", alive);
+    assert_eq!(alive, 0);
+    println!("✓ 有环的 RcGraph 被完整释放，没有泄漏");
+
+    println!("\nWeak 的作用:");
+    println!("  - 所有权是一棵树: 图 → 节点；边只是不拥有的引用");
+    println!("  - Weak 不增加强引用计数，环不会阻止释放");
+    println!("  - 访问邻居要 upgrade()，节点被释放后得到 None");
+}
+
+/// 示例 17: 图 - 遍历、拓扑排序与最短路径
+pub fn graph_algorithms() {
+    println!("\n=== 示例 17: 图 - 遍历、拓扑排序与最短路径 ===");
+
+    use graph::{Graph, GraphError, NodeId, bfs, dfs, dijkstra, find_cycle, topological_sort};
+
+    // 课程先修关系（有向无环图）
+    let mut courses = Graph::new();
+    let intro = courses.add_node("程序设计入门");
+    let ds = courses.add_node("数据结构");
+    let discrete = courses.add_node("离散数学");
+    let algo = courses.add_node("算法");
+    let os = courses.add_node("操作系统");
+    let compiler = courses.add_node("编译原理");
+    courses.add_edge(intro, ds, 1);
+    courses.add_edge(discrete, algo, 1);
+    courses.add_edge(ds, algo, 1);
+    courses.add_edge(ds, os, 1);
+    courses.add_edge(algo, compiler, 1);
+    courses.add_edge(os, compiler, 1);
+
+    let names = |graph: &Graph<&str>, ids: &[NodeId]| -> String {
+        ids.iter().map(|&id| *graph.node(id)).collect::<Vec<_>>().join(" → ")
+    };
+
+    println!("BFS (从入门): {}", names(&courses, &bfs(&courses, intro)));
+    println!("DFS (从入门): {}", names(&courses, &dfs(&courses, intro)));
+    let order = topological_sort(&courses).unwrap();
+    println!("拓扑排序（一种合法的修课顺序）: {}", names(&courses, &order));
+    assert_eq!(find_cycle(&courses), None);
+
+    // 加一条边形成环：编译原理 → 程序设计入门
+    courses.add_edge(compiler, intro, 1);
+    match topological_sort(&courses) {
+        Ok(_) => println!("意外: 有环的图也排序成功了"),
+        Err(e) => {
+            println!("加入 编译原理 → 入门 之后: {}", e);
+            let GraphError::Cycle { path } = &e;
+            println!("  环: {}", names(&courses, path));
+            assert_eq!(path.first(), path.last());
+        }
+    }
+    println!("共 {} 条边", courses.edge_count());
+
+    // 带权图上的最短路径
+    let mut map = Graph::new();
+    let cities = ["北京", "天津", "济南", "南京", "上海", "郑州", "武汉"];
+    let id: Vec<NodeId> = cities.iter().map(|&c| map.add_node(c)).collect();
+    let roads = [(0, 1, 120), (1, 2, 320), (0, 5, 690), (2, 3, 620), (3, 4, 300), (5, 6, 530), (6, 4, 840), (2, 5, 400)];
+    for (a, b, km) in roads {
+        map.add_edge(id[a], id[b], km);
+        map.add_edge(id[b], id[a], km);
+    }
+    let paths = dijkstra(&map, id[0]);
+    for target in [4, 6] {
+        let route = paths.path_to(id[target]).unwrap();
+        println!(
+            "北京 → {} 最短 {} 公里: {}",
+            cities[target],
+            paths.distance(id[target]).unwrap(),
+            names(&map, &route)
+        );
+    }
+    assert_eq!(paths.distance(id[4]), Some(120 + 320 + 620 + 300));
+    assert_eq!(paths.distance(id[6]), Some(690 + 530));
+
+    let isolated = map.add_node("拉萨");
+    let paths = dijkstra(&map, id[0]);
+    assert_eq!(paths.distance(isolated), None);
+    assert_eq!(paths.path_to(isolated), None);
+    println!("北京 → 拉萨: 不可达");
+
+    println!("\n算法要点:");
+    println!("  - 所有算法只依赖 Neighbors trait，两种图都能用");
+    println!("  - DFS 和环检测用显式栈，不会因为图太深而栈溢出");
+    println!("  - 拓扑排序用 Kahn 算法，有环时报告环上的节点");
+    println!("  - Dijkstra 用 BinaryHeap + Reverse 实现最小堆");
+}
+
+/// 示例 18: 图 - Rc + Weak 与索引 arena 的对比
+pub fn graph_design_comparison() {
+    println!("\n=== 示例 18: 图 - Rc + Weak 与索引 arena 的对比 ===");
+
+    use graph::{Graph, Neighbors, NodeId, RcGraph, bfs, dijkstra};
+    use std::time::Instant;
+
+    // 同样的随机图建两份：固定种子
+    let n = 20_000;
+    let edges_per_node = 5;
+    let mut rng = XorShift::new(42);
+    let edges: Vec<(usize, usize, u32)> = (0..n * edges_per_node)
+        .map(|i| (i / edges_per_node, rng.below(n as u64) as usize, rng.below(100) as u32 + 1))
+        .collect();
+
+    let start = Instant::now();
+    let mut rc_graph = RcGraph::new();
+    let rc_nodes: Vec<_> = (0..n).map(|i| rc_graph.add_node(&i.to_string())).collect();
+    for &(a, b, w) in &edges {
+        rc_graph.connect(rc_nodes[a].id(), rc_nodes[b].id(), w);
+    }
+    let rc_build = start.elapsed();
+
+    let start = Instant::now();
+    let mut arena = Graph::new();
+    for i in 0..n {
+        arena.add_node(i.to_string());
+    }
+    for &(a, b, w) in &edges {
+        arena.add_edge(NodeId(a), NodeId(b), w);
+    }
+    let arena_build = start.elapsed();
+
+    let start = Instant::now();
+    let rc_order = bfs(&rc_graph, NodeId(0));
+    let rc_paths = dijkstra(&rc_graph, NodeId(0));
+    let rc_search = start.elapsed();
+
+    let start = Instant::now();
+    let arena_order = bfs(&arena, NodeId(0));
+    let arena_paths = dijkstra(&arena, NodeId(0));
+    let arena_search = start.elapsed();
+
+    assert_eq!(rc_order, arena_order);
+    assert_eq!(rc_graph.node_count(), arena.node_count());
+    for i in (0..n).step_by(997) {
+        assert_eq!(rc_paths.distance(NodeId(i)), arena_paths.distance(NodeId(i)));
+    }
+    println!("{} 个节点、{} 条边，两种设计的遍历和最短路径结果相同", n, edges.len());
+    println!("  {:<12} {:>12} {:>16}", "", "建图", "BFS + Dijkstra");
+    println!("  {:<12} {:>12?} {:>16?}", "Rc + Weak", rc_build, rc_search);
+    println!("  {:<12} {:>12?} {:>16?}", "arena", arena_build, arena_search);
+
+    println!("\n每个节点 / 边的额外开销:");
+    println!(
+        "  Rc + Weak: 节点 {} 字节 + Rc 的两个计数，边 {} 字节",
+        std::mem::size_of::<graph::RcNode>(),
+        std::mem::size_of::<(std::rc::Weak<graph::RcNode>, u32)>()
+    );
+    println!("  arena:     边 {} 字节，节点只有数据本身", std::mem::size_of::<(NodeId, u32)>());
+
+    println!("\n如何选择:");
+    println!("  - Rc + Weak: 节点可以单独传递和持有，但每次访问邻居都要 upgrade 和 RefCell 借用");
+    println!("  - arena: 内存连续、没有引用计数，删除节点要自己处理下标失效");
+    println!("  - 需要引用环时，两者都避免了示例 14 的泄漏问题");
+}
+
+/// 示例 19: 缓存 - LRU 与 LFU 的淘汰策略
+pub fn cache_eviction_policies() {
+    println!("\n=== 示例 19: 缓存 - LRU 与 LFU 的淘汰策略 ===");
+
+    use cache::{Cache, LfuCache, LruCache};
+
+    // 同样的访问序列分别交给 LRU 和 LFU
+    let mut lru = LruCache::new(3);
+    let mut lfu = LfuCache::new(3);
+    for (key, value) in [("a", 1), ("b", 2), ("c", 3)] {
+        lru.put(key, value);
+        lfu.put(key, value);
+    }
+    // a 被访问三次，b 一次，c 没有被访问
+    for key in ["a", "a", "b", "a"] {
+        lru.get(&key);
+        lfu.get(&key);
+    }
+    println!("LRU 中的键（从新到旧）: {:?}", lru.keys());
+    println!("LFU 中 a 的访问次数: {:?}", lfu.frequency(&"a"));
+
+    // 容量已满，插入 d 各淘汰一个
+    lru.put("d", 4);
+    lfu.put("d", 4);
+    println!("插入 d 后 LRU: {:?}", lru.keys());
+    assert_eq!(lru.keys(), [&"d", &"a", &"b"]);
+    assert_eq!(lru.get(&"c"), None);
+    assert_eq!(lfu.get(&"c"), None);
+
+    // 区别：最近刚访问过一次的 d 和访问过很多次的 a
+    lru.get(&"b");
+    lru.put("e", 5);
+    println!("再访问 b、插入 e 后 LRU: {:?}（a 最久没被访问，被淘汰）", lru.keys());
+    assert_eq!(lru.get(&"a"), None);
+
+    lfu.get(&"d");
+    lfu.put("e", 5);
+    println!("插入 e 后 LFU 保留了访问最多的 a: {:?}", lfu.get(&"a"));
+    assert_eq!(lfu.get(&"a"), Some(&1));
+    assert_eq!(lfu.get(&"b"), None);
+
+    // 更新已有的键不会淘汰
+    lru.put("e", 50);
+    assert_eq!(lru.len(), 3);
+    assert_eq!(lru.get(&"e"), Some(&50));
+
+    println!("LRU 统计: {}", lru.stats());
+    println!("LFU 统计: {}", lfu.stats());
+    assert_eq!(lru.stats().evictions, 2);
+    assert_eq!(lfu.stats().evictions, 2);
+
+    // 与一个用 Vec 实现的朴素 LRU 对照，随机操作结果必须一致
+    let mut rng = XorShift::new(7);
+    let capacity = 16;
+    let mut fast = LruCache::new(capacity);
+    let mut naive: Vec<(u64, u64)> = Vec::new(); // 从新到旧
+    for _ in 0..100_000 {
+        let key = rng.below(40);
+        if rng.below(3) == 0 {
+            let value = rng.next_u64();
+            fast.put(key, value);
+            naive.retain(|&(k, _)| k != key);
+            naive.insert(0, (key, value));
+            naive.truncate(capacity);
+        } else {
+            let expected = naive.iter().position(|&(k, _)| k == key).map(|i| {
+                let entry = naive.remove(i);
+                naive.insert(0, entry);
+                entry.1
+            });
+            assert_eq!(fast.get(&key).copied(), expected);
+        }
+    }
+    let naive_keys: Vec<u64> = naive.iter().map(|&(k, _)| k).collect();
+    assert_eq!(fast.keys().into_iter().copied().collect::<Vec<_>>(), naive_keys);
+    println!("✓ 100000 次随机操作，LruCache 与朴素实现的结果一致");
+
+    println!("\n两种策略:");
+    println!("  - LRU: HashMap 找到节点，双向链表维护访问顺序，get / put 都是 O(1)");
+    println!("  - LRU 的链表节点放在 Vec 里用下标连接，淘汰时直接复用尾部节点");
+    println!("  - LFU: 按 (访问次数, 最后访问时刻) 排序，O(log n)，能抵抗一次性扫描");
+    println!("  - get 会更新访问信息，所以即使是查找也需要 &mut self");
+}
+
+/// 示例 20: 缓存 - TTL 过期
+///
+/// 用 FakeClock 手动推进时间，不需要真的等待
+pub fn cache_ttl_expiry() {
+    println!("\n=== 示例 20: 缓存 - TTL 过期 ===");
+
+    use crate::util::{FakeClock, SystemClock};
+    use cache::{Cache, TtlCache};
+    use std::time::Duration;
+
+    let clock = Arc::new(FakeClock::new());
+    let mut sessions = TtlCache::new(3, Duration::from_secs(60), clock.clone());
+
+    sessions.put("alice", "token-a");
+    clock.advance(Duration::from_secs(30));
+    sessions.put("bob", "token-b");
+    println!("30 秒后: alice 还剩 {:?}", sessions.time_to_live(&"alice"));
+    assert_eq!(sessions.time_to_live(&"alice"), Some(Duration::from_secs(30)));
+
+    clock.advance(Duration::from_secs(30));
+    println!("60 秒后: alice = {:?}", sessions.get(&"alice"));
+    println!("60 秒后: bob = {:?}", sessions.get(&"bob"));
+    assert_eq!(sessions.get(&"alice"), None);
+    assert_eq!(sessions.get(&"bob"), Some(&"token-b"));
+
+    // 重新写入会刷新过期时间
+    sessions.put("bob", "token-b2");
+    clock.advance(Duration::from_secs(45));
+    assert_eq!(sessions.get(&"bob"), Some(&"token-b2"));
+    println!("刷新后 45 秒: bob 仍然有效");
+
+    // 容量已满且没有过期的条目：淘汰最早过期的
+    sessions.put("carol", "token-c");
+    sessions.put("dave", "token-d");
+    sessions.put("erin", "token-e");
+    assert_eq!(sessions.len(), 3);
+    assert_eq!(sessions.get(&"bob"), None);
+    println!("容量 3，插入 erin 时淘汰了最早过期的 bob");
+
+    clock.advance(Duration::from_secs(60));
+    let purged = sessions.purge_expired();
+    println!("再过 60 秒，purge_expired 删除了 {} 个条目", purged);
+    assert_eq!(purged, 3);
+    assert!(sessions.is_empty());
+
+    println!("统计: {}", sessions.stats());
+    assert_eq!(sessions.stats().evictions, 1);
+    assert_eq!(sessions.stats().expirations, 4);
+
+    // 反复写入同一个热点键：作废的队列项会被定期清理，队列长度不超过容量的两倍
+    for _ in 0..10_000 {
+        sessions.put("hot", "token-h");
+    }
+    println!("热点键写入 10000 次后，过期队列长度: {}", sessions.queue_len());
+    assert!(sessions.queue_len() <= 2 * 3);
+    assert_eq!(sessions.get(&"hot"), Some(&"token-h"));
+
+    // 生产环境换成系统时钟，其余代码不变
+    let mut real: TtlCache<&str, i32> = TtlCache::new(10, Duration::from_secs(1), Arc::new(SystemClock));
+    real.put("k", 1);
+    println!("SystemClock: 刚写入的 k = {:?}", real.get(&"k"));
+
+    println!("\nTTL 缓存的要点:");
+    println!("  - 所有条目的 TTL 相同，过期顺序就是写入顺序，用队列就够了");
+    println!("  - 过期的条目在 get / put 时惰性清理，不需要后台线程");
+    println!("  - 队列项带写入序号，更新留下的作废项在队列过长时整体清理");
+    println!("  - 时钟通过 Clock trait 注入，和并发模块的限流器共用 util::FakeClock");
+}
+
+/// 示例 21: 缓存 - 分片减少锁竞争
+///
+/// 多个线程同时读写，对比示例 15 的单个 Mutex<HashMap> 和分片的 LRU
+pub fn cache_sharded_benchmark() {
+    println!("\n=== 示例 21: 缓存 - 分片减少锁竞争 ===");
+
+    use cache::{LruCache, Sharded};
+    use std::collections::HashMap;
+    use std::thread;
+    use std::time::Instant;
+
+    let threads = 8;
+    let operations = 200_000;
+    let keys: Vec<String> = (0..10_000).map(|i| format!("key-{}", i)).collect();
+
+    // 每个线程的访问序列：90% 读，10% 写
+    let key_count = keys.len() as u64;
+    let workload = |thread: usize| {
+        let mut rng = XorShift::new(thread as u64 * 2654435761 + 1);
+        (0..operations)
+            .map(move |_| {
+                let x = rng.next_u64();
+                (((x >> 16) % key_count) as usize, x.is_multiple_of(10))
+            })
+            .collect::<Vec<_>>()
+    };
+    let workloads: Vec<Vec<(usize, bool)>> = (0..threads).map(workload).collect();
+    let reads = workloads.iter().flatten().filter(|&&(_, write)| !write).count() as u64;
+
+    // 示例 15 的写法：一把锁保护整个 HashMap，没有容量上限
+    let single = Mutex::new(HashMap::new());
+    let start = Instant::now();
+    thread::scope(|s| {
+        for ops in &workloads {
+            let (single, keys) = (&single, &keys);
+            s.spawn(move || {
+                for &(i, write) in ops {
+                    if write {
+                        single.lock().unwrap().insert(keys[i].clone(), i.to_string());
+                    } else {
+                        let _ = single.lock().unwrap().get(&keys[i]).cloned();
+                    }
+                }
+            });
+        }
+    });
+    let single_time = start.elapsed();
+
+    let run_sharded = |shards: usize| {
+        let cache = Sharded::new(shards, || LruCache::new(8192 / shards));
+        let start = Instant::now();
+        thread::scope(|s| {
+            for ops in &workloads {
+                let (cache, keys) = (&cache, &keys);
+                s.spawn(move || {
+                    for &(i, write) in ops {
+                        if write {
+                            cache.put(keys[i].clone(), i.to_string());
+                        } else if let Some(value) = cache.get::<String, String>(&keys[i]) {
+                            assert_eq!(value, i.to_string());
+                        }
+                    }
+                });
+            }
+        });
+        (start.elapsed(), cache.stats::<String, String>(), cache.len::<String, String>())
+    };
+
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    println!("{} 个线程，每个 {} 次操作（90% 读），本机 {} 个核心", threads, operations, cores);
+    println!("  {:<24} {:>12?}", "Mutex<HashMap>（示例 15）", single_time);
+    for shards in [1, 4, 16, 64] {
+        let (time, stats, len) = run_sharded(shards);
+        println!("  {:<24} {:>12?}  {} 个条目，{}", format!("{} 个分片的 LRU", shards), time, len, stats);
+        assert!(len <= 8192);
+        assert_eq!(stats.hits + stats.misses, reads);
+    }
+
+    println!("\n结论:");
+    println!("  - 一把锁时所有线程排队，核心越多竞争越严重");
+    println!("  - 分片后不同的键落在不同的锁上，竞争按分片数减少；只有一个核心时没有真正的并行，看不出差别");
+    println!("  - LRU 的 get 要调整链表，单线程下比 HashMap 的 get 慢，这是有界和淘汰的代价");
+    println!("  - 代价: 淘汰只在分片内部进行，整体不是严格的 LRU");
+    println!("  - 分片内部用 LruCache，所以总内存有上限，示例 15 的 HashMap 会无限增长");
+}
+
+/// 示例 22: 自己实现 Rc 和 Weak
+///
+/// 完整版的示例 13：一个堆块、弱引用、写时复制
+pub fn my_rc_weak() {
+    println!("\n=== 示例 22: 自己实现 Rc 和 Weak ===");
+
+    use my_rc::{MyRc, MyWeak};
+
+    let a = MyRc::new(String::from("共享的字符串"));
+    let b = MyRc::clone(&a);
+    let weak = MyRc::downgrade(&a);
+    println!("a = {:?}", a);
+    println!("strong = {}, weak = {}", MyRc::strong_count(&a), MyRc::weak_count(&a));
+    assert_eq!((MyRc::strong_count(&a), MyRc::weak_count(&a)), (2, 1));
+    assert!(MyRc::ptr_eq(&a, &b));
+
+    drop(a);
+    println!("丢弃 a 后 upgrade: {:?}", weak.upgrade());
+    drop(b);
+    println!("丢弃 b 后 upgrade: {:?}（值已析构，堆块等 weak 丢弃时才释放）", weak.upgrade());
+    assert!(weak.upgrade().is_none());
+    assert_eq!(weak.strong_count(), 0);
+
+    // 树：子节点用 MyWeak 指向父节点，不会形成引用环
+    struct TreeNode {
+        name: &'static str,
+        parent: RefCell<Option<MyWeak<TreeNode>>>,
+        children: RefCell<Vec<MyRc<TreeNode>>>,
+    }
+    let root = MyRc::new(TreeNode {
+        name: "root",
+        parent: RefCell::new(None),
+        children: RefCell::new(Vec::new()),
+    });
+    let leaf = MyRc::new(TreeNode {
+        name: "leaf",
+        parent: RefCell::new(Some(MyRc::downgrade(&root))),
+        children: RefCell::new(Vec::new()),
+    });
+    root.children.borrow_mut().push(MyRc::clone(&leaf));
+    let parent = leaf.parent.borrow().as_ref().and_then(MyWeak::upgrade).map(|p| p.name);
+    println!("{} 的父节点: {:?}，root 有 {} 个子节点", leaf.name, parent, root.children.borrow().len());
+    let watch = MyRc::downgrade(&root);
+    drop(root);
+    assert!(watch.upgrade().is_none());
+    assert_eq!(MyRc::strong_count(&leaf), 1);
+    println!("丢弃 root 后整棵树被释放，leaf 只剩一个强引用");
+
+    // get_mut：只有唯一的引用时才能修改
+    let mut counter = MyRc::new(0);
+    *MyRc::get_mut(&mut counter).unwrap() += 1;
+    let other = MyRc::clone(&counter);
+    assert!(MyRc::get_mut(&mut counter).is_none());
+    drop(other);
+    let w = MyRc::downgrade(&counter);
+    assert!(MyRc::get_mut(&mut counter).is_none(), "有 Weak 时也不能 get_mut");
+    drop(w);
+    println!("get_mut 后 counter = {:?}", counter);
+
+    // make_mut：共享时复制，独占时原地修改
+    let mut original = MyRc::new(vec![1, 2, 3]);
+    let snapshot = MyRc::clone(&original);
+    MyRc::make_mut(&mut original).push(4);
+    println!("make_mut 后 original = {:?}, snapshot = {:?}", original, snapshot);
+    assert!(!MyRc::ptr_eq(&original, &snapshot));
+    let before = MyRc::downgrade(&original);
+    MyRc::make_mut(&mut original).push(5);
+    assert!(before.upgrade().is_none(), "只有 Weak 共享时值被搬走，旧 Weak 失效");
+    assert_eq!(*original, [1, 2, 3, 4, 5]);
+
+    // try_unwrap：唯一的强引用时取回所有权
+    let shared = MyRc::clone(&snapshot);
+    let snapshot = match MyRc::try_unwrap(snapshot) {
+        Ok(_) => unreachable!(),
+        Err(rc) => rc,
+    };
+    drop(shared);
+    let owned: Vec<i32> = MyRc::try_unwrap(snapshot).unwrap();
+    println!("try_unwrap 取回: {:?}", owned);
+
+    println!("\n与示例 13 的区别:");
+    println!("  - 计数和值放在同一个堆块里，只分配一次");
+    println!("  - weak 计数额外加 1 代表所有强引用，强引用归零时析构值，弱引用归零时释放内存");
+    println!("  - 用 NonNull + PhantomData，不是 Send，也不能跨线程共享");
+    println!("  - make_mut 只在真正共享时复制");
+}
+
+/// 示例 23: 自己实现 Arc
+pub fn my_arc_threads() {
+    println!("\n=== 示例 23: 自己实现 Arc ===");
+
+    use my_arc::MyArc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    struct Tracked(usize);
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let config = MyArc::new(Tracked(42));
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let config = MyArc::clone(&config);
+            thread::spawn(move || {
+                // 每个线程反复 clone 和 drop
+                let mut sum = 0;
+                for _ in 0..10_000 {
+                    let local = MyArc::clone(&config);
+                    sum += local.0;
+                }
+                (i, sum)
+            })
+        })
+        .collect();
+    for handle in handles {
+        let (i, sum) = handle.join().unwrap();
+        assert_eq!(sum, 42 * 10_000);
+        if i == 0 {
+            println!("线程 0 读取了 10000 次，和为 {}", sum);
+        }
+    }
+    println!("所有线程结束后 strong = {}", MyArc::strong_count(&config));
+    assert_eq!(MyArc::strong_count(&config), 1);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+    drop(config);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    println!("✓ 80000 次跨线程 clone / drop 后值恰好析构一次");
+
+    // make_mut 和 try_unwrap
+    let mut data = MyArc::new(vec![1, 2, 3]);
+    let reader = MyArc::clone(&data);
+    let handle = thread::spawn(move || reader.iter().sum::<i32>());
+    MyArc::make_mut(&mut data).push(4);
+    println!("make_mut 后 data = {:?}，另一个线程读到的和 = {}", data, handle.join().unwrap());
+
+    let copy = MyArc::clone(&data);
+    assert!(MyArc::ptr_eq(&data, &copy));
+    assert!(MyArc::get_mut(&mut data).is_none());
+    let data = MyArc::try_unwrap(data).unwrap_err();
+    drop(copy);
+    let owned = MyArc::try_unwrap(data).unwrap();
+    println!("try_unwrap 取回: {:?}", owned);
+
+    println!("\n内存序:");
+    println!("  - clone: Relaxed，已经持有引用，计数不会在此期间归零");
+    println!("  - drop: Release 递减，最后一个引用再用 Acquire 栅栏，之后才析构值");
+    println!("  - try_unwrap: compare_exchange(1, 0, Acquire, ..)，成功就没有任何其他引用");
+    println!("  - T: Send + Sync 时 MyArc 才是 Send / Sync");
+}
+
+/// 示例 24: MyRc / MyArc 的泄漏和释放检查
+///
+/// 随机操作与标准库的 Rc 对照，并记录每个值的析构次数。
+/// 打开这个示例后用 `cargo +nightly miri run` 运行，Miri 还能检查释放后使用和泄漏。
+pub fn my_pointer_checks() {
+    println!("\n=== 示例 24: MyRc / MyArc 的泄漏和释放检查 ===");
+
+    use my_arc::MyArc;
+    use my_rc::{MyRc, MyWeak};
+    use std::rc::Weak;
+
+    // 记录存活的值的个数：创建和克隆加一，析构减一，最后必须归零
+    struct Tracked {
+        live: Rc<Cell<isize>>,
+    }
+    impl Tracked {
+        fn new(live: &Rc<Cell<isize>>) -> Self {
+            live.set(live.get() + 1);
+            Tracked { live: Rc::clone(live) }
+        }
+    }
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            Tracked::new(&self.live)
+        }
+    }
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.live.set(self.live.get() - 1);
+        }
+    }
+
+    let mut rng = XorShift::new(12345);
+
+    let rounds = 2_000;
+    for _ in 0..rounds {
+        let live = Rc::new(Cell::new(0));
+        let mut mine: Vec<MyRc<Tracked>> = vec![MyRc::new(Tracked::new(&live))];
+        let mut my_weak: Vec<MyWeak<Tracked>> = Vec::new();
+        let mut std_rc: Vec<Rc<()>> = vec![Rc::new(())];
+        let mut std_weak: Vec<Weak<()>> = Vec::new();
+
+        for _ in 0..30 {
+            match rng.below(6) {
+                0 if !mine.is_empty() => {
+                    let i = rng.below(mine.len() as u64) as usize;
+                    mine.push(MyRc::clone(&mine[i]));
+                    std_rc.push(Rc::clone(&std_rc[i]));
+                }
+                1 if !mine.is_empty() => {
+                    let i = rng.below(mine.len() as u64) as usize;
+                    mine.swap_remove(i);
+                    std_rc.swap_remove(i);
+                }
+                2 if !mine.is_empty() => {
+                    my_weak.push(MyRc::downgrade(&mine[0]));
+                    std_weak.push(Rc::downgrade(&std_rc[0]));
+                }
+                3 if !my_weak.is_empty() => {
+                    let i = rng.below(my_weak.len() as u64) as usize;
+                    match (my_weak[i].upgrade(), std_weak[i].upgrade()) {
+                        (Some(a), Some(b)) => {
+                            mine.push(a);
+                            std_rc.push(b);
+                        }
+                        (None, None) => {}
+                        _ => panic!("upgrade 结果与标准库不一致"),
+                    }
+                }
+                4 if !my_weak.is_empty() => {
+                    let i = rng.below(my_weak.len() as u64) as usize;
+                    my_weak.swap_remove(i);
+                    std_weak.swap_remove(i);
+                }
+                5 if !mine.is_empty() => {
+                    // 值可能被复制，之后两边不再对应同一个分配，只比较行为
+                    let i = rng.below(mine.len() as u64) as usize;
+                    let _ = MyRc::make_mut(&mut mine[i]);
+                    let _ = Rc::make_mut(&mut std_rc[i]);
+                }
+                _ => {}
+            }
+            for (a, b) in mine.iter().zip(&std_rc) {
+                assert_eq!(MyRc::strong_count(a), Rc::strong_count(b));
+                assert_eq!(MyRc::weak_count(a), Rc::weak_count(b));
+            }
+        }
+        // 存活的值就是 mine 中不同的分配：只剩 Weak 的值必须已经析构
+        let distinct = (0..mine.len())
+            .filter(|&i| !mine[..i].iter().any(|rc| MyRc::ptr_eq(rc, &mine[i])))
+            .count();
+        assert_eq!(live.get(), distinct as isize);
+        drop(mine);
+        drop(my_weak);
+        assert_eq!(live.get(), 0, "每个值恰好析构一次");
+    }
+    println!("✓ {} 轮随机 clone / drop / downgrade / upgrade / make_mut，计数与 std::rc::Rc 一致", rounds);
+    println!("✓ 每个值恰好析构一次（没有泄漏，也没有重复析构）");
+
+    // try_unwrap 之后只剩 Weak：堆块由 Weak 释放
+    let live = Rc::new(Cell::new(0));
+    let rc = MyRc::new(Tracked::new(&live));
+    let weak = MyRc::downgrade(&rc);
+    let value = MyRc::try_unwrap(rc).ok().unwrap();
+    assert!(weak.upgrade().is_none());
+    assert_eq!(live.get(), 1);
+    drop(value);
+    drop(weak);
+    assert_eq!(live.get(), 0);
+    println!("✓ try_unwrap 后 Weak 无法 upgrade，值只析构一次");
+
+    // MyArc：clone 出的句柄修改的是同一个值
+    let arc = MyArc::new(Cell::new(0));
+    let copies: Vec<_> = (0..10).map(|_| MyArc::clone(&arc)).collect();
+    copies.iter().for_each(|c| c.set(c.get() + 1));
+    drop(copies);
+    assert_eq!(MyArc::try_unwrap(arc).unwrap().get(), 10);
+    println!("✓ MyArc 的 clone / drop / try_unwrap 计数正确");
+
+    println!("\n检查方法:");
+    println!("  - 用 Drop 计数确认每个值恰好析构一次");
+    println!("  - 每一步都和标准库的 Rc 比较 strong / weak 计数");
+    println!("  - Miri 可以进一步发现释放后使用、未初始化读取和泄漏（需要 nightly 工具链）");
+}
+
+/// 示例 25: arena - 句柄与代数
+pub fn arena_basics() {
+    println!("\n=== 示例 25: arena - 句柄与代数 ===");
+
+    use arena::Arena;
+
+    let mut names = Arena::new();
+    let alice = names.alloc(String::from("alice"));
+    let bob = names.alloc(String::from("bob"));
+    println!("alice = {:?}, bob = {:?}", alice, bob);
+    println!("names[bob] = {}", names[bob]);
+
+    names[alice].push_str("@example.com");
+    println!("通过 IndexMut 修改: {}", names[alice]);
+
+    // 删除后槽位被复用，但代数不同，旧句柄不会指向新值
+    let removed = names.remove(bob);
+    let carol = names.alloc(String::from("carol"));
+    println!("删除 bob（{:?}），新分配的 carol = {:?}（同一个槽位，第 1 代）", removed, carol);
+    assert_eq!(names.get(bob), None);
+    assert_eq!(names.get(carol).map(String::as_str), Some("carol"));
+    assert_eq!(names.remove(bob), None, "重复删除不会影响新值");
+    println!("旧句柄 bob: get = {:?}, contains = {}", names.get(bob), names.contains(bob));
+
+    // 类型化的句柄：Id<String> 不能用在 Arena<i32> 上，下面这行无法编译
+    // let numbers: Arena<i32> = Arena::new(); numbers.get(alice);
+
+    for (id, name) in names.iter() {
+        println!("  {:?} → {}", id, name);
+    }
+
+    // 一次性清空：保留内存，所有句柄失效
+    let mut numbers = Arena::with_capacity(1000);
+    let ids: Vec<_> = (0..1000).map(|i| numbers.alloc(i)).collect();
+    let capacity = numbers.capacity();
+    numbers.clear();
+    assert!(numbers.is_empty());
+    assert!(ids.iter().all(|&id| !numbers.contains(id)));
+    let reused = numbers.alloc(7);
+    assert_eq!(numbers.capacity(), capacity);
+    println!("clear 后 1000 个句柄全部失效，容量保持 {}，新句柄 {:?}", capacity, reused);
+
+    println!("\narena 的要点:");
+    println!("  - 句柄 = 下标 + 代数，Copy、体积小，可以随意形成环");
+    println!("  - 删除时代数加一，旧句柄访问返回 None，而不是读到别的对象");
+    println!("  - 空闲槽位串成链表，分配时优先复用");
+    println!("  - clear / drop 一次性释放所有值，不用逐个节点递归");
+}
+
+/// 示例 26: arena - 二叉树和图
+///
+/// 对比示例 2 的 Box 二叉树和示例 14 / 16 的 Rc 图
+pub fn arena_tree_and_graph() {
+    println!("\n=== 示例 26: arena - 二叉树和图 ===");
+
+    use arena::{Arena, Id};
+    use std::time::Instant;
+
+    // ---------- 二叉搜索树 ----------
+
+    // 示例 2 的写法：每个节点一个 Box
+    struct BoxNode {
+        key: u64,
+        left: Option<Box<BoxNode>>,
+        right: Option<Box<BoxNode>>,
+    }
+
+    fn box_insert(mut slot: &mut Option<Box<BoxNode>>, key: u64) {
+        while let Some(node) = slot {
+            slot = if key < node.key { &mut node.left } else { &mut node.right };
+        }
+        *slot = Some(Box::new(BoxNode { key, left: None, right: None }));
+    }
+
+    // arena 的写法：子节点是句柄
+    struct ArenaNode {
+        key: u64,
+        left: Option<Id<ArenaNode>>,
+        right: Option<Id<ArenaNode>>,
+    }
+
+    struct ArenaTree {
+        nodes: Arena<ArenaNode>,
+        root: Option<Id<ArenaNode>>,
+    }
+
+    impl ArenaTree {
+        fn insert(&mut self, key: u64) {
+            let new = self.nodes.alloc(ArenaNode { key, left: None, right: None });
+            let Some(mut current) = self.root else {
+                self.root = Some(new);
+                return;
+            };
+            loop {
+                let node = &mut self.nodes[current];
+                let child = if key < node.key { &mut node.left } else { &mut node.right };
+                match *child {
+                    Some(next) => current = next,
+                    None => {
+                        *child = Some(new);
+                        return;
+                    }
+                }
+            }
+        }
+
+        fn contains(&self, key: u64) -> bool {
+            let mut current = self.root;
+            while let Some(id) = current {
+                let node = &self.nodes[id];
+                if key == node.key {
+                    return true;
+                }
+                current = if key < node.key { node.left } else { node.right };
+            }
+            false
+        }
+
+        /// 中序遍历，用显式栈
+        fn in_order(&self) -> Vec<u64> {
+            let mut keys = Vec::with_capacity(self.nodes.len());
+            let mut stack = Vec::new();
+            let mut current = self.root;
+            while current.is_some() || !stack.is_empty() {
+                while let Some(id) = current {
+                    stack.push(id);
+                    current = self.nodes[id].left;
+                }
+                let id = stack.pop().unwrap();
+                keys.push(self.nodes[id].key);
+                current = self.nodes[id].right;
+            }
+            keys
+        }
+    }
+
+    // Box 树默认的 drop 是递归的，这里的随机树不深，可以直接 drop
+
+    let n = 200_000;
+    let mut rng = XorShift::new(99);
+    let keys: Vec<u64> = (0..n).map(|_| rng.below(1_000_000_000)).collect();
+
+    let start = Instant::now();
+    let mut box_root = None;
+    for &key in &keys {
+        box_insert(&mut box_root, key);
+    }
+    let box_build = start.elapsed();
+
+    let start = Instant::now();
+    let mut tree = ArenaTree { nodes: Arena::new(), root: None };
+    let mut grows = 0;
+    for &key in &keys {
+        let before = tree.nodes.capacity();
+        tree.insert(key);
+        if tree.nodes.capacity() != before {
+            grows += 1;
+        }
+    }
+    let arena_build = start.elapsed();
+
+    let start = Instant::now();
+    let found = keys.iter().step_by(7).filter(|&&k| tree.contains(k)).count();
+    let arena_search = start.elapsed();
+    assert_eq!(found, keys.len().div_ceil(7));
+    assert!(!tree.contains(1_000_000_001));
+
+    let sorted = tree.in_order();
+    assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(sorted.len(), n);
+
+    let start = Instant::now();
+    drop(box_root);
+    let box_drop = start.elapsed();
+    let start = Instant::now();
+    drop(tree);
+    let arena_drop = start.elapsed();
+
+    println!("二叉搜索树，插入 {} 个随机键:", n);
+    println!("  {:<8} {:>12} {:>12} {:>16}", "", "建树", "释放", "向系统申请内存");
+    println!("  {:<8} {:>12?} {:>12?} {:>16}", "Box", box_build, box_drop, format!("{} 次", n));
+    println!("  {:<8} {:>12?} {:>12?} {:>16}", "arena", arena_build, arena_drop, format!("{} 次", grows));
+    println!("  arena 树查找 {} 次用时 {:?}，中序遍历结果有序", found, arena_search);
+
+    // ---------- 图 ----------
+
+    struct City {
+        name: &'static str,
+        roads: Vec<Id<City>>,
+    }
+
+    let mut map: Arena<City> = Arena::new();
+    let names = ["北京", "天津", "济南", "南京", "上海"];
+    let ids: Vec<Id<City>> = names.iter().map(|&name| map.alloc(City { name, roads: Vec::new() })).collect();
+    // 环形的路网：arena 中的环不需要 Weak，也不会泄漏
+    for i in 0..ids.len() {
+        let next = ids[(i + 1) % ids.len()];
+        map[ids[i]].roads.push(next);
+        map[next].roads.push(ids[i]);
+    }
+
+    // 删除一个城市：指向它的句柄自动失效，遍历时跳过
+    let removed = map.remove(ids[2]).unwrap();
+    println!("\n删除 {} 后各城市还能到达的邻居:", removed.name);
+    for (_, city) in map.iter() {
+        let roads: Vec<&str> = city.roads.iter().filter_map(|&id| map.get(id)).map(|c| c.name).collect();
+        println!("  {} → {:?}", city.name, roads);
+    }
+    assert!(map[ids[1]].roads.iter().any(|&id| !map.contains(id)));
+
+    // 和 Rc + Weak 的图比较建图和释放的开销
+    let n = 100_000;
+    let start = Instant::now();
+    let mut rc_graph = graph::RcGraph::new();
+    let rc_nodes: Vec<_> = (0..n).map(|_| rc_graph.add_node("")).collect();
+    for i in 0..n {
+        rc_graph.connect(rc_nodes[i].id(), rc_nodes[(i + 1) % n].id(), 1);
+    }
+    drop(rc_nodes);
+    drop(rc_graph);
+    let rc_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut ring: Arena<City> = Arena::new();
+    let ids: Vec<_> = (0..n).map(|_| ring.alloc(City { name: "", roads: Vec::new() })).collect();
+    for i in 0..n {
+        ring[ids[i]].roads.push(ids[(i + 1) % n]);
+    }
+    drop(ring);
+    let arena_time = start.elapsed();
+    println!("\n{} 个节点的环，建图 + 释放:", n);
+    println!("  Rc + Weak: {:?}", rc_time);
+    println!("  arena:     {:?}", arena_time);
+
+    println!("\n对比:");
+    println!("  - Box / Rc 每个节点一次分配；arena 只在 Vec 扩容时分配，次数是对数级");
+    println!("  - arena 节点在内存中连续，遍历对缓存友好");
+    println!("  - 图中有环时 arena 不需要 Weak，删除节点后旧句柄由代数检测");
+    println!("  - 代价: 访问节点要经过 arena，节点不能脱离 arena 单独传递");
+}
+
+/// 示例 27: arena - 表达式树
+///
+/// 枚举模块的 Expr 分别用 Box 和 arena 存储，比较构建、求值和释放；
+/// 需要在 main.rs 中同时启用 mod enums
+pub fn arena_expr_tree() {
+    println!("\n=== 示例 27: arena - 表达式树 ===");
+
+    use crate::enums::expr::{Env, Expr};
+    use crate::enums::parser;
+    use arena_expr::ExprTree;
+    use std::thread;
+    use std::time::Instant;
+
+    let mut env = Env::new();
+    env.insert(String::from("x"), 3.0);
+    env.insert(String::from("y"), 4.0);
+
+    // None 表示应当出错；解析器生成的 Spanned 节点也原样存进 arena，错误的位置相同
+    let cases = [
+        ("sqrt(x * x + y * y)", Some(5.0)),
+        ("-x * 2 + y / 4", Some(-5.0)),
+        ("max(x, y, 2 ^ 3) - min(x, y)", Some(5.0)),
+        ("if(x - 3, 1 / 0, y)", Some(4.0)),
+        ("x / (y - 4)", None),
+        ("sqrt(1 - y)", None),
+        ("(-8) ^ 0.5", None),
+        ("10 ^ 400", None),
+        ("ln(z) + 1", None),
+        ("min()", None),
+    ];
+    for (text, expected) in cases {
+        let expr = parser::parse(text).expect("合法的表达式");
+        let tree = ExprTree::from_expr(&expr);
+        let result = tree.eval_with(&env);
+        assert_eq!(result.as_ref().ok(), expected.as_ref(), "{} 的结果不对", text);
+        assert_eq!(result, expr.eval_with(&env), "{} 的结果不一致", text);
+        assert_eq!(tree.to_expr(), expr);
+        match result {
+            Ok(value) => println!("  {:<30} = {}（{} 个节点）", text, value, tree.node_count()),
+            Err(e) => println!("  {:<30} 错误: {}", text, e),
+        }
+    }
+    println!("✓ 结果和错误（包括位置）都与 Expr::eval_with 相同，转换回 Expr 也相同");
+
+    // 一棵很深的树：((x * 1 + x * 2) + x * 3) + ... + x * n，左结合的加法链有 n 层。
+    // Box 版本的 clone、drop 和两种表示的递归求值都要递归 n 层，
+    // debug 构建的栈帧较大，主线程默认的栈放不下，所以放到栈更大的线程里运行
+    let n = 20_000;
+    let benchmark = move || {
+        let term = |i: usize| Expr::Multiply(Box::new(Expr::Var(String::from("x"))), Box::new(Expr::Number(i as f64)));
+        let expr = (1..=n).map(term).reduce(|sum, t| Expr::Add(Box::new(sum), Box::new(t))).unwrap();
+        let iterations = 20;
+
+        let start = Instant::now();
+        let copies: Vec<Expr> = (0..iterations).map(|_| expr.clone()).collect();
+        let box_build = start.elapsed();
+        let start = Instant::now();
+        let trees: Vec<ExprTree> = (0..iterations).map(|_| ExprTree::from_expr(&expr)).collect();
+        let arena_build = start.elapsed();
+
+        let start = Instant::now();
+        let box_sum: f64 = copies.iter().map(|e| e.eval_with(&env).unwrap()).sum();
+        let box_eval = start.elapsed();
+        let start = Instant::now();
+        let arena_sum: f64 = trees.iter().map(|t| t.eval_with(&env).unwrap()).sum();
+        let arena_eval = start.elapsed();
+        assert_eq!(box_sum, arena_sum);
+
+        let nodes = trees[0].node_count();
+        let start = Instant::now();
+        drop(copies);
+        let box_drop = start.elapsed();
+        let start = Instant::now();
+        drop(trees);
+        let arena_drop = start.elapsed();
+        drop(expr);
+
+        println!("\n{} 个节点、{} 层的表达式，构建 {} 份:", nodes, n, iterations);
+        println!("  {:<6} {:>12} {:>12} {:>12}", "", "构建", "求值", "释放");
+        println!("  {:<6} {:>12?} {:>12?} {:>12?}", "Box", box_build, box_eval, box_drop);
+        println!("  {:<6} {:>12?} {:>12?} {:>12?}", "arena", arena_build, arena_eval, arena_drop);
+    };
+    thread::Builder::new()
+        .stack_size(256 << 20)
+        .spawn(benchmark)
+        .unwrap()
+        .join()
+        .unwrap();
+
+    println!("\n要点:");
+    println!("  - Node 和 Expr 一一对应，只是 Box<Expr> 换成了 Id<Node>");
+    println!("  - 两种表示共用 checked_div / checked_pow / call_builtin，错误完全相同");
+    println!("  - Box 版本每个节点一次分配，arena 版本只在扩容时分配");
+    println!("  - Box 树的 drop 要递归到最深的节点，arena 一次释放整个 Vec");
+    println!("  - Var 节点里的 String 仍然单独分配，所以差距不如二叉树明显");
+}
+
+/// 示例 28: 双向链表 - Rc + Weak
+pub fn linked_list_rc() {
+    println!("\n=== 示例 28: 双向链表 - Rc + Weak ===");
+
+    use linked_list::LinkedList;
+
+    let mut list = LinkedList::new();
+    list.push_back(2);
+    list.push_back(3);
+    list.push_front(1);
+    println!("list = {:?}，长度 {}", list, list.len());
+    println!("front = {:?}, back = {:?}", list.front().map(|v| *v), list.back());
+    println!("反向迭代: {:?}", list.iter().rev().collect::<Vec<_>>());
+
+    // 两端交替迭代，在中间相遇时停止
+    let mut both = list.iter();
+    println!("两端交替: {:?} {:?} {:?} {:?}", both.next(), both.next_back(), both.next(), both.next_back());
+
+    // 光标：在 2 之前插入、把 3 删除、在末尾的幽灵位置插入
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next();
+    cursor.insert_before(10);
+    println!("光标在下标 {:?}，当前元素 {:?}", cursor.index(), cursor.current().map(|v| *v));
+    if let Some(mut value) = cursor.current() {
+        *value *= 100;
+    }
+    cursor.move_next();
+    let removed = cursor.remove_current();
+    println!("删除 {:?} 后光标在幽灵位置: index = {:?}", removed, cursor.index());
+    cursor.insert_before(4);
+    cursor.insert_after(0);
+    assert_eq!(list.iter().collect::<Vec<_>>(), [0, 1, 10, 200, 4]);
+    println!("编辑后: {:?}", list);
+
+    assert_eq!(list.pop_front(), Some(0));
+    assert_eq!(list.pop_back(), Some(4));
+    let owned: Vec<i32> = list.into_iter().rev().collect();
+    println!("into_iter().rev(): {:?}", owned);
+
+    // 节点是 Rc，但不会泄漏：pop / drop 都能取回或释放所有值
+    let tracker = Rc::new(());
+    let mut shared: LinkedList<Rc<()>> = (0..1000).map(|_| Rc::clone(&tracker)).collect();
+    shared.pop_back();
+    println!("1000 个节点时 tracker 的强引用计数: {}", Rc::strong_count(&tracker));
+    drop(shared);
+    assert_eq!(Rc::strong_count(&tracker), 1);
+    println!("丢弃链表后: {}", Rc::strong_count(&tracker));
+
+    println!("\nRc + Weak 链表的要点:");
+    println!("  - next 是 Rc（拥有），prev 和 tail 是 Weak，没有引用环");
+    println!("  - 访问节点都要 borrow / borrow_mut，违反规则会在运行时 panic");
+    println!("  - 不能借出 &T：迭代器返回克隆的值，front 返回 Ref 守卫");
+    println!("  - 光标在尾部和头部之间有一个幽灵位置，和标准库的 CursorMut 一致");
+}
+
+/// 示例 29: 双向链表 - 裸指针
+pub fn linked_list_raw() {
+    println!("\n=== 示例 29: 双向链表 - 裸指针 ===");
+
+    use raw_list::RawList;
+
+    let mut list: RawList<String> = ["b", "c"].iter().map(|s| s.to_string()).collect();
+    list.push_front(String::from("a"));
+    println!("list = {:?}", list);
+
+    // 可以直接借出引用，不需要克隆
+    let first: &String = list.front().unwrap();
+    let last: &String = list.back().unwrap();
+    println!("front = {}, back = {}", first, last);
+    for value in list.iter_mut() {
+        value.push('!');
+    }
+    println!("iter_mut 修改后: {:?}", list.iter().rev().collect::<Vec<_>>());
+
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_prev(); // 幽灵位置
+    cursor.move_prev(); // 最后一个元素
+    let index = cursor.index();
+    println!("从头部向前移动两次到达: {:?}（下标 {:?}）", cursor.current(), index);
+    cursor.insert_after(String::from("d"));
+    if let Some(value) = cursor.current() {
+        value.make_ascii_uppercase();
+    }
+    cursor.move_prev();
+    cursor.remove_current();
+    assert_eq!(list.iter().map(String::as_str).collect::<Vec<_>>(), ["a!", "C!", "d"]);
+    println!("编辑后: {:?}", list);
+
+    println!("\n裸指针版本:");
+    println!("  - 节点之间是 NonNull，没有引用计数和 RefCell 的运行时开销");
+    println!("  - front / iter / iter_mut 直接借出 &T / &mut T，生命周期绑定在链表上");
+    println!("  - 所有 unsafe 都依赖模块文档中列出的不变量，编译器不再帮忙检查");
+    println!("  - 这个示例和示例 30 可以用 cargo +nightly miri run 检查");
+}
+
+/// 示例 30: 双向链表 - 随机操作检查与性能
+///
+/// 两种链表执行同样的随机操作，结果与 VecDeque 对照
+pub fn linked_list_checks() {
+    println!("\n=== 示例 30: 双向链表 - 随机操作检查与性能 ===");
+
+    use linked_list::LinkedList;
+    use raw_list::RawList;
+    use std::collections::VecDeque;
+    use std::time::Instant;
+
+    let mut rng = XorShift::new(2024);
+
+    // Miri 逐条解释执行，比正常运行慢几千倍，在 Miri 下减小规模
+    let (rounds, n) = if cfg!(miri) { (30, 2_000u64) } else { (300, 200_000u64) };
+    let mut operations = 0;
+    for _ in 0..rounds {
+        let mut safe = LinkedList::new();
+        let mut raw = RawList::new();
+        let mut model: VecDeque<u64> = VecDeque::new();
+
+        for _ in 0..40 {
+            let value = rng.below(1000);
+            operations += 1;
+            match rng.below(5) {
+                0 => {
+                    safe.push_front(value);
+                    raw.push_front(value);
+                    model.push_front(value);
+                }
+                1 => {
+                    safe.push_back(value);
+                    raw.push_back(value);
+                    model.push_back(value);
+                }
+                2 => {
+                    let expected = model.pop_front();
+                    assert_eq!(safe.pop_front(), expected);
+                    assert_eq!(raw.pop_front(), expected);
+                }
+                3 => {
+                    let expected = model.pop_back();
+                    assert_eq!(safe.pop_back(), expected);
+                    assert_eq!(raw.pop_back(), expected);
+                }
+                _ => {
+                    // 两个光标走同样的步数，做同样的编辑；model 用下标模拟
+                    let mut a = safe.cursor_front_mut();
+                    let mut b = raw.cursor_front_mut();
+                    let mut position = if model.is_empty() { None } else { Some(0) };
+                    for _ in 0..rng.below(6) {
+                        let step = rng.below(5);
+                        match step {
+                            0 => {
+                                a.move_next();
+                                b.move_next();
+                                position = match position {
+                                    Some(i) if i + 1 < model.len() => Some(i + 1),
+                                    Some(_) => None,
+                                    None if model.is_empty() => None,
+                                    None => Some(0),
+                                };
+                            }
+                            1 => {
+                                a.move_prev();
+                                b.move_prev();
+                                position = match position {
+                                    Some(0) => None,
+                                    Some(i) => Some(i - 1),
+                                    None => model.len().checked_sub(1),
+                                };
+                            }
+                            2 => {
+                                a.insert_before(value);
+                                b.insert_before(value);
+                                match position {
+                                    Some(i) => {
+                                        model.insert(i, value);
+                                        position = Some(i + 1);
+                                    }
+                                    None => model.push_back(value),
+                                }
+                            }
+                            3 => {
+                                a.insert_after(value);
+                                b.insert_after(value);
+                                match position {
+                                    Some(i) => model.insert(i + 1, value),
+                                    None => model.push_front(value),
+                                }
+                            }
+                            _ => {
+                                let expected = position.map(|i| model.remove(i).unwrap());
+                                assert_eq!(a.remove_current(), expected);
+                                assert_eq!(b.remove_current(), expected);
+                                if position.is_some_and(|i| i >= model.len()) {
+                                    position = None;
+                                }
+                            }
+                        }
+                        operations += 1;
+                        assert_eq!(a.index(), position);
+                        assert_eq!(b.index(), position);
+                        let expected = position.map(|i| model[i]);
+                        assert_eq!(a.current().map(|v| *v), expected);
+                        assert_eq!(b.current().copied(), expected);
+                    }
+                }
+            }
+            assert_eq!(safe.len(), model.len());
+            assert_eq!(raw.len(), model.len());
+            assert_eq!(safe.is_empty(), model.is_empty());
+            assert_eq!(raw.is_empty(), model.is_empty());
+        }
+        let expected: Vec<u64> = model.iter().copied().collect();
+        assert_eq!(safe.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(raw.iter().copied().collect::<Vec<_>>(), expected);
+        let reversed: Vec<u64> = model.iter().rev().copied().collect();
+        assert_eq!(safe.iter().rev().collect::<Vec<_>>(), reversed);
+        assert_eq!(raw.iter().rev().copied().collect::<Vec<_>>(), reversed);
+    }
+    println!("✓ {} 轮共 {} 次随机操作（两端 push / pop 和光标编辑），两种链表都与 VecDeque 一致", rounds, operations);
+
+    // 性能：push_back n 个元素，遍历求和，再全部 pop_front
+    let start = Instant::now();
+    let mut safe = LinkedList::new();
+    (0..n).for_each(|i| safe.push_back(i));
+    let safe_sum: u64 = safe.iter().sum();
+    while safe.pop_front().is_some() {}
+    let safe_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut raw = RawList::new();
+    (0..n).for_each(|i| raw.push_back(i));
+    let raw_sum: u64 = raw.iter().sum();
+    while raw.pop_front().is_some() {}
+    let raw_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut std_list = std::collections::LinkedList::new();
+    (0..n).for_each(|i| std_list.push_back(i));
+    let std_sum: u64 = std_list.iter().sum();
+    while std_list.pop_front().is_some() {}
+    let std_time = start.elapsed();
+
+    assert_eq!(safe_sum, raw_sum);
+    assert_eq!(raw_sum, std_sum);
+    println!("\n{} 个元素 push_back + 遍历 + pop_front:", n);
+    println!("  {:<30} {:?}", "Rc + Weak", safe_time);
+    println!("  {:<30} {:?}", "裸指针", raw_time);
+    println!("  {:<30} {:?}", "std::collections::LinkedList", std_time);
+
+    println!("\n结论:");
+    println!("  - 随机操作和模型对照，能覆盖光标在两端、幽灵位置和空链表的边界情况");
+    println!("  - Rc + Weak 版本多了计数更新、RefCell 检查和 upgrade，明显更慢");
+    println!("  - 裸指针版本和标准库的实现思路相同，速度在同一个数量级");
+}
+/// 示例 31: Cow 文本规范化流水线
+///
+/// 每一步只在需要修改时才分配
+pub fn text_normalize_pipeline() {
+    println!("\n=== 示例 31: Cow 文本规范化流水线 ===");
+
+    use normalize::{Pipeline, Stage};
+
+    let pipeline = Pipeline::standard();
+    let inputs = [
+        "rust 所有权",
+        "  rust 所有权  ",
+        "Rust  所有权",
+        "ＲＵＳＴ　２０２４！",
+        "\u{FEFF}Hello,\t\tWorld\u{200B}\n",
+        "已经是规范的文本",
+    ];
+    for input in inputs {
+        let output = pipeline.run(input);
+        let kind = match output {
+            Cow::Borrowed(_) => "借用",
+            Cow::Owned(_) => "拥有",
+        };
+        println!("{:<28} -> {:<20} ({})", format!("{:?}", input), format!("{:?}", output), kind);
+    }
+    println!("\n{}", pipeline.stats());
+
+    // 自定义步骤：示例 11 的替换也可以作为流水线的一步
+    struct Replace {
+        from: &'static str,
+        to: &'static str,
+    }
+
+    impl Stage for Replace {
+        fn name(&self) -> &'static str {
+            "replace"
+        }
+
+        fn needs_change(&self, text: &str) -> bool {
+            text.contains(self.from)
+        }
+
+        fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+            Cow::Owned(text.replace(self.from, self.to))
+        }
+    }
+
+    let custom = Pipeline::standard().stage(Replace { from: "bad", to: "good" });
+    for input in ["this is a good text", "this is a BAD text"] {
+        let output = custom.run(input);
+        println!("{:?} -> {:?} (借用: {})", input, output, matches!(output, Cow::Borrowed(_)));
+    }
+    print!("{}", custom.stats());
+
+    println!("\n要点:");
+    println!("  - 每一步的签名都是 Cow<'a, str> -> Cow<'a, str>，可以任意组合");
+    println!("  - 不需要修改的步骤原样返回，借用的输入一路保持借用");
+    println!("  - trim 只取子切片，即使修改了也不分配");
+    println!("  - 已经是 String 的输入尽量原地修改（truncate / retain / make_ascii_lowercase）");
+}
+
+/// 示例 32: 文本规范化 - 检查与性能
+///
+/// 和每一步都分配的朴素实现对照
+pub fn text_normalize_checks() {
+    println!("\n=== 示例 32: 文本规范化 - 检查与性能 ===");
+
+    use normalize::Pipeline;
+    use std::time::Instant;
+
+    // 朴素实现：每一步都生成新的 String
+    fn naive(input: &str) -> String {
+        let stripped: String = input
+            .chars()
+            .filter(|&c| !((c.is_control() && !c.is_whitespace()) || matches!(c, '\u{200B}'..='\u{200D}' | '\u{FEFF}')))
+            .collect();
+        let trimmed = stripped.trim().to_string();
+        let half: String = trimmed
+            .chars()
+            .map(|c| match c {
+                '\u{3000}' => ' ',
+                '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap(),
+                _ => c,
+            })
+            .collect();
+        let collapsed = half.split_whitespace().collect::<Vec<_>>().join(" ");
+        collapsed.to_lowercase()
+    }
+
+    let mut rng = XorShift::new(40);
+    let alphabet = ['a', 'B', '中', '文', ' ', ' ', '\t', '\n', '\u{3000}', 'Ａ', '１', '！', '\u{200B}', '\u{7}', '-'];
+    let pipeline = Pipeline::standard();
+    for _ in 0..2000 {
+        let len = rng.below(12) as usize;
+        let input: String = (0..len).map(|_| *rng.pick(&alphabet)).collect();
+        let output = pipeline.run(&input);
+        assert_eq!(output, naive(&input), "输入 {:?}", input);
+
+        // 规范化是幂等的：再跑一遍必然借用着通过，并且就是同一块内存
+        let again = pipeline.run(&output);
+        assert!(matches!(again, Cow::Borrowed(_)));
+        assert_eq!(again.as_ptr(), output.as_ptr());
+        assert_eq!(again, output);
+    }
+    let stats = pipeline.stats();
+    assert_eq!(stats.inputs, 4000);
+    assert!(stats.borrowed >= 2000);
+    println!("✓ 2000 个随机输入与朴素实现结果一致，第二遍全部借用通过");
+    print!("{}", stats);
+
+    // 性能：大部分输入本来就是规范的
+    let clean: Vec<String> = (0..50_000).map(|i| format!("第 {} 行 rust 所有权与借用", i)).collect();
+    let dirty: Vec<String> = (0..50_000).map(|i| format!("  第 {} 行  Rust　所有权！ ", i)).collect();
+    for (label, inputs) in [("已规范的输入", &clean), ("需要修改的输入", &dirty)] {
+        let pipeline = Pipeline::standard();
+        let start = Instant::now();
+        let total: usize = inputs.iter().map(|s| pipeline.run(s).len()).sum();
+        let cow_time = start.elapsed();
+
+        let start = Instant::now();
+        let naive_total: usize = inputs.iter().map(|s| naive(s).len()).sum();
+        let naive_time = start.elapsed();
+
+        assert_eq!(total, naive_total);
+        let stats = pipeline.stats();
+        println!(
+            "\n{} {} 个：借用通过 {} 个，分配 {} 次",
+            label,
+            stats.inputs,
+            stats.borrowed,
+            stats.total_allocations()
+        );
+        println!("  {:<12} {:?}", "Cow 流水线", cow_time);
+        println!("  {:<12} {:?}", "朴素实现", naive_time);
+    }
+
+    println!("\n结论:");
+    println!("  - 已规范的输入只做扫描，不分配，比朴素实现快得多");
+    println!("  - 需要修改时 Cow 版本也只在真正改动的步骤分配");
+    println!("  - 幂等性检查保证第二遍不会做任何修改");
 }
 
 /// 运行所有示例
@@ -631,6 +2114,23 @@ pub fn run_all_examples() {
     custom_smart_pointer_rc();
     practical_graph();
     practical_cache();
+    graph_weak_edges();
+    graph_algorithms();
+    graph_design_comparison();
+    cache_eviction_policies();
+    cache_ttl_expiry();
+    cache_sharded_benchmark();
+    my_rc_weak();
+    my_arc_threads();
+    my_pointer_checks();
+    arena_basics();
+    arena_tree_and_graph();
+    arena_expr_tree();
+    linked_list_rc();
+    linked_list_raw();
+    linked_list_checks();
+    text_normalize_pipeline();
+    text_normalize_checks();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  智能指针是 Rust 的强大工具！        ║");
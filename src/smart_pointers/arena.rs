@@ -0,0 +1,216 @@
+//! # 类型化 arena
+//!
+//! `box_recursive_types` 和 `practical_graph` 里每个节点都是一次单独的堆分配，
+//! 节点之间用 `Box` / `Rc` 相连。arena 把同一类对象集中存放在一个 `Vec` 里，
+//! 用句柄（下标）代替指针：
+//!
+//! - 分配只是往 `Vec` 里 push，`Vec` 扩容时才真正向系统申请内存
+//! - 整个 arena 一次性释放，不需要逐个节点递归 drop
+//! - 句柄是 `Copy` 的小整数，可以随意形成环，没有引用计数也没有借用冲突
+//!
+//! 下标的问题是删除后槽位会被复用，旧的下标可能指向新的对象。
+//! 每个槽位记录一个代数（generation），删除时加一；句柄同时保存下标和代数，
+//! 代数对不上就说明句柄已经失效。
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// 指向 `Arena<T>` 中一个值的句柄
+///
+/// 类型参数让 `Id<A>` 不能用在 `Arena<B>` 上。`PhantomData<fn() -> T>`
+/// 不拥有 T，所以 `Id<T>` 总是 `Copy`、`Send`、`Sync`，与 T 无关。
+pub struct Id<T> {
+    index: u32,
+    generation: u32,
+    _type: PhantomData<fn() -> T>,
+}
+
+// 手写而不是 derive：derive 会要求 T 也实现这些 trait
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    /// 例如 `Id(3v1)`：第 3 个槽位，第 1 代
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Id({}v{})", self.index, self.generation)
+    }
+}
+
+enum Slot<T> {
+    Occupied { generation: u32, value: T },
+    /// 空闲的槽位串成链表，`next_free` 是下一个空闲槽位
+    Free { generation: u32, next_free: Option<u32> },
+}
+
+/// 存放同一类型值的 arena
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free_head: Option<u32>,
+    len: usize,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// 预先分配 `capacity` 个槽位，之后的 alloc 不会再向系统申请内存
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            slots: Vec::with_capacity(capacity),
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    /// 存入一个值，优先复用被删除的槽位
+    pub fn alloc(&mut self, value: T) -> Id<T> {
+        self.len += 1;
+        if let Some(index) = self.free_head {
+            let slot = &mut self.slots[index as usize];
+            let Slot::Free { generation, next_free } = *slot else {
+                unreachable!("空闲链表指向了被占用的槽位");
+            };
+            self.free_head = next_free;
+            *slot = Slot::Occupied { generation, value };
+            return Id {
+                index,
+                generation,
+                _type: PhantomData,
+            };
+        }
+
+        let index = u32::try_from(self.slots.len()).expect("arena 最多存放 u32::MAX 个值");
+        self.slots.push(Slot::Occupied { generation: 0, value });
+        Id {
+            index,
+            generation: 0,
+            _type: PhantomData,
+        }
+    }
+
+    /// 句柄失效（值已被删除）时返回 None
+    pub fn get(&self, id: Id<T>) -> Option<&T> {
+        match self.slots.get(id.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, id: Id<T>) -> Option<&mut T> {
+        match self.slots.get_mut(id.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, id: Id<T>) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// 删除并返回值；槽位的代数加一，所有指向它的旧句柄随之失效
+    pub fn remove(&mut self, id: Id<T>) -> Option<T> {
+        if !self.contains(id) {
+            return None;
+        }
+        let free = Slot::Free {
+            // 代数用完后回绕：同一个槽位被复用 2^32 次后旧句柄才可能误判
+            generation: id.generation.wrapping_add(1),
+            next_free: self.free_head,
+        };
+        let Slot::Occupied { value, .. } = std::mem::replace(&mut self.slots[id.index as usize], free) else {
+            unreachable!();
+        };
+        self.free_head = Some(id.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// 一次性删除所有值，保留已分配的内存；所有旧句柄都失效
+    pub fn clear(&mut self) {
+        let mut next_free = None;
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            let generation = match slot {
+                Slot::Occupied { generation, .. } => generation.wrapping_add(1),
+                Slot::Free { generation, .. } => *generation,
+            };
+            *slot = Slot::Free { generation, next_free };
+            next_free = Some(index as u32);
+        }
+        self.free_head = next_free;
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 已分配的槽位数（包括空闲的）
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// 所有存活的值和它们的句柄，按槽位顺序
+    pub fn iter(&self) -> impl Iterator<Item = (Id<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| match slot {
+            Slot::Occupied { generation, value } => Some((
+                Id {
+                    index: index as u32,
+                    generation: *generation,
+                    _type: PhantomData,
+                },
+                value,
+            )),
+            Slot::Free { .. } => None,
+        })
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<Id<T>> for Arena<T> {
+    type Output = T;
+
+    /// # Panics
+    ///
+    /// 句柄已失效时 panic
+    fn index(&self, id: Id<T>) -> &T {
+        self.get(id).unwrap_or_else(|| panic!("{:?} 已失效", id))
+    }
+}
+
+impl<T> IndexMut<Id<T>> for Arena<T> {
+    fn index_mut(&mut self, id: Id<T>) -> &mut T {
+        self.get_mut(id).unwrap_or_else(|| panic!("{:?} 已失效", id))
+    }
+}
@@ -0,0 +1,123 @@
+//! # 建在 arena 上的表达式树
+//!
+//! `enums::expr::Expr` 的每个子节点都是一个 `Box`；`ExprTree` 把同样的节点存进 `Arena<Node>`，
+//! 子节点用 `NodeId` 引用。求值复用 `enums::expr` 的 `checked_div`、`checked_pow` 和 `call_builtin`，
+//! 溢出检查和错误位置的规则也相同，所以两种表示的结果和错误完全一致。
+//!
+//! 需要在 main.rs 中同时启用 `mod enums;`。
+
+use super::arena::{Arena, Id};
+use crate::enums::expr::{Env, Expr, ExprError, Span, call_builtin, checked_div, checked_pow, is_conditional};
+
+pub type NodeId = Id<Node>;
+
+/// 和 `Expr` 一一对应，只是子节点换成了句柄
+pub enum Node {
+    Number(f64),
+    Var(String),
+    Neg(NodeId),
+    Add(NodeId, NodeId),
+    Subtract(NodeId, NodeId),
+    Multiply(NodeId, NodeId),
+    Divide(NodeId, NodeId),
+    Pow(NodeId, NodeId),
+    Call(String, Vec<NodeId>),
+    Spanned(Span, NodeId),
+}
+
+/// 一棵表达式树：所有节点和根节点的句柄
+pub struct ExprTree {
+    nodes: Arena<Node>,
+    root: NodeId,
+}
+
+impl ExprTree {
+    /// 从 `Expr` 转换，子节点先于父节点存入 arena
+    pub fn from_expr(expr: &Expr) -> Self {
+        let mut nodes = Arena::new();
+        let root = Self::insert(&mut nodes, expr);
+        ExprTree { nodes, root }
+    }
+
+    fn insert(nodes: &mut Arena<Node>, expr: &Expr) -> NodeId {
+        let mut child = |e: &Expr| Self::insert(nodes, e);
+        let node = match expr {
+            Expr::Number(n) => Node::Number(*n),
+            Expr::Var(name) => Node::Var(name.clone()),
+            Expr::Neg(inner) => Node::Neg(child(inner)),
+            Expr::Add(l, r) => Node::Add(child(l), child(r)),
+            Expr::Subtract(l, r) => Node::Subtract(child(l), child(r)),
+            Expr::Multiply(l, r) => Node::Multiply(child(l), child(r)),
+            Expr::Divide(l, r) => Node::Divide(child(l), child(r)),
+            Expr::Pow(l, r) => Node::Pow(child(l), child(r)),
+            Expr::Call(name, args) => Node::Call(name.clone(), args.iter().map(child).collect()),
+            Expr::Spanned(span, inner) => Node::Spanned(*span, child(inner)),
+        };
+        nodes.alloc(node)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// 转换回 `Expr`
+    pub fn to_expr(&self) -> Expr {
+        self.build(self.root)
+    }
+
+    fn build(&self, id: NodeId) -> Expr {
+        let b = |id: &NodeId| Box::new(self.build(*id));
+        match &self.nodes[id] {
+            Node::Number(n) => Expr::Number(*n),
+            Node::Var(name) => Expr::Var(name.clone()),
+            Node::Neg(inner) => Expr::Neg(b(inner)),
+            Node::Add(l, r) => Expr::Add(b(l), b(r)),
+            Node::Subtract(l, r) => Expr::Subtract(b(l), b(r)),
+            Node::Multiply(l, r) => Expr::Multiply(b(l), b(r)),
+            Node::Divide(l, r) => Expr::Divide(b(l), b(r)),
+            Node::Pow(l, r) => Expr::Pow(b(l), b(r)),
+            Node::Call(name, args) => Expr::Call(name.clone(), args.iter().map(|id| self.build(*id)).collect()),
+            Node::Spanned(span, inner) => Expr::Spanned(*span, b(inner)),
+        }
+    }
+
+    /// 与 `Expr::eval_with` 的规则相同
+    pub fn eval_with(&self, env: &Env) -> Result<f64, ExprError> {
+        self.eval_node(self.root, env)
+    }
+
+    fn eval_node(&self, id: NodeId, env: &Env) -> Result<f64, ExprError> {
+        let eval = |id: &NodeId| self.eval_node(*id, env);
+        let value = match &self.nodes[id] {
+            Node::Number(n) => *n,
+            Node::Var(name) => match env.get(name) {
+                Some(value) => *value,
+                None => {
+                    return Err(ExprError::UndefinedVariable {
+                        name: name.clone(),
+                        span: None,
+                    });
+                }
+            },
+            Node::Neg(inner) => -eval(inner)?,
+            Node::Add(l, r) => eval(l)? + eval(r)?,
+            Node::Subtract(l, r) => eval(l)? - eval(r)?,
+            Node::Multiply(l, r) => eval(l)? * eval(r)?,
+            Node::Divide(l, r) => checked_div(eval(l)?, eval(r)?)?,
+            Node::Pow(l, r) => checked_pow(eval(l)?, eval(r)?)?,
+            Node::Call(name, args) if is_conditional(name, args.len()) => {
+                let branch = if eval(&args[0])? != 0.0 { &args[1] } else { &args[2] };
+                eval(branch)?
+            }
+            Node::Call(name, args) => {
+                let args = args.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+                call_builtin(name, &args)?
+            }
+            Node::Spanned(span, inner) => return eval(inner).map_err(|e| e.or_at(*span)),
+        };
+        if value.is_infinite() {
+            return Err(ExprError::Overflow { span: None });
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,484 @@
+//! # 缓存
+//!
+//! `practical_cache`（示例 15）是一个没有容量上限的 `Arc<Mutex<HashMap>>`。
+//! 这里实现三种有界缓存和一个分片的并发包装：
+//!
+//! - `LruCache`：淘汰最久没有被访问的条目，HashMap + 双向链表，所有操作 O(1)
+//! - `LfuCache`：淘汰访问次数最少的条目（次数相同时淘汰最久没访问的），O(log n)
+//! - `TtlCache`：条目在写入 `ttl` 之后过期，时间来自可注入的 `Clock`
+//! - `Sharded<C>`：把键按哈希分到多个各自加锁的缓存里，减少锁竞争
+//!
+//! 所有缓存都统计命中、未命中、淘汰和过期次数。
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::util::Clock;
+
+/// 命中率等统计信息
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// 因为容量已满被淘汰的条目数
+    pub evictions: u64,
+    /// 因为过期被删除的条目数
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+
+    fn record(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+    }
+}
+
+impl std::ops::Add for CacheStats {
+    type Output = CacheStats;
+
+    fn add(self, other: CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            evictions: self.evictions + other.evictions,
+            expirations: self.expirations + other.expirations,
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "命中 {}，未命中 {}，命中率 {:.1}%，淘汰 {}，过期 {}",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.evictions,
+            self.expirations
+        )
+    }
+}
+
+/// 缓存的公共接口
+pub trait Cache<K, V> {
+    /// 查找，命中时可能更新条目的访问信息，所以需要 `&mut self`
+    fn get(&mut self, key: &K) -> Option<&V>;
+
+    /// 插入或更新，容量已满时先淘汰一个条目
+    fn put(&mut self, key: K, value: V);
+
+    fn len(&self) -> usize;
+
+    fn stats(&self) -> CacheStats;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// ==================== LRU ====================
+
+/// 链表的"空指针"
+const NIL: usize = usize::MAX;
+
+/// 链表节点存在 Vec 里，prev / next 是下标
+struct LruEntry<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+/// 最近最少使用（Least Recently Used）缓存
+///
+/// `map` 从键找到节点下标，双向链表按访问时间排序：
+/// 头部是最近访问的，尾部是最久没访问的，淘汰时直接取尾部。
+pub struct LruCache<K, V> {
+    capacity: usize,
+    map: HashMap<K, usize>,
+    entries: Vec<LruEntry<K, V>>,
+    head: usize,
+    tail: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// # Panics
+    ///
+    /// `capacity` 为 0 时 panic
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "容量必须大于 0");
+        LruCache {
+            capacity,
+            map: HashMap::with_capacity(capacity),
+            entries: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// 从最近访问到最久没访问的键
+    pub fn keys(&self) -> Vec<&K> {
+        let mut keys = Vec::with_capacity(self.entries.len());
+        let mut index = self.head;
+        while index != NIL {
+            keys.push(&self.entries[index].key);
+            index = self.entries[index].next;
+        }
+        keys
+    }
+
+    /// 把节点从链表中摘下
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = (self.entries[index].prev, self.entries[index].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next].prev = prev,
+        }
+    }
+
+    /// 把节点放到链表头部
+    fn push_front(&mut self, index: usize) {
+        self.entries[index].prev = NIL;
+        self.entries[index].next = self.head;
+        match self.head {
+            NIL => self.tail = index,
+            head => self.entries[head].prev = index,
+        }
+        self.head = index;
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let index = self.map.get(key).copied();
+        self.stats.record(index.is_some());
+        let index = index?;
+        self.unlink(index);
+        self.push_front(index);
+        Some(&self.entries[index].value)
+    }
+
+    fn put(&mut self, key: K, value: V) {
+        if let Some(&index) = self.map.get(&key) {
+            self.entries[index].value = value;
+            self.unlink(index);
+            self.push_front(index);
+            return;
+        }
+
+        let index = if self.entries.len() < self.capacity {
+            self.entries.push(LruEntry {
+                key: key.clone(),
+                value,
+                prev: NIL,
+                next: NIL,
+            });
+            self.entries.len() - 1
+        } else {
+            // 已满：复用尾部节点的位置，不需要重新分配
+            let index = self.tail;
+            self.unlink(index);
+            let old = std::mem::replace(
+                &mut self.entries[index],
+                LruEntry {
+                    key: key.clone(),
+                    value,
+                    prev: NIL,
+                    next: NIL,
+                },
+            );
+            self.map.remove(&old.key);
+            self.stats.evictions += 1;
+            index
+        };
+        self.map.insert(key, index);
+        self.push_front(index);
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+// ==================== LFU ====================
+
+/// 最不经常使用（Least Frequently Used）缓存
+///
+/// 每个条目记录访问次数和最后访问的时刻（一个递增的计数器），
+/// `order` 按 (次数, 时刻) 排序，第一个就是要淘汰的条目。
+pub struct LfuCache<K, V> {
+    capacity: usize,
+    map: HashMap<K, (V, u64, u64)>,
+    order: BTreeMap<(u64, u64), K>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    /// # Panics
+    ///
+    /// `capacity` 为 0 时 panic
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "容量必须大于 0");
+        LfuCache {
+            capacity,
+            map: HashMap::with_capacity(capacity),
+            order: BTreeMap::new(),
+            tick: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// 键的访问次数
+    pub fn frequency(&self, key: &K) -> Option<u64> {
+        self.map.get(key).map(|(_, frequency, _)| *frequency)
+    }
+
+    /// 访问次数加一，并更新排序索引
+    fn touch(&mut self, key: &K) {
+        self.tick += 1;
+        let (_, frequency, last) = self.map.get_mut(key).unwrap();
+        let owned = self.order.remove(&(*frequency, *last)).unwrap();
+        *frequency += 1;
+        *last = self.tick;
+        self.order.insert((*frequency, *last), owned);
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LfuCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let hit = self.map.contains_key(key);
+        self.stats.record(hit);
+        if !hit {
+            return None;
+        }
+        self.touch(key);
+        self.map.get(key).map(|(value, _, _)| value)
+    }
+
+    fn put(&mut self, key: K, value: V) {
+        if self.map.contains_key(&key) {
+            self.map.get_mut(&key).unwrap().0 = value;
+            self.touch(&key);
+            return;
+        }
+
+        if self.map.len() == self.capacity {
+            let (_, victim) = self.order.pop_first().unwrap();
+            self.map.remove(&victim);
+            self.stats.evictions += 1;
+        }
+        self.tick += 1;
+        self.order.insert((1, self.tick), key.clone());
+        self.map.insert(key, (value, 1, self.tick));
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+// ==================== TTL ====================
+
+/// 按写入时间过期的缓存
+///
+/// 所有条目的存活时间相同，所以过期顺序就是写入顺序：
+/// `queue` 按写入顺序记录 (过期时刻, 写入序号, 键)。更新一个键时旧的队列项不删除，
+/// 出队时和 `map` 中的写入序号对不上就说明已经作废，直接跳过。
+///
+/// 反复更新同一个键会留下很多作废的队列项，队列长度超过容量的两倍时整体清理一次，
+/// 清理后只剩有效的项（不超过容量），所以均摊下来每次写入仍是 O(1)。
+pub struct TtlCache<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    /// 值、过期时刻、写入序号
+    map: HashMap<K, (V, Instant, u64)>,
+    queue: VecDeque<(Instant, u64, K)>,
+    next_seq: u64,
+    clock: Arc<dyn Clock>,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> TtlCache<K, V> {
+    /// # Panics
+    ///
+    /// `capacity` 为 0 时 panic
+    pub fn new(capacity: usize, ttl: Duration, clock: Arc<dyn Clock>) -> Self {
+        assert!(capacity > 0, "容量必须大于 0");
+        TtlCache {
+            capacity,
+            ttl,
+            map: HashMap::with_capacity(capacity),
+            queue: VecDeque::new(),
+            next_seq: 0,
+            clock,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// 删除所有已过期的条目，返回删除的数量
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut purged = 0;
+        while let Some((expires, _, _)) = self.queue.front() {
+            if *expires > now {
+                break;
+            }
+            let (_, seq, key) = self.queue.pop_front().unwrap();
+            if self.is_current(&key, seq) {
+                self.map.remove(&key);
+                self.stats.expirations += 1;
+                purged += 1;
+            }
+        }
+        purged
+    }
+
+    /// 键还剩多久过期
+    pub fn time_to_live(&self, key: &K) -> Option<Duration> {
+        let (_, expires, _) = self.map.get(key)?;
+        expires.checked_duration_since(self.clock.now()).filter(|d| !d.is_zero())
+    }
+
+    /// 过期队列的长度，包括还没清理的作废项；不会超过容量的两倍
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 队列项是否还对应 `map` 中的条目
+    fn is_current(&self, key: &K, seq: u64) -> bool {
+        self.map.get(key).is_some_and(|&(_, _, current)| current == seq)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for TtlCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        // 先清理，过期的条目就不会被当作命中
+        self.purge_expired();
+        let hit = self.map.contains_key(key);
+        self.stats.record(hit);
+        self.map.get(key).map(|(value, _, _)| value)
+    }
+
+    fn put(&mut self, key: K, value: V) {
+        self.purge_expired();
+        if self.map.len() == self.capacity && !self.map.contains_key(&key) {
+            // 没有过期的条目可以清理：淘汰最早过期的那个
+            while let Some((_, seq, victim)) = self.queue.pop_front() {
+                if self.is_current(&victim, seq) {
+                    self.map.remove(&victim);
+                    self.stats.evictions += 1;
+                    break;
+                }
+            }
+        }
+        let expires = self.clock.now() + self.ttl;
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.queue.push_back((expires, seq, key.clone()));
+        self.map.insert(key, (value, expires, seq));
+        if self.queue.len() > 2 * self.capacity {
+            let map = &self.map;
+            self.queue
+                .retain(|(_, seq, key)| map.get(key).is_some_and(|&(_, _, current)| current == *seq));
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+// ==================== 分片 ====================
+
+/// 分片的并发缓存
+///
+/// 单个 `Mutex<C>` 让所有线程排队；分成 N 片后，访问不同分片的线程互不阻塞。
+/// 每个分片是独立的缓存，淘汰也只在分片内部进行。
+pub struct Sharded<C> {
+    shards: Vec<Mutex<C>>,
+}
+
+impl<C> Sharded<C> {
+    /// 用 `make` 创建 `count` 个分片
+    ///
+    /// # Panics
+    ///
+    /// `count` 为 0 时 panic
+    pub fn new(count: usize, make: impl Fn() -> C) -> Self {
+        assert!(count > 0, "分片数必须大于 0");
+        Sharded {
+            shards: (0..count).map(|_| Mutex::new(make())).collect(),
+        }
+    }
+
+    fn shard<K: Hash>(&self, key: &K) -> &Mutex<C> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+
+    /// 查找，返回值的克隆（锁在返回前就释放了，不能返回引用）
+    pub fn get<K: Hash, V: Clone>(&self, key: &K) -> Option<V>
+    where
+        C: Cache<K, V>,
+    {
+        self.shard(key).lock().unwrap().get(key).cloned()
+    }
+
+    pub fn put<K: Hash, V>(&self, key: K, value: V)
+    where
+        C: Cache<K, V>,
+    {
+        self.shard(&key).lock().unwrap().put(key, value);
+    }
+
+    /// 所有分片的统计之和
+    pub fn stats<K, V>(&self) -> CacheStats
+    where
+        C: Cache<K, V>,
+    {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().stats())
+            .fold(CacheStats::default(), |total, stats| total + stats)
+    }
+
+    pub fn len<K, V>(&self) -> usize
+    where
+        C: Cache<K, V>,
+    {
+        self.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
+    }
+}
@@ -0,0 +1,360 @@
+//! # 图
+//!
+//! `practical_graph`（示例 14）用 `Rc<Node>` 保存邻居，只要图中有环，
+//! 引用计数就永远不会归零，整个环都会泄漏。这里给出两种不泄漏的设计：
+//!
+//! - `RcGraph`：图本身用 `Rc` 持有所有节点，节点之间的边是 `Weak`，
+//!   边不增加强引用计数，所以有环也能在图被丢弃时全部释放
+//! - `Graph<N>`：索引式 arena，节点和边都存在 `Vec` 里，用下标互相引用，
+//!   根本没有引用计数
+//!
+//! 两种设计都实现 `Neighbors` trait，BFS、DFS、拓扑排序、环检测和 Dijkstra
+//! 只依赖这个 trait，可以直接用于任何一种。
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::rc::{Rc, Weak};
+
+use thiserror::Error;
+
+/// 节点编号，就是节点在图中的下标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+/// 图算法需要的最小接口
+pub trait Neighbors {
+    /// 节点数，节点编号为 0..node_count
+    fn node_count(&self) -> usize;
+
+    /// 节点的出边：(目标节点, 权重)
+    fn neighbors(&self, node: NodeId) -> Vec<(NodeId, u32)>;
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum GraphError {
+    #[error("图中存在环: {path:?}")]
+    Cycle { path: Vec<NodeId> },
+}
+
+// ==================== 设计一：Rc + Weak ====================
+
+/// 带权重的边，目标节点用 Weak 引用
+struct RcEdge {
+    to: Weak<RcNode>,
+    weight: u32,
+}
+
+/// `RcGraph` 中的节点
+pub struct RcNode {
+    id: NodeId,
+    name: String,
+    edges: RefCell<Vec<RcEdge>>,
+}
+
+impl RcNode {
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 邻居节点；图已经被丢弃时 upgrade 失败，邻居为空
+    pub fn neighbors(&self) -> Vec<Rc<RcNode>> {
+        self.edges
+            .borrow()
+            .iter()
+            .filter_map(|edge| edge.to.upgrade())
+            .collect()
+    }
+}
+
+/// 节点由图强引用持有，边是 Weak
+#[derive(Default)]
+pub struct RcGraph {
+    nodes: Vec<Rc<RcNode>>,
+}
+
+impl RcGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, name: &str) -> Rc<RcNode> {
+        let node = Rc::new(RcNode {
+            id: NodeId(self.nodes.len()),
+            name: name.to_string(),
+            edges: RefCell::new(Vec::new()),
+        });
+        self.nodes.push(Rc::clone(&node));
+        node
+    }
+
+    /// 添加一条有向边，不增加 `to` 的强引用计数
+    ///
+    /// 用 `NodeId` 而不是 `Rc<RcNode>` 指定端点，边就只能连到本图的节点
+    pub fn connect(&self, from: NodeId, to: NodeId, weight: u32) {
+        self.nodes[from.0].edges.borrow_mut().push(RcEdge {
+            to: Rc::downgrade(&self.nodes[to.0]),
+            weight,
+        });
+    }
+
+    pub fn node(&self, id: NodeId) -> &Rc<RcNode> {
+        &self.nodes[id.0]
+    }
+}
+
+impl Neighbors for RcGraph {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn neighbors(&self, node: NodeId) -> Vec<(NodeId, u32)> {
+        self.nodes[node.0]
+            .edges
+            .borrow()
+            .iter()
+            .filter_map(|edge| edge.to.upgrade().map(|to| (to.id, edge.weight)))
+            .collect()
+    }
+}
+
+// ==================== 设计二：索引 arena ====================
+
+/// 索引式的图：节点数据存在 `Vec<N>` 里，邻接表也用下标表示
+pub struct Graph<N> {
+    nodes: Vec<N>,
+    adjacency: Vec<Vec<(NodeId, u32)>>,
+}
+
+impl<N> Graph<N> {
+    pub fn new() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            adjacency: Vec::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, data: N) -> NodeId {
+        self.nodes.push(data);
+        self.adjacency.push(Vec::new());
+        NodeId(self.nodes.len() - 1)
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: u32) {
+        self.adjacency[from.0].push((to, weight));
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id.0]
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.adjacency.iter().map(Vec::len).sum()
+    }
+}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> Neighbors for Graph<N> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn neighbors(&self, node: NodeId) -> Vec<(NodeId, u32)> {
+        self.adjacency[node.0].clone()
+    }
+}
+
+// ==================== 图算法 ====================
+
+/// 广度优先遍历，返回访问顺序
+pub fn bfs(graph: &impl Neighbors, start: NodeId) -> Vec<NodeId> {
+    let mut visited = vec![false; graph.node_count()];
+    let mut order = Vec::new();
+    let mut queue = VecDeque::from([start]);
+    visited[start.0] = true;
+
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for (next, _) in graph.neighbors(node) {
+            if !visited[next.0] {
+                visited[next.0] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    order
+}
+
+/// 深度优先遍历（前序），用显式的栈代替递归，深的图也不会栈溢出
+pub fn dfs(graph: &impl Neighbors, start: NodeId) -> Vec<NodeId> {
+    let mut visited = vec![false; graph.node_count()];
+    let mut order = Vec::new();
+    let mut stack = vec![start];
+
+    while let Some(node) = stack.pop() {
+        if visited[node.0] {
+            continue;
+        }
+        visited[node.0] = true;
+        order.push(node);
+        // 逆序压栈，先访问第一个邻居，和递归版本的顺序一致
+        for (next, _) in graph.neighbors(node).into_iter().rev() {
+            if !visited[next.0] {
+                stack.push(next);
+            }
+        }
+    }
+    order
+}
+
+/// 查找一个有向环，返回环上的节点（首尾相同）
+pub fn find_cycle(graph: &impl Neighbors) -> Option<Vec<NodeId>> {
+    // 三色标记：未访问 / 在当前路径上 / 已完成
+    #[derive(Clone, Copy, PartialEq)]
+    enum Color {
+        White,
+        Gray,
+        Black,
+    }
+
+    // DFS 栈中的一帧：节点、它的出边、下一条要检查的出边
+    struct Frame {
+        node: NodeId,
+        edges: Vec<(NodeId, u32)>,
+        next_edge: usize,
+    }
+
+    let n = graph.node_count();
+    let mut color = vec![Color::White; n];
+    let frame = |node: NodeId| Frame {
+        node,
+        edges: graph.neighbors(node),
+        next_edge: 0,
+    };
+
+    for root in 0..n {
+        if color[root] != Color::White {
+            continue;
+        }
+        color[root] = Color::Gray;
+        let mut path = vec![frame(NodeId(root))];
+
+        while let Some(top) = path.last_mut() {
+            let Some(&(next, _)) = top.edges.get(top.next_edge) else {
+                // 所有出边都检查完了，节点出栈
+                let done = path.pop().unwrap();
+                color[done.node.0] = Color::Black;
+                continue;
+            };
+            top.next_edge += 1;
+
+            match color[next.0] {
+                Color::White => {
+                    color[next.0] = Color::Gray;
+                    path.push(frame(next));
+                }
+                // 指向当前路径上的节点：找到环
+                Color::Gray => {
+                    let start = path.iter().position(|f| f.node == next).unwrap();
+                    let mut cycle: Vec<NodeId> = path[start..].iter().map(|f| f.node).collect();
+                    cycle.push(next);
+                    return Some(cycle);
+                }
+                Color::Black => {}
+            }
+        }
+    }
+    None
+}
+
+/// 拓扑排序（Kahn 算法）：有环时返回环上的节点
+pub fn topological_sort(graph: &impl Neighbors) -> Result<Vec<NodeId>, GraphError> {
+    let n = graph.node_count();
+    let mut in_degree = vec![0; n];
+    for node in 0..n {
+        for (next, _) in graph.neighbors(NodeId(node)) {
+            in_degree[next.0] += 1;
+        }
+    }
+
+    let mut ready: VecDeque<NodeId> = (0..n).filter(|&i| in_degree[i] == 0).map(NodeId).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(node) = ready.pop_front() {
+        order.push(node);
+        for (next, _) in graph.neighbors(node) {
+            in_degree[next.0] -= 1;
+            if in_degree[next.0] == 0 {
+                ready.push_back(next);
+            }
+        }
+    }
+
+    if order.len() == n {
+        Ok(order)
+    } else {
+        let path = find_cycle(graph).expect("拓扑排序没有覆盖所有节点，说明一定有环");
+        Err(GraphError::Cycle { path })
+    }
+}
+
+/// Dijkstra 的结果：到每个节点的最短距离和前驱
+pub struct ShortestPaths {
+    distance: Vec<Option<u64>>,
+    previous: Vec<Option<NodeId>>,
+}
+
+impl ShortestPaths {
+    /// 到 `target` 的最短距离，不可达时为 None
+    pub fn distance(&self, target: NodeId) -> Option<u64> {
+        self.distance[target.0]
+    }
+
+    /// 从起点到 `target` 的最短路径
+    pub fn path_to(&self, target: NodeId) -> Option<Vec<NodeId>> {
+        self.distance[target.0]?;
+        let mut path = vec![target];
+        let mut current = target;
+        while let Some(prev) = self.previous[current.0] {
+            path.push(prev);
+            current = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// 单源最短路径（边权非负）
+pub fn dijkstra(graph: &impl Neighbors, source: NodeId) -> ShortestPaths {
+    let n = graph.node_count();
+    let mut distance = vec![None; n];
+    let mut previous = vec![None; n];
+    // BinaryHeap 是最大堆，用 Reverse 变成最小堆
+    let mut heap = BinaryHeap::from([Reverse((0u64, source))]);
+    distance[source.0] = Some(0);
+
+    while let Some(Reverse((dist, node))) = heap.pop() {
+        // 同一个节点可能多次入堆，只处理距离最新的那次
+        if distance[node.0].is_some_and(|best| dist > best) {
+            continue;
+        }
+        for (next, weight) in graph.neighbors(node) {
+            let candidate = dist + weight as u64;
+            if distance[next.0].is_none_or(|best| candidate < best) {
+                distance[next.0] = Some(candidate);
+                previous[next.0] = Some(node);
+                heap.push(Reverse((candidate, next)));
+            }
+        }
+    }
+
+    ShortestPaths { distance, previous }
+}
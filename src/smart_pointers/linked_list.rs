@@ -0,0 +1,392 @@
+//! # 双向链表：Rc + Weak 版本
+//!
+//! `rc_refcell_combination`（示例 6）只修改共享的值，这里用同样的组合搭一个有前后指针的结构：
+//!
+//! ```text
+//! head ──► [1] ══► [2] ══► [3] ◄── tail (Weak)
+//!           ▲ ┄┄┄┄┄ ┘ ▲ ┄┄┄┄┄ ┘
+//! ══► next: Rc<RefCell<Node>>（拥有下一个节点）
+//! ┄┄► prev: Weak<RefCell<Node>>（不拥有，避免引用环）
+//! ```
+//!
+//! 完全没有 unsafe，代价是每次访问都要借用 `RefCell`，并且不能借出 `&T`：
+//! 引用的生命周期会被 `Ref` 守卫限制，所以迭代器返回克隆的值。
+//! 对照的 unsafe 版本见 `raw_list`。
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+
+type Link<T> = Rc<RefCell<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    next: Option<Link<T>>,
+    prev: Option<Weak<RefCell<Node<T>>>>,
+}
+
+/// 双向链表
+pub struct LinkedList<T> {
+    head: Option<Link<T>>,
+    tail: Option<Weak<RefCell<Node<T>>>>,
+    len: usize,
+}
+
+fn new_node<T>(value: T) -> Link<T> {
+    Rc::new(RefCell::new(Node {
+        value,
+        next: None,
+        prev: None,
+    }))
+}
+
+/// 节点已经从链表中摘下、没有其他强引用时取出值
+fn into_value<T>(node: Link<T>) -> T {
+    match Rc::try_unwrap(node) {
+        Ok(cell) => cell.into_inner().value,
+        Err(_) => unreachable!("摘下的节点不应再被共享"),
+    }
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn tail_node(&self) -> Option<Link<T>> {
+        self.tail.as_ref().and_then(Weak::upgrade)
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let node = new_node(value);
+        match self.head.take() {
+            Some(old) => {
+                old.borrow_mut().prev = Some(Rc::downgrade(&node));
+                node.borrow_mut().next = Some(old);
+            }
+            None => self.tail = Some(Rc::downgrade(&node)),
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let node = new_node(value);
+        match self.tail_node() {
+            Some(old) => {
+                node.borrow_mut().prev = Some(Rc::downgrade(&old));
+                self.tail = Some(Rc::downgrade(&node));
+                old.borrow_mut().next = Some(node);
+            }
+            None => {
+                self.tail = Some(Rc::downgrade(&node));
+                self.head = Some(node);
+            }
+        }
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let old = self.head.take()?;
+        match old.borrow_mut().next.take() {
+            Some(next) => {
+                next.borrow_mut().prev = None;
+                self.head = Some(next);
+            }
+            None => self.tail = None,
+        }
+        self.len -= 1;
+        Some(into_value(old))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let old = self.tail_node()?;
+        let prev = old.borrow_mut().prev.take().and_then(|p| p.upgrade());
+        match prev {
+            Some(prev) => {
+                // 前一个节点的 next 是 old 的唯一强引用
+                prev.borrow_mut().next = None;
+                self.tail = Some(Rc::downgrade(&prev));
+            }
+            None => {
+                self.head = None;
+                self.tail = None;
+            }
+        }
+        self.len -= 1;
+        Some(into_value(old))
+    }
+
+    /// 第一个元素；返回的 `Ref` 存在期间不能修改链表
+    pub fn front(&self) -> Option<Ref<'_, T>> {
+        self.head.as_ref().map(|node| Ref::map(node.borrow(), |n| &n.value))
+    }
+
+    /// 最后一个元素的克隆：tail 是 Weak，upgrade 出的 Rc 活不过这个函数，无法借出 Ref
+    pub fn back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.tail_node().map(|node| node.borrow().value.clone())
+    }
+
+    /// 双端迭代器，返回元素的克隆
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        Iter {
+            front: self.head.as_ref().map(Rc::downgrade),
+            back: self.tail.clone(),
+            remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    /// 光标，初始位置在第一个元素上（空链表时在"幽灵"位置）
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let current = self.head.clone();
+        CursorMut {
+            index: current.as_ref().map(|_| 0),
+            current,
+            list: self,
+        }
+    }
+
+    /// 在 `prev` 和 `next` 之间插入新节点，`None` 表示链表的一端
+    fn link_between(&mut self, prev: Option<Link<T>>, next: Option<Link<T>>, value: T) -> Link<T> {
+        let node = new_node(value);
+        match &next {
+            Some(next) => next.borrow_mut().prev = Some(Rc::downgrade(&node)),
+            None => self.tail = Some(Rc::downgrade(&node)),
+        }
+        node.borrow_mut().next = next;
+        match prev {
+            Some(prev) => {
+                node.borrow_mut().prev = Some(Rc::downgrade(&prev));
+                prev.borrow_mut().next = Some(Rc::clone(&node));
+            }
+            None => self.head = Some(Rc::clone(&node)),
+        }
+        self.len += 1;
+        node
+    }
+
+    /// 摘下节点，返回它原来的下一个节点
+    fn unlink(&mut self, node: &Link<T>) -> Option<Link<T>> {
+        let mut inner = node.borrow_mut();
+        let next = inner.next.take();
+        let prev = inner.prev.take().and_then(|p| p.upgrade());
+        drop(inner);
+        match &next {
+            Some(next) => next.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev.as_ref().map(Rc::downgrade),
+        }
+        match prev {
+            Some(prev) => prev.borrow_mut().next = next.clone(),
+            None => self.head = next.clone(),
+        }
+        self.len -= 1;
+        next
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 逐个弹出，避免默认的递归 drop 在长链表上栈溢出
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        for value in iter {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug + Clone> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// 借用迭代器（返回克隆的值）
+///
+/// 两端保存的是 Weak：如果保存 Rc，迭代器在作用域结束前都会让节点多一个强引用，
+/// 之后删除这个节点时就无法用 `try_unwrap` 取回其中的值
+pub struct Iter<'a, T> {
+    front: Option<Weak<RefCell<Node<T>>>>,
+    back: Option<Weak<RefCell<Node<T>>>>,
+    // 两端相遇时停止
+    remaining: usize,
+    _list: PhantomData<&'a LinkedList<T>>,
+}
+
+impl<T: Clone> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.take()?.upgrade()?;
+        self.remaining -= 1;
+        let node = node.borrow();
+        self.front = node.next.as_ref().map(Rc::downgrade);
+        Some(node.value.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.take()?.upgrade()?;
+        self.remaining -= 1;
+        let node = node.borrow();
+        self.back = node.prev.clone();
+        Some(node.value.clone())
+    }
+}
+
+/// 拥有所有权的迭代器
+pub struct IntoIter<T>(LinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+/// 可修改链表的光标
+///
+/// 和标准库的 `CursorMut` 一样，尾部和头部之间有一个不对应任何元素的"幽灵"位置，
+/// 从最后一个元素 `move_next` 会到达幽灵位置，再 `move_next` 回到第一个元素。
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    /// None 表示幽灵位置
+    current: Option<Link<T>>,
+    index: Option<usize>,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// 当前元素的下标，幽灵位置为 None
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// 当前元素
+    pub fn current(&mut self) -> Option<RefMut<'_, T>> {
+        self.current
+            .as_ref()
+            .map(|node| RefMut::map(node.borrow_mut(), |n| &mut n.value))
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = node.borrow().next.clone();
+                self.index = self.current.as_ref().map(|_| self.index.unwrap() + 1);
+            }
+            None => {
+                self.current = self.list.head.clone();
+                self.index = self.current.as_ref().map(|_| 0);
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = node.borrow().prev.as_ref().and_then(Weak::upgrade);
+                self.index = self.current.as_ref().map(|_| self.index.unwrap() - 1);
+            }
+            None => {
+                self.current = self.list.tail_node();
+                self.index = self.current.as_ref().map(|_| self.list.len - 1);
+            }
+        }
+    }
+
+    /// 在当前元素之前插入；在幽灵位置时插入到链表末尾
+    pub fn insert_before(&mut self, value: T) {
+        match &self.current {
+            Some(node) => {
+                let prev = node.borrow().prev.as_ref().and_then(Weak::upgrade);
+                self.list.link_between(prev, Some(Rc::clone(node)), value);
+                self.index = self.index.map(|i| i + 1);
+            }
+            None => self.list.push_back(value),
+        }
+    }
+
+    /// 在当前元素之后插入；在幽灵位置时插入到链表开头
+    pub fn insert_after(&mut self, value: T) {
+        match &self.current {
+            Some(node) => {
+                let next = node.borrow().next.clone();
+                self.list.link_between(Some(Rc::clone(node)), next, value);
+            }
+            None => self.list.push_front(value),
+        }
+    }
+
+    /// 删除当前元素，光标移到下一个元素（可能是幽灵位置）
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current.take()?;
+        self.current = self.list.unlink(&node);
+        if self.current.is_none() {
+            self.index = None;
+        }
+        Some(into_value(node))
+    }
+}
@@ -0,0 +1,147 @@
+//! # 自己实现 Arc
+//!
+//! 和 `MyRc` 的结构相同，计数换成 `AtomicUsize`，可以跨线程共享。
+//! 难点在内存序（ordering）：
+//!
+//! - clone 只需要 `Relaxed`：已经持有一个引用，计数不可能在此期间归零
+//! - drop 用 `Release` 递减：本线程对值的所有访问都发生在递减之前
+//! - 最后一个引用在析构前用 `Acquire` 栅栏：看到其他线程 drop 前的所有访问，
+//!   保证析构不会和其他线程对值的读取交错
+//!
+//! 为了把注意力放在原子操作上，这里没有实现 Weak。
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+
+struct ArcInner<T> {
+    strong: AtomicUsize,
+    value: ManuallyDrop<T>,
+}
+
+/// 线程安全的引用计数指针
+pub struct MyArc<T> {
+    ptr: NonNull<ArcInner<T>>,
+    _owns: PhantomData<ArcInner<T>>,
+}
+
+// 把 MyArc 发送到另一个线程等于让两个线程共享 &T（需要 Sync），
+// 最后一个引用所在的线程会析构 T（需要 Send）
+unsafe impl<T: Send + Sync> Send for MyArc<T> {}
+unsafe impl<T: Send + Sync> Sync for MyArc<T> {}
+
+/// 计数超过这个值就终止进程：其他线程还来不及 abort 时计数也不会回绕到 0
+const MAX_COUNT: usize = isize::MAX as usize;
+
+impl<T> MyArc<T> {
+    pub fn new(value: T) -> Self {
+        let boxed = Box::new(ArcInner {
+            strong: AtomicUsize::new(1),
+            value: ManuallyDrop::new(value),
+        });
+        MyArc {
+            ptr: NonNull::from(Box::leak(boxed)),
+            _owns: PhantomData,
+        }
+    }
+
+    fn inner(&self) -> &ArcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// 其他线程随时可能改变计数，结果只能作为参考
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.load(Ordering::Relaxed)
+    }
+
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// 唯一的引用时返回可变引用
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        // Acquire 与其他引用 drop 时的 Release 配对，看到它们对值的全部访问
+        if this.inner().strong.load(Ordering::Acquire) == 1 {
+            Some(unsafe { &mut (*this.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    /// 写时复制
+    ///
+    /// 持有 `&mut this` 时其他线程无法通过它 clone，计数只会减少，
+    /// 所以检查到 1 之后它就一直是 1
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: Clone,
+    {
+        if this.inner().strong.load(Ordering::Acquire) != 1 {
+            *this = MyArc::new((**this).clone());
+        }
+        unsafe { &mut (*this.ptr.as_ptr()).value }
+    }
+
+    /// 唯一的引用时取出值，否则原样返回
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        // 从 1 改成 0 成功，就没有任何其他引用了
+        if this
+            .inner()
+            .strong
+            .compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(this);
+        }
+        let inner = this.ptr.as_ptr();
+        mem::forget(this);
+        unsafe {
+            let value = ManuallyDrop::take(&mut (*inner).value);
+            drop(Box::from_raw(inner));
+            Ok(value)
+        }
+    }
+}
+
+impl<T> Clone for MyArc<T> {
+    fn clone(&self) -> Self {
+        if self.inner().strong.fetch_add(1, Ordering::Relaxed) > MAX_COUNT {
+            std::process::abort();
+        }
+        MyArc {
+            ptr: self.ptr,
+            _owns: PhantomData,
+        }
+    }
+}
+
+impl<T> Deref for MyArc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T> Drop for MyArc<T> {
+    fn drop(&mut self) {
+        if self.inner().strong.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        atomic::fence(Ordering::Acquire);
+        unsafe {
+            let inner = self.ptr.as_ptr();
+            ManuallyDrop::drop(&mut (*inner).value);
+            drop(Box::from_raw(inner));
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MyArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,214 @@
+//! # 自己实现 Rc 和 Weak
+//!
+//! `custom_smart_pointer_rc`（示例 13）的 `SimpleRc` 把值和计数分别放在两个堆块里，
+//! 而且没有 `Weak`。这里按照标准库的思路重新实现：
+//!
+//! ```text
+//! MyRc ─┐
+//! MyRc ─┼──► RcBox { strong, weak, value }   一个堆块
+//! MyWeak┘
+//! ```
+//!
+//! - `strong` 是 `MyRc` 的个数，归零时析构 `value`
+//! - `weak` 是 `MyWeak` 的个数，再加上所有 `MyRc` 共同持有的 1，归零时释放堆块
+//!
+//! 这样值可以先于堆块被析构：`MyWeak` 还在时堆块不能释放，但 `upgrade` 会失败。
+
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+
+/// 堆上的块：两个计数和值
+struct RcBox<T> {
+    strong: Cell<usize>,
+    weak: Cell<usize>,
+    // 值的析构由 strong 计数控制，不能随堆块一起自动析构
+    value: ManuallyDrop<T>,
+}
+
+/// 单线程引用计数指针
+///
+/// `NonNull` 不是 `Send` / `Sync`，所以 `MyRc` 也不是：
+/// `Cell` 计数没有同步，跨线程共享会产生数据竞争。
+pub struct MyRc<T> {
+    ptr: NonNull<RcBox<T>>,
+    // 告诉编译器 MyRc 逻辑上拥有一个 T（影响 drop 检查）
+    _owns: PhantomData<RcBox<T>>,
+}
+
+/// 不增加强引用计数的弱指针
+pub struct MyWeak<T> {
+    ptr: NonNull<RcBox<T>>,
+}
+
+fn increment(count: &Cell<usize>) {
+    // 计数溢出后会错误地提前释放，标准库也是直接终止进程
+    if count.get() == usize::MAX {
+        std::process::abort();
+    }
+    count.set(count.get() + 1);
+}
+
+impl<T> MyRc<T> {
+    pub fn new(value: T) -> Self {
+        let boxed = Box::new(RcBox {
+            strong: Cell::new(1),
+            weak: Cell::new(1),
+            value: ManuallyDrop::new(value),
+        });
+        MyRc {
+            ptr: NonNull::from(Box::leak(boxed)),
+            _owns: PhantomData,
+        }
+    }
+
+    fn inner(&self) -> &RcBox<T> {
+        // 只要还有 MyRc，堆块就一定有效
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.get()
+    }
+
+    /// `MyWeak` 的个数（不含所有强引用共同持有的那 1 个）
+    pub fn weak_count(this: &Self) -> usize {
+        this.inner().weak.get() - 1
+    }
+
+    pub fn downgrade(this: &Self) -> MyWeak<T> {
+        increment(&this.inner().weak);
+        MyWeak { ptr: this.ptr }
+    }
+
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// 没有其他 `MyRc` 和 `MyWeak` 时返回可变引用
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if this.inner().strong.get() == 1 && this.inner().weak.get() == 1 {
+            // 唯一的访问者，不会与其他引用重叠
+            Some(unsafe { &mut (*this.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    /// 写时复制：有其他 `MyRc` 时先克隆出独占的值
+    ///
+    /// 只有 `MyWeak` 时不需要克隆，把值搬到新的堆块，旧的 `MyWeak` 随后无法 upgrade
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: Clone,
+    {
+        if this.inner().strong.get() != 1 {
+            *this = MyRc::new((**this).clone());
+        } else if this.inner().weak.get() != 1 {
+            let old = this.ptr.as_ptr();
+            unsafe {
+                let value = ManuallyDrop::take(&mut (*old).value);
+                (*old).strong.set(0);
+                // 旧块交给剩下的 MyWeak 释放；ptr::write 不会运行旧 MyRc 的 drop
+                (*old).weak.set((*old).weak.get() - 1);
+                ptr::write(this, MyRc::new(value));
+            }
+        }
+        unsafe { &mut (*this.ptr.as_ptr()).value }
+    }
+
+    /// 唯一的强引用时取出值，否则原样返回
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if this.inner().strong.get() != 1 {
+            return Err(this);
+        }
+        let inner = this.ptr.as_ptr();
+        let value = unsafe { ManuallyDrop::take(&mut (*inner).value) };
+        unsafe { (*inner).strong.set(0) };
+        // 所有强引用共同持有的那个弱计数，交给一个临时的 MyWeak 去释放
+        drop(MyWeak { ptr: this.ptr });
+        mem::forget(this);
+        Ok(value)
+    }
+}
+
+impl<T> Clone for MyRc<T> {
+    fn clone(&self) -> Self {
+        increment(&self.inner().strong);
+        MyRc {
+            ptr: self.ptr,
+            _owns: PhantomData,
+        }
+    }
+}
+
+impl<T> Deref for MyRc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T> Drop for MyRc<T> {
+    fn drop(&mut self) {
+        let inner = self.ptr.as_ptr();
+        unsafe {
+            let strong = (*inner).strong.get() - 1;
+            (*inner).strong.set(strong);
+            if strong == 0 {
+                ManuallyDrop::drop(&mut (*inner).value);
+                drop(MyWeak { ptr: self.ptr });
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MyRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> MyWeak<T> {
+    /// 值还没被析构时得到一个新的强引用
+    pub fn upgrade(&self) -> Option<MyRc<T>> {
+        let inner = unsafe { self.ptr.as_ref() };
+        if inner.strong.get() == 0 {
+            return None;
+        }
+        increment(&inner.strong);
+        Some(MyRc {
+            ptr: self.ptr,
+            _owns: PhantomData,
+        })
+    }
+
+    pub fn strong_count(&self) -> usize {
+        unsafe { self.ptr.as_ref() }.strong.get()
+    }
+}
+
+impl<T> Clone for MyWeak<T> {
+    fn clone(&self) -> Self {
+        increment(&unsafe { self.ptr.as_ref() }.weak);
+        MyWeak { ptr: self.ptr }
+    }
+}
+
+impl<T> Drop for MyWeak<T> {
+    fn drop(&mut self) {
+        let inner = self.ptr.as_ptr();
+        unsafe {
+            let weak = (*inner).weak.get() - 1;
+            (*inner).weak.set(weak);
+            if weak == 0 {
+                // value 是 ManuallyDrop，这里只释放内存，值早已析构
+                drop(Box::from_raw(inner));
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! # Cow 文本规范化流水线
+//!
+//! `cow_practical`（示例 11）的 `process_text` 只替换一个词。这里把规范化拆成若干步骤，
+//! 每一步接收 `Cow<str>`、返回 `Cow<str>`：
+//!
+//! - 不需要修改时原样返回：借用的输入一路保持借用，一次分配都没有
+//! - 需要修改时才生成 `String`；已经是 `String` 的尽量原地修改
+//!
+//! 实际数据里大部分输入本来就是规范的，这时整条流水线都不分配。
+//! `Pipeline` 用 `Cell` 记录每一步修改和分配了多少次，以及有多少输入借用着通过。
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::fmt;
+
+/// 流水线中的一步
+pub trait Stage {
+    fn name(&self) -> &'static str;
+
+    /// 是否需要修改；返回 false 时不会调用 `apply`
+    fn needs_change(&self, text: &str) -> bool;
+
+    /// 执行修改
+    fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str>;
+}
+
+/// 去掉首尾空白
+pub struct Trim;
+
+impl Stage for Trim {
+    fn name(&self) -> &'static str {
+        "trim"
+    }
+
+    fn needs_change(&self, text: &str) -> bool {
+        text.trim().len() != text.len()
+    }
+
+    /// 借用的输入取子切片即可，修改了也不分配
+    fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+        match text {
+            Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+            Cow::Owned(mut s) => {
+                let end = s.trim_end().len();
+                s.truncate(end);
+                let start = s.len() - s.trim_start().len();
+                s.drain(..start);
+                Cow::Owned(s)
+            }
+        }
+    }
+}
+
+/// 连续的空白（包括制表符、换行）合并成一个空格
+pub struct CollapseWhitespace;
+
+impl Stage for CollapseWhitespace {
+    fn name(&self) -> &'static str {
+        "collapse_whitespace"
+    }
+
+    fn needs_change(&self, text: &str) -> bool {
+        let mut prev_space = false;
+        text.chars().any(|c| {
+            let space = c.is_whitespace();
+            let bad = space && (c != ' ' || prev_space);
+            prev_space = space;
+            bad
+        })
+    }
+
+    fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+        let mut out = String::with_capacity(text.len());
+        let mut prev_space = false;
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !prev_space {
+                    out.push(' ');
+                }
+                prev_space = true;
+            } else {
+                out.push(c);
+                prev_space = false;
+            }
+        }
+        Cow::Owned(out)
+    }
+}
+
+/// 全角字符转半角：全角空格 U+3000 和 U+FF01..=U+FF5E（全角的 ASCII 字母、数字、标点）
+///
+/// 中文输入法常常混入全角的数字和标点，比如 "ＲＵＳＴ　２０２４！"
+pub struct FullWidthToHalfWidth;
+
+impl FullWidthToHalfWidth {
+    fn convert(c: char) -> Option<char> {
+        match c {
+            '\u{3000}' => Some(' '),
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0),
+            _ => None,
+        }
+    }
+}
+
+impl Stage for FullWidthToHalfWidth {
+    fn name(&self) -> &'static str {
+        "full_to_half_width"
+    }
+
+    fn needs_change(&self, text: &str) -> bool {
+        text.chars().any(|c| Self::convert(c).is_some())
+    }
+
+    // 全角字符占 3 个字节，半角只占 1 个，无法原地替换
+    fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+        Cow::Owned(text.chars().map(|c| Self::convert(c).unwrap_or(c)).collect())
+    }
+}
+
+/// 转小写
+pub struct Lowercase;
+
+impl Stage for Lowercase {
+    fn name(&self) -> &'static str {
+        "lowercase"
+    }
+
+    // 汉字没有大小写，纯中文的输入不会被修改
+    fn needs_change(&self, text: &str) -> bool {
+        text.chars().any(char::is_uppercase)
+    }
+
+    /// 大写字母都是 ASCII 时原地修改（中英混排的常见情况）；
+    /// 其他情况交给 `to_lowercase`，非 ASCII 字母的小写可能比大写长
+    fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+        match text {
+            Cow::Owned(mut s) if !s.chars().any(|c| !c.is_ascii() && c.is_uppercase()) => {
+                s.make_ascii_lowercase();
+                Cow::Owned(s)
+            }
+            other => Cow::Owned(other.to_lowercase()),
+        }
+    }
+}
+
+/// 删除控制字符（保留空白，交给 `CollapseWhitespace`）和零宽字符
+///
+/// 从网页复制的中文里经常夹着零宽空格 U+200B 和 BOM U+FEFF
+pub struct StripControl;
+
+impl StripControl {
+    fn is_stripped(c: char) -> bool {
+        (c.is_control() && !c.is_whitespace()) || matches!(c, '\u{200B}'..='\u{200D}' | '\u{FEFF}')
+    }
+}
+
+impl Stage for StripControl {
+    fn name(&self) -> &'static str {
+        "strip_control"
+    }
+
+    fn needs_change(&self, text: &str) -> bool {
+        text.chars().any(Self::is_stripped)
+    }
+
+    fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+        match text {
+            Cow::Borrowed(s) => Cow::Owned(s.chars().filter(|&c| !Self::is_stripped(c)).collect()),
+            Cow::Owned(mut s) => {
+                s.retain(|c| !Self::is_stripped(c));
+                Cow::Owned(s)
+            }
+        }
+    }
+}
+
+struct StageEntry {
+    stage: Box<dyn Stage>,
+    changed: Cell<usize>,
+    allocations: Cell<usize>,
+}
+
+/// 按顺序执行的若干步骤
+///
+/// 计数器放在 `Cell` 里，`run` 只需要 `&self`
+pub struct Pipeline {
+    stages: Vec<StageEntry>,
+    inputs: Cell<usize>,
+    borrowed: Cell<usize>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline {
+            stages: Vec::new(),
+            inputs: Cell::new(0),
+            borrowed: Cell::new(0),
+        }
+    }
+
+    /// 常用的五步
+    ///
+    /// 顺序影响分配次数：`Trim` 放在前面，只有首尾空白的输入可以借用着通过；
+    /// `StripControl` 放在 `Trim` 之前，被删掉的零宽字符后面露出的空白还能被去掉。
+    pub fn standard() -> Self {
+        Pipeline::new()
+            .stage(StripControl)
+            .stage(Trim)
+            .stage(FullWidthToHalfWidth)
+            .stage(CollapseWhitespace)
+            .stage(Lowercase)
+    }
+
+    /// 在末尾追加一步
+    pub fn stage(mut self, stage: impl Stage + 'static) -> Self {
+        self.stages.push(StageEntry {
+            stage: Box::new(stage),
+            changed: Cell::new(0),
+            allocations: Cell::new(0),
+        });
+        self
+    }
+
+    /// 依次执行每一步；没有任何一步分配时，返回的是输入的子切片
+    pub fn run<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(input);
+        for entry in &self.stages {
+            if !entry.stage.needs_change(&text) {
+                continue;
+            }
+            let was_borrowed = matches!(text, Cow::Borrowed(_));
+            let old_ptr = text.as_ptr();
+            text = entry.stage.apply(text);
+            entry.changed.set(entry.changed.get() + 1);
+            // 借用变成拥有，或者换了一块新的缓冲区，才算一次分配
+            if matches!(text, Cow::Owned(_)) && (was_borrowed || text.as_ptr() != old_ptr) {
+                entry.allocations.set(entry.allocations.get() + 1);
+            }
+        }
+        self.inputs.set(self.inputs.get() + 1);
+        if matches!(text, Cow::Borrowed(_)) {
+            self.borrowed.set(self.borrowed.get() + 1);
+        }
+        text
+    }
+
+    /// 到目前为止的计数
+    pub fn stats(&self) -> PipelineStats {
+        PipelineStats {
+            inputs: self.inputs.get(),
+            borrowed: self.borrowed.get(),
+            stages: self
+                .stages
+                .iter()
+                .map(|entry| StageStats {
+                    name: entry.stage.name(),
+                    changed: entry.changed.get(),
+                    allocations: entry.allocations.get(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageStats {
+    pub name: &'static str,
+    /// 这一步修改了多少个输入
+    pub changed: usize,
+    /// 其中需要新缓冲区的次数
+    pub allocations: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineStats {
+    pub inputs: usize,
+    /// 结果仍然借用输入（零分配）的个数
+    pub borrowed: usize,
+    pub stages: Vec<StageStats>,
+}
+
+impl PipelineStats {
+    pub fn total_allocations(&self) -> usize {
+        self.stages.iter().map(|s| s.allocations).sum()
+    }
+}
+
+impl fmt::Display for PipelineStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "输入 {} 个，借用通过 {} 个，共分配 {} 次",
+            self.inputs,
+            self.borrowed,
+            self.total_allocations()
+        )?;
+        for stage in &self.stages {
+            writeln!(
+                f,
+                "  {:<20} 修改 {:>3} 次，分配 {:>3} 次",
+                stage.name, stage.changed, stage.allocations
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! # 双向链表：裸指针版本
+//!
+//! 和 `linked_list::LinkedList` 的接口相同，节点之间改用 `NonNull` 相连：
+//! 没有引用计数和 `RefCell`，可以直接借出 `&T` / `&mut T`。
+//! 安全性完全由下面的不变量保证，编译器不再检查：
+//!
+//! - 每个节点由 `Box::leak` 创建，只属于一个链表，只在摘下后用 `Box::from_raw` 释放一次
+//! - `head` / `tail` 以及每个节点的 `prev` / `next` 总是指向链表中存活的节点
+//! - 借出的引用的生命周期与 `&self` / `&mut self` 绑定，借用期间链表不能被修改
+//!
+//! 用 `cargo +nightly miri run` 运行示例可以检查这些不变量是否被破坏。
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+struct Node<T> {
+    value: T,
+    next: Option<NonNull<Node<T>>>,
+    prev: Option<NonNull<Node<T>>>,
+}
+
+/// 双向链表
+pub struct RawList<T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    // 逻辑上拥有若干个 Box<Node<T>>
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+impl<T> RawList<T> {
+    pub fn new() -> Self {
+        RawList {
+            head: None,
+            tail: None,
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let head = self.head;
+        self.link_between(None, head, value);
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let tail = self.tail;
+        self.link_between(tail, None, value);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+        Some(unsafe { self.unlink(head) }.1)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        Some(unsafe { self.unlink(tail) }.1)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.head,
+            back: self.tail,
+            remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.head,
+            back: self.tail,
+            remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: self.head.map(|_| 0),
+            list: self,
+        }
+    }
+
+    fn link_between(&mut self, prev: Option<NonNull<Node<T>>>, next: Option<NonNull<Node<T>>>, value: T) {
+        let node = NonNull::from(Box::leak(Box::new(Node { value, next, prev })));
+        unsafe {
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(node),
+                None => self.head = Some(node),
+            }
+        }
+        self.len += 1;
+    }
+
+    /// 摘下并释放节点，返回它原来的下一个节点和值
+    ///
+    /// # Safety
+    ///
+    /// `node` 必须是这个链表中的节点
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> (Option<NonNull<Node<T>>>, T) {
+        let boxed = unsafe { Box::from_raw(node.as_ptr()) };
+        unsafe {
+            match boxed.next {
+                Some(next) => (*next.as_ptr()).prev = boxed.prev,
+                None => self.tail = boxed.prev,
+            }
+            match boxed.prev {
+                Some(prev) => (*prev.as_ptr()).next = boxed.next,
+                None => self.head = boxed.next,
+            }
+        }
+        self.len -= 1;
+        (boxed.next, boxed.value)
+    }
+}
+
+impl<T> Default for RawList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for RawList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> FromIterator<T> for RawList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = RawList::new();
+        for value in iter {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RawList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Option<NonNull<Node<T>>>,
+    back: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    _list: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.front.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.front = node.next;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.back.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.back = node.prev;
+            &node.value
+        })
+    }
+}
+
+/// 可变迭代器：`remaining` 保证两端不会交出同一个元素的两个 `&mut`
+pub struct IterMut<'a, T> {
+    front: Option<NonNull<Node<T>>>,
+    back: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    _list: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // 只借出 value 字段，不对整个节点创建 &mut，以免和之后读取 next 冲突
+        self.front.map(|node| unsafe {
+            self.front = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).value
+        })
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // 只借出 value 字段，不对整个节点创建 &mut，以免和之后读取 prev 冲突
+        self.back.map(|node| unsafe {
+            self.back = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).value
+        })
+    }
+}
+
+/// 光标，语义与 `linked_list::CursorMut` 相同
+pub struct CursorMut<'a, T> {
+    list: &'a mut RawList<T>,
+    current: Option<NonNull<Node<T>>>,
+    index: Option<usize>,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index = self.current.map(|_| self.index.unwrap() + 1);
+            }
+            None => {
+                self.current = self.list.head;
+                self.index = self.current.map(|_| 0);
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).prev };
+                self.index = self.current.map(|_| self.index.unwrap() - 1);
+            }
+            None => {
+                self.current = self.list.tail;
+                self.index = self.current.map(|_| self.list.len - 1);
+            }
+        }
+    }
+
+    pub fn insert_before(&mut self, value: T) {
+        match self.current {
+            Some(node) => {
+                let prev = unsafe { (*node.as_ptr()).prev };
+                self.list.link_between(prev, Some(node), value);
+                self.index = self.index.map(|i| i + 1);
+            }
+            None => self.list.push_back(value),
+        }
+    }
+
+    pub fn insert_after(&mut self, value: T) {
+        match self.current {
+            Some(node) => {
+                let next = unsafe { (*node.as_ptr()).next };
+                self.list.link_between(Some(node), next, value);
+            }
+            None => self.list.push_front(value),
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        let (next, value) = unsafe { self.list.unlink(node) };
+        self.current = next;
+        if next.is_none() {
+            self.index = None;
+        }
+        Some(value)
+    }
+}
@@ -412,47 +412,50 @@ pub fn practical_book_management() {
     println!("归还后: {}", book.info());
 }
 
-/// 示例 15: 实际应用 - 银行账户
-pub fn practical_bank_account() {
-    println!("\n=== 示例 15: 实际应用 - 银行账户 ===");
-
-    struct BankAccount {
-        account_number: String,
-        holder_name: String,
-        balance: f64,
-    }
+/// 银行账户
+///
+/// 定义在模块级别，示例 15 直接使用，示例 16 把它包装成 Actor
+pub struct BankAccount {
+    account_number: String,
+    holder_name: String,
+    balance: f64,
+}
 
-    impl BankAccount {
-        fn new(account_number: String, holder_name: String) -> Self {
-            BankAccount {
-                account_number,
-                holder_name,
-                balance: 0.0,
-            }
+impl BankAccount {
+    pub fn new(account_number: String, holder_name: String) -> Self {
+        BankAccount {
+            account_number,
+            holder_name,
+            balance: 0.0,
         }
+    }
 
-        fn deposit(&mut self, amount: f64) {
-            if amount > 0.0 {
-                self.balance += amount;
-                println!("存入 {:.2} 元，余额: {:.2} 元", amount, self.balance);
-            }
+    pub fn deposit(&mut self, amount: f64) {
+        if amount > 0.0 {
+            self.balance += amount;
+            println!("存入 {:.2} 元，余额: {:.2} 元", amount, self.balance);
         }
+    }
 
-        fn withdraw(&mut self, amount: f64) -> bool {
-            if amount > 0.0 && amount <= self.balance {
-                self.balance -= amount;
-                println!("取出 {:.2} 元，余额: {:.2} 元", amount, self.balance);
-                true
-            } else {
-                println!("余额不足或金额无效");
-                false
-            }
+    pub fn withdraw(&mut self, amount: f64) -> bool {
+        if amount > 0.0 && amount <= self.balance {
+            self.balance -= amount;
+            println!("取出 {:.2} 元，余额: {:.2} 元", amount, self.balance);
+            true
+        } else {
+            println!("余额不足或金额无效");
+            false
         }
+    }
 
-        fn get_balance(&self) -> f64 {
-            self.balance
-        }
+    pub fn get_balance(&self) -> f64 {
+        self.balance
     }
+}
+
+/// 示例 15: 实际应用 - 银行账户
+pub fn practical_bank_account() {
+    println!("\n=== 示例 15: 实际应用 - 银行账户 ===");
 
     let mut account = BankAccount::new(
         String::from("6222021234567890"),
@@ -469,6 +472,79 @@ pub fn practical_bank_account() {
     println!("最终余额: {:.2} 元", account.get_balance());
 }
 
+/// 示例 16: 实际应用 - 银行账户 Actor
+///
+/// 复用示例 15 的 BankAccount，把它放进 Actor（见 concurrency::actor），
+/// 多个线程通过消息并发地存取款，账户本身不需要任何锁
+pub fn practical_bank_account_actor() {
+    println!("\n=== 示例 16: 实际应用 - 银行账户 Actor ===");
+
+    use crate::concurrency::actor::{self, Actor, ReplyTo};
+    use std::thread;
+
+    // 账户支持的操作就是 Actor 的消息类型
+    enum AccountMsg {
+        Deposit(f64),
+        Withdraw { amount: f64, reply: ReplyTo<bool> },
+        Balance(ReplyTo<f64>),
+    }
+
+    // Actor 只是包了一层 BankAccount，每条消息对应一个已有的方法
+    struct AccountActor {
+        account: BankAccount,
+    }
+
+    impl Actor for AccountActor {
+        type Message = AccountMsg;
+
+        fn handle(&mut self, msg: AccountMsg) {
+            match msg {
+                AccountMsg::Deposit(amount) => self.account.deposit(amount),
+                AccountMsg::Withdraw { amount, reply } => reply.send(self.account.withdraw(amount)),
+                AccountMsg::Balance(reply) => reply.send(self.account.get_balance()),
+            }
+        }
+
+        fn stopped(&mut self) {
+            let account = &self.account;
+            println!("账户 {}（{}）关闭，余额 {:.2} 元", account.account_number, account.holder_name, account.balance);
+        }
+    }
+
+    let account = BankAccount::new(String::from("6222021234567890"), String::from("张三"));
+    let (addr, handle) = actor::spawn(AccountActor { account });
+
+    // 三个"柜台"线程同时存款
+    let tellers: Vec<_> = (1..=3)
+        .map(|i| {
+            let addr = addr.clone();
+            thread::spawn(move || {
+                addr.send(AccountMsg::Deposit(100.0 * i as f64)).unwrap();
+            })
+        })
+        .collect();
+    for teller in tellers {
+        teller.join().unwrap();
+    }
+
+    // 取款需要知道结果，使用请求/响应
+    let ok = addr.ask(|reply| AccountMsg::Withdraw { amount: 500.0, reply }).unwrap();
+    println!("取款 500.00 元: {}", if ok { "成功" } else { "失败" });
+    let ok = addr.ask(|reply| AccountMsg::Withdraw { amount: 500.0, reply }).unwrap();
+    println!("再取款 500.00 元: {}", if ok { "成功" } else { "失败" });
+
+    let balance = addr.ask(AccountMsg::Balance).unwrap();
+    println!("最终余额: {:.2} 元", balance);
+
+    addr.stop().unwrap();
+    handle.join();
+
+    println!("\n结构体 + Actor:");
+    println!("  - BankAccount 本身不变，Actor 只是把方法调用变成消息");
+    println!("  - 所有修改都在 Actor 线程中串行发生，不会出现数据竞争");
+    println!("  - 调用方通过消息和回复通道与账户交互");
+}
+
 /// 运行所有示例
 pub fn run_all_examples() {
     println!("╔════════════════════════════════════════╗");
@@ -490,6 +566,7 @@ pub fn run_all_examples() {
     struct_ownership();
     practical_book_management();
     practical_bank_account();
+    practical_bank_account_actor();
 
     println!("\n╔════════════════════════════════════════╗");
     println!("║  结构体是组织相关数据的强大工具！   ║");
@@ -0,0 +1,135 @@
+//! # 示例共用的小工具
+//!
+//! 各个教学模块可以在 main.rs 中单独启用，它们共用的、与教学主题无关的部分放在这里，
+//! 这样任何一个模块单独启用时都能编译。
+
+// 只启用部分模块时，其余模块用到的工具没有调用者
+#![allow(dead_code)]
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 可重现的伪随机数（xorshift64），固定种子得到固定的序列
+///
+/// 只用于生成随机测试输入和基准数据，不适合任何需要安全性的场合
+#[derive(Debug, Clone)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        // 状态为 0 时序列全是 0
+        XorShift(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// `[0, n)` 之间的整数（有轻微的取模偏差，对测试数据无所谓）
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// `[0, 1)` 之间的浮点数
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// 随机取切片中的一个元素
+    pub fn pick<'t, T>(&mut self, items: &'t [T]) -> &'t T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// 时钟抽象
+///
+/// 限流器、缓存过期、重试和断路器都只通过这个 trait 读取时间和等待，
+/// 换成 `FakeClock` 就不需要真的等待
+pub trait Clock: Send + Sync {
+    /// 当前时间
+    fn now(&self) -> Instant;
+
+    /// 等待一段时间
+    fn sleep(&self, duration: Duration);
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+/// 真实的系统时钟：`sleep` 会阻塞当前线程
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// 可手动推进的假时钟
+///
+/// `sleep` 不会阻塞，只记录时长并直接把时间往前拨，
+/// 所以阻塞的等待在假时钟上会立即返回，结果完全确定。
+pub struct FakeClock {
+    origin: Instant,
+    elapsed_nanos: AtomicU64,
+    sleeps: Mutex<Vec<Duration>>,
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        FakeClock {
+            origin: Instant::now(),
+            elapsed_nanos: AtomicU64::new(0),
+            sleeps: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 把时间往前推进 `duration`，不算作 sleep
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    /// 从创建到现在经过的（假）时间
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_nanos.load(Ordering::SeqCst))
+    }
+
+    /// 到目前为止每次 sleep 的时长
+    pub fn sleeps(&self) -> Vec<Duration> {
+        self.sleeps.lock().unwrap().clone()
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.sleeps.lock().unwrap().push(duration);
+        self.advance(duration);
+    }
+}